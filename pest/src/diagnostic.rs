@@ -0,0 +1,245 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::error;
+use std::fmt;
+
+use error::Error;
+use line_index::LineIndex;
+use renderer::Renderer;
+use span::Span;
+
+/// A `struct` which wraps an `Error` with the path of its file, an error code, secondary labeled
+/// `Span`s and `note:` and `help:` footers. Useful for errors found after parsing, e.g. when
+/// checking that keys are only defined once.
+///
+/// The `Error` is the primary annotation: its position or span is underlined with `^` and its
+/// message is reported below the input. Labeled spans are underlined with `-` and followed by
+/// their label.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::{Diagnostic, Error, Position};
+/// # #[allow(non_camel_case_types)]
+/// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+/// # enum Rule {
+/// #     a
+/// # }
+/// let input = "a = 1\nb = 2\na = 3";
+/// let first = Position::from_start(input);
+/// let second = first.clone().skip(12).unwrap();
+///
+/// let error: Error<Rule> = Error::CustomErrorSpan {
+///     message: "duplicate key `a`".to_owned(),
+///     span: second.clone().span(&second.clone().skip(1).unwrap())
+/// };
+/// let diagnostic = Diagnostic::new(error)
+///     .with_code("T001")
+///     .with_label(first.clone().span(&first.skip(1).unwrap()), "first defined here")
+///     .with_help("rename one of the keys");
+///
+/// assert_eq!(
+///     format!("{}", diagnostic),
+///     [
+///         " --> 3:1",
+///         "  |",
+///         "1 | a = 1",
+///         "  | - first defined here",
+///         "...",
+///         "3 | a = 3",
+///         "  | ^",
+///         "  |",
+///         "  = error[T001]: duplicate key `a`",
+///         "  = help: rename one of the keys"
+///     ].join("\n")
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Diagnostic<'i, R> {
+    error: Error<'i, R>,
+    path: Option<String>,
+    code: Option<String>,
+    labels: Vec<(Span<'i>, String)>,
+    notes: Vec<String>,
+    helps: Vec<String>
+}
+
+impl<'i, R> Diagnostic<'i, R> {
+    /// Creates a `Diagnostic` from an `error`, without a path, code, labels or footers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{Diagnostic, Error, Position};
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// # enum Rule {
+    /// #     a
+    /// # }
+    /// let error: Error<Rule> = Error::CustomErrorPos {
+    ///     message: "unexpected a".to_owned(),
+    ///     pos: Position::from_start("a")
+    /// };
+    /// let diagnostic = Diagnostic::new(error.clone());
+    ///
+    /// assert_eq!(format!("{}", diagnostic), format!("{}", error));
+    /// ```
+    pub fn new(error: Error<'i, R>) -> Diagnostic<'i, R> {
+        Diagnostic {
+            error,
+            path: None,
+            code: None,
+            labels: vec![],
+            notes: vec![],
+            helps: vec![]
+        }
+    }
+
+    /// Sets the `path` of the file the `Error` was found in, consuming the `Diagnostic`. It is
+    /// reported in front of the line and column as `--> path:line:col`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{Diagnostic, Error, Position};
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// # enum Rule {
+    /// #     a
+    /// # }
+    /// let error: Error<Rule> = Error::CustomErrorPos {
+    ///     message: "unexpected b".to_owned(),
+    ///     pos: Position::from_start("a\nb").skip(2).unwrap()
+    /// };
+    /// let diagnostic = Diagnostic::new(error).with_path("config.toml");
+    ///
+    /// assert!(format!("{}", diagnostic).starts_with(" --> config.toml:2:1\n"));
+    /// ```
+    #[inline]
+    pub fn with_path(mut self, path: &str) -> Diagnostic<'i, R> {
+        self.path = Some(path.to_owned());
+        self
+    }
+
+    /// Sets the error `code`, e.g. `"E001"`, consuming the `Diagnostic`. It is reported in front
+    /// of the message as `error[E001]: `.
+    #[inline]
+    pub fn with_code(mut self, code: &str) -> Diagnostic<'i, R> {
+        self.code = Some(code.to_owned());
+        self
+    }
+
+    /// Adds a `span` with a `label`, e.g. `"first defined here"`, consuming the `Diagnostic`.
+    /// The `span` must be in the same input as the `Error`.
+    #[inline]
+    pub fn with_label(mut self, span: Span<'i>, label: &str) -> Diagnostic<'i, R> {
+        self.labels.push((span, label.to_owned()));
+        self
+    }
+
+    /// Adds a `note` reported as `note: ` after the message, consuming the `Diagnostic`.
+    #[inline]
+    pub fn with_note(mut self, note: &str) -> Diagnostic<'i, R> {
+        self.notes.push(note.to_owned());
+        self
+    }
+
+    /// Adds a `help` reported as `help: ` after the notes, consuming the `Diagnostic`.
+    #[inline]
+    pub fn with_help(mut self, help: &str) -> Diagnostic<'i, R> {
+        self.helps.push(help.to_owned());
+        self
+    }
+
+    /// Returns the wrapped `Error`.
+    #[inline]
+    pub fn error(&self) -> &Error<'i, R> {
+        &self.error
+    }
+
+    /// Returns the path of the file the `Error` was found in, if any.
+    #[inline]
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Returns the error code, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{Diagnostic, Error, Position};
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// # enum Rule {
+    /// #     a
+    /// # }
+    /// let error: Error<Rule> = Error::CustomErrorPos {
+    ///     message: "unexpected a".to_owned(),
+    ///     pos: Position::from_start("a")
+    /// };
+    ///
+    /// assert_eq!(Diagnostic::new(error.clone()).code(), None);
+    /// assert_eq!(Diagnostic::new(error).with_code("E001").code(), Some("E001"));
+    /// ```
+    #[inline]
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// Returns the labeled `Span`s, in the order they were added.
+    #[inline]
+    pub fn labels(&self) -> &[(Span<'i>, String)] {
+        &self.labels
+    }
+
+    /// Returns the notes, in the order they were added.
+    #[inline]
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    /// Returns the help messages, in the order they were added.
+    #[inline]
+    pub fn helps(&self) -> &[String] {
+        &self.helps
+    }
+}
+
+impl<'i, R: fmt::Debug> Diagnostic<'i, R> {
+    /// Formats the diagnostic just like its `Display` implementation, but looks up lines in an
+    /// already built `LineIndex` of the input, just like
+    /// [`Error::format_with`](enum.Error.html#method.format_with).
+    pub fn format_with(&self, index: &LineIndex<'i>) -> String {
+        Renderer::new().render_diagnostic_with(self, index)
+    }
+}
+
+impl<'i, R> From<Error<'i, R>> for Diagnostic<'i, R> {
+    fn from(error: Error<'i, R>) -> Diagnostic<'i, R> {
+        Diagnostic::new(error)
+    }
+}
+
+impl<'i, R: fmt::Debug> fmt::Display for Diagnostic<'i, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Renderer::new().render_diagnostic(self))
+    }
+}
+
+impl<'i, R: fmt::Debug> error::Error for Diagnostic<'i, R> {
+    fn description(&self) -> &str {
+        match self.error {
+            Error::ParsingError { .. } => "parsing error",
+            Error::CustomErrorPos { ref message, .. }
+            | Error::CustomErrorSpan { ref message, .. } => message
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// An `enum` which defines the units in which columns are counted.
+///
+/// [`Position::line_col`](struct.Position.html#method.line_col) always counts `Chars`, while
+/// [`Position::line_col_with`](struct.Position.html#method.line_col_with) and
+/// [`LineIndex`](struct.LineIndex.html) can count in any of them.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ColumnEncoding {
+    /// UTF-8 bytes
+    Bytes,
+    /// Unicode scalar values, i.e. `char`s
+    Chars,
+    /// UTF-16 code units, as used by the Language Server Protocol
+    Utf16,
+    /// Terminal display width of grapheme clusters, with tabs advancing to the next multiple of
+    /// `tab_width`
+    Display {
+        /// Width of a tab stop
+        tab_width: usize
+    }
+}
+
+impl ColumnEncoding {
+    /// Returns the width of `string` in the units of the `ColumnEncoding`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::ColumnEncoding;
+    /// assert_eq!(ColumnEncoding::Bytes.width("a嗨"), 4);
+    /// assert_eq!(ColumnEncoding::Chars.width("a嗨"), 2);
+    /// assert_eq!(ColumnEncoding::Utf16.width("a𝄞"), 3);
+    /// assert_eq!(ColumnEncoding::Display { tab_width: 4 }.width("a嗨"), 3);
+    /// assert_eq!(ColumnEncoding::Display { tab_width: 4 }.width("a\tb"), 5);
+    /// ```
+    pub fn width(self, string: &str) -> usize {
+        match self {
+            ColumnEncoding::Bytes => string.len(),
+            ColumnEncoding::Chars => string.chars().count(),
+            ColumnEncoding::Utf16 => string.chars().map(|c| c.len_utf16()).sum(),
+            ColumnEncoding::Display { tab_width } => {
+                string.graphemes(true).fold(0, |width, grapheme| {
+                    if grapheme == "\t" {
+                        if tab_width == 0 {
+                            width
+                        } else {
+                            width + tab_width - width % tab_width
+                        }
+                    } else {
+                        width + grapheme.width()
+                    }
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_combining() {
+        let encoding = ColumnEncoding::Display { tab_width: 4 };
+
+        assert_eq!(encoding.width("e\u{301}x"), 2);
+        assert_eq!(ColumnEncoding::Chars.width("e\u{301}x"), 3);
+    }
+
+    #[test]
+    fn display_tabs() {
+        let encoding = ColumnEncoding::Display { tab_width: 4 };
+
+        assert_eq!(encoding.width("\t"), 4);
+        assert_eq!(encoding.width("ab\t"), 4);
+        assert_eq!(encoding.width("abcd\t"), 8);
+        assert_eq!(ColumnEncoding::Display { tab_width: 0 }.width("a\t"), 1);
+    }
+
+    #[test]
+    fn utf16_astral() {
+        assert_eq!(ColumnEncoding::Utf16.width("𝄞"), 2);
+        assert_eq!(ColumnEncoding::Chars.width("𝄞"), 1);
+        assert_eq!(ColumnEncoding::Bytes.width("𝄞"), 4);
+    }
+}
@@ -11,18 +11,28 @@ use std::error;
 use std::fmt;
 
 use RuleType;
+use diagnostic::Diagnostic;
+use line_index::LineIndex;
 use position::Position;
+use renderer::Renderer;
 use span::Span;
 
 /// An `enum` which defines possible errors.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Error<'i, R> {
-    /// Generated parsing error with expected and unexpected `Rule`s and a position
+    /// Generated parsing error with expected and unexpected `Rule`s, expected literals and a
+    /// position
     ParsingError {
         /// Positive attempts
         positives: Vec<R>,
         /// Negative attempts
         negatives: Vec<R>,
+        /// Attempted literals, formatted for messages, e.g. `` `}` `` or `` `0`..`9` ``
+        literals: Vec<String>,
+        /// `Rule`s that all of the attempts were made inside of, outermost first, with their
+        /// starting positions; only kept with
+        /// [`ParserState::track_rule_stack`](struct.ParserState.html#method.track_rule_stack)
+        rule_stack: Vec<(R, Position<'i>)>,
         /// Deepest position of attempts
         pos: Position<'i>
     },
@@ -43,8 +53,9 @@ pub enum Error<'i, R> {
 }
 
 impl<'i, R: RuleType> Error<'i, R> {
-    /// Renames all `Rule`s from a `ParsingError` variant returning a `CustomErrorPos`. It does
-    /// nothing when called on `CustomErrorPos` and `CustomErrorSpan` variants.
+    /// Renames all `Rule`s from a `ParsingError` variant returning a `CustomErrorPos`. Expected
+    /// literals are listed after the expected `Rule`s as they are, while the `rule_stack` is
+    /// dropped. It does nothing when called on `CustomErrorPos` and `CustomErrorSpan` variants.
     ///
     /// Useful in order to rename verbose rules or have detailed per-`Rule` formatting.
     ///
@@ -65,6 +76,8 @@ impl<'i, R: RuleType> Error<'i, R> {
     /// Error::ParsingError {
     ///     positives: vec![Rule::open_paren],
     ///     negatives: vec![Rule::closed_paren],
+    ///     literals: vec![],
+    ///     rule_stack: vec![],
     ///     pos: pos
     /// }.renamed_rules(|rule| {
     ///     match *rule {
@@ -81,118 +94,126 @@ impl<'i, R: RuleType> Error<'i, R> {
             Error::ParsingError {
                 positives,
                 negatives,
-                pos
+                literals,
+                pos,
+                ..
             } => {
-                let message = parsing_error_message(&positives, &negatives, f);
+                let message = parsing_error_message(&positives, &negatives, &literals, f);
                 Error::CustomErrorPos { message, pos }
             }
             error => error
         }
     }
-}
 
-fn message<'i, R: fmt::Debug>(error: &Error<'i, R>) -> String {
-    match *error {
-        Error::ParsingError {
-            ref positives,
-            ref negatives,
-            ..
-        } => parsing_error_message(positives, negatives, |r| format!("{:?}", r)),
-        Error::CustomErrorPos { ref message, .. } | Error::CustomErrorSpan { ref message, .. } => {
-            message.to_owned()
-        }
+    /// Attaches the `path` of the file the error was found in, returning a
+    /// [`Diagnostic`](struct.Diagnostic.html) which reports it as `--> path:line:col`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{Error, Position};
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// # enum Rule {
+    /// #     a
+    /// # }
+    /// let input = "a = 1\nb = ";
+    /// let error: Error<Rule> = Error::CustomErrorPos {
+    ///     message: "expected value".to_owned(),
+    ///     pos: Position::from_start(input).skip(10).unwrap()
+    /// };
+    ///
+    /// assert_eq!(
+    ///     format!("{}", error.with_path("path/to/file.toml")),
+    ///     [
+    ///         " --> path/to/file.toml:2:5",
+    ///         "  |",
+    ///         "2 | b = ",
+    ///         "  |     ^---",
+    ///         "  |",
+    ///         "  = expected value"
+    ///     ].join("\n")
+    /// );
+    /// ```
+    #[inline]
+    pub fn with_path(self, path: &str) -> Diagnostic<'i, R> {
+        Diagnostic::new(self).with_path(path)
+    }
+
+    /// Formats the error just like its `Display` implementation, but looks up lines in an
+    /// already built `LineIndex` of the input instead of rescanning it. Useful when formatting
+    /// many errors for the same input.
+    ///
+    /// The reported column is counted in the `LineIndex`'s
+    /// [`ColumnEncoding`](enum.ColumnEncoding.html). With `ColumnEncoding::Display`, the underline
+    /// is also measured in display width so that it lines up under wide and combining characters
+    /// and tabs.
+    ///
+    /// The `index` must have been built from the same input as the error. Errors can be rendered
+    /// with context lines by a [`Renderer`](struct.Renderer.html) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{Error, LineIndex, Position};
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// # enum Rule {
+    /// #     a
+    /// # }
+    /// let input = "a\nb";
+    /// let index = LineIndex::new(input);
+    /// let error: Error<Rule> = Error::CustomErrorPos {
+    ///     message: "unexpected b".to_owned(),
+    ///     pos: Position::from_start(input).skip(2).unwrap()
+    /// };
+    ///
+    /// assert_eq!(error.format_with(&index), format!("{}", error));
+    /// ```
+    pub fn format_with(&self, index: &LineIndex<'i>) -> String {
+        Renderer::new().render_with(self, index)
     }
 }
 
-fn parsing_error_message<R: fmt::Debug, F>(positives: &[R], negatives: &[R], mut f: F) -> String
+pub fn parsing_error_message<R: fmt::Debug, F>(
+    positives: &[R],
+    negatives: &[R],
+    literals: &[String],
+    mut f: F
+) -> String
 where
     F: FnMut(&R) -> String
 {
-    match (negatives.is_empty(), positives.is_empty()) {
+    let negatives: Vec<_> = negatives.iter().map(&mut f).collect();
+    let expected: Vec<_> = positives
+        .iter()
+        .map(&mut f)
+        .chain(literals.iter().cloned())
+        .collect();
+
+    match (negatives.is_empty(), expected.is_empty()) {
         (false, false) => format!(
             "unexpected {}; expected {}",
-            enumerate(negatives, &mut f),
-            enumerate(positives, &mut f)
+            enumerate(&negatives),
+            enumerate(&expected)
         ),
-        (false, true) => format!("unexpected {}", enumerate(negatives, &mut f)),
-        (true, false) => format!("expected {}", enumerate(positives, &mut f)),
+        (false, true) => format!("unexpected {}", enumerate(&negatives)),
+        (true, false) => format!("expected {}", enumerate(&expected)),
         (true, true) => "unknown parsing error".to_owned()
     }
 }
 
-fn enumerate<R: fmt::Debug, F>(rules: &[R], f: &mut F) -> String
-where
-    F: FnMut(&R) -> String
-{
-    match rules.len() {
-        1 => f(&rules[0]),
-        2 => format!("{} or {}", f(&rules[0]), f(&rules[1])),
-        l => {
-            let separated = rules
-                .iter()
-                .take(l - 1)
-                .map(|r| f(r))
-                .collect::<Vec<_>>()
-                .join(", ");
-            format!("{}, or {}", separated, f(&rules[l - 1]))
-        }
-    }
-}
-
-fn underline<'i, R: fmt::Debug>(error: &Error<'i, R>, offset: usize) -> String {
-    let mut underline = String::new();
-
-    for _ in 0..offset {
-        underline.push(' ');
-    }
-
-    match *error {
-        Error::CustomErrorSpan { ref span, .. } => {
-            if span.end() - span.start() > 1 {
-                underline.push('^');
-                for _ in 2..(span.end() - span.start()) {
-                    underline.push('-');
-                }
-                underline.push('^');
-            } else {
-                underline.push('^');
-            }
-        }
-        _ => underline.push_str("^---")
-    };
-
-    underline
-}
-
-fn format<'i, R: fmt::Debug>(error: &Error<'i, R>) -> String {
-    let pos = match *error {
-        Error::ParsingError { ref pos, .. } | Error::CustomErrorPos { ref pos, .. } => pos.clone(),
-        Error::CustomErrorSpan { ref span, .. } => span.clone().split().0.clone()
-    };
-    let (line, col) = pos.line_col();
-    let line_str_len = format!("{}", line).len();
-
-    let mut spacing = String::new();
-    for _ in 0..line_str_len {
-        spacing.push(' ');
+fn enumerate(items: &[String]) -> String {
+    match items.len() {
+        1 => items[0].clone(),
+        2 => format!("{} or {}", items[0], items[1]),
+        l => format!("{}, or {}", items[..l - 1].join(", "), items[l - 1])
     }
-
-    let mut result = format!("{}--> {}:{}\n", spacing, line, col);
-    result.push_str(&format!("{} |\n", spacing));
-    result.push_str(&format!("{} | ", line));
-
-    let line = pos.line_of();
-    result.push_str(&format!("{}\n", line));
-    result.push_str(&format!("{} | {}\n", spacing, underline(error, col - 1)));
-    result.push_str(&format!("{} |\n", spacing));
-    result.push_str(&format!("{} = {}", spacing, message(error)));
-
-    result
 }
 
 impl<'i, R: fmt::Debug> fmt::Display for Error<'i, R> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", format(self))
+        write!(f, "{}", Renderer::new().render(self))
     }
 }
 
@@ -210,6 +231,7 @@ impl<'i, R: fmt::Debug> error::Error for Error<'i, R> {
 mod tests {
     use super::*;
     use super::super::position;
+    use encoding::ColumnEncoding;
 
     #[test]
     fn display_parsing_error_mixed() {
@@ -218,6 +240,8 @@ mod tests {
         let error: Error<u32> = Error::ParsingError {
             positives: vec![1, 2, 3],
             negatives: vec![4, 5, 6],
+            literals: vec![],
+            rule_stack: vec![],
             pos: pos
         };
 
@@ -241,6 +265,8 @@ mod tests {
         let error: Error<u32> = Error::ParsingError {
             positives: vec![1, 2],
             negatives: vec![],
+            literals: vec![],
+            rule_stack: vec![],
             pos: pos
         };
 
@@ -257,6 +283,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn display_parsing_error_literals() {
+        let input = "ab\ncd\nef";
+        let pos = unsafe { position::new(input, 4) };
+        let error: Error<u32> = Error::ParsingError {
+            positives: vec![1],
+            negatives: vec![],
+            literals: vec!["`,`".to_owned(), "`}`".to_owned()],
+            rule_stack: vec![],
+            pos: pos
+        };
+
+        assert_eq!(
+            format!("{}", error),
+            vec![
+                " --> 2:2",
+                "  |",
+                "2 | cd",
+                "  |  ^---",
+                "  |",
+                "  = expected 1, `,`, or `}`",
+            ].join("\n")
+        );
+    }
+
+    #[test]
+    fn display_parsing_error_rule_stack() {
+        let input = "ab\ncd\nef";
+        let pos = unsafe { position::new(input, 4) };
+        let error: Error<u32> = Error::ParsingError {
+            positives: vec![1],
+            negatives: vec![],
+            literals: vec![],
+            rule_stack: vec![
+                (2, Position::from_start(input)),
+                (3, unsafe { position::new(input, 3) }),
+            ],
+            pos: pos
+        };
+
+        assert_eq!(
+            format!("{}", error),
+            vec![
+                " --> 2:2",
+                "  |",
+                "2 | cd",
+                "  |  ^---",
+                "  |",
+                "  = expected 1",
+                "  = while parsing `3` starting at 2:1",
+                "  = while parsing `2` starting at 1:1",
+            ].join("\n")
+        );
+        assert_eq!(error.format_with(&LineIndex::new(input)), format!("{}", error));
+    }
+
     #[test]
     fn display_parsing_error_negatives() {
         let input = "ab\ncd\nef";
@@ -264,6 +346,8 @@ mod tests {
         let error: Error<u32> = Error::ParsingError {
             positives: vec![],
             negatives: vec![4, 5, 6],
+            literals: vec![],
+            rule_stack: vec![],
             pos: pos
         };
 
@@ -287,6 +371,8 @@ mod tests {
         let error: Error<u32> = Error::ParsingError {
             positives: vec![],
             negatives: vec![],
+            literals: vec![],
+            rule_stack: vec![],
             pos: pos
         };
 
@@ -325,6 +411,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn format_with_line_index() {
+        let input = "ab\r\ncd\nef";
+        let index = LineIndex::new(input);
+        let start = unsafe { position::new(input, 5) };
+        let end = unsafe { position::new(input, 6) };
+        let error: Error<&str> = Error::CustomErrorSpan {
+            message: "error: big one".to_owned(),
+            span: start.span(&end)
+        };
+
+        assert_eq!(error.format_with(&index), format!("{}", error));
+        assert_eq!(
+            error.format_with(&index),
+            vec![
+                " --> 2:2",
+                "  |",
+                "2 | cd",
+                "  |  ^",
+                "  |",
+                "  = error: big one",
+            ].join("\n")
+        );
+    }
+
+    #[test]
+    fn format_with_display_width() {
+        let input = "\t嗨 ab";
+        let index = LineIndex::new(input).with_encoding(ColumnEncoding::Display { tab_width: 4 });
+        let start = unsafe { position::new(input, 4) };
+        let end = unsafe { position::new(input, 7) };
+        let error: Error<&str> = Error::CustomErrorSpan {
+            message: "error: big one".to_owned(),
+            span: start.span(&end)
+        };
+
+        assert_eq!(
+            error.format_with(&index),
+            vec![
+                " --> 1:7",
+                "  |",
+                "1 | \t嗨 ab",
+                "  |       ^-^",
+                "  |",
+                "  = error: big one",
+            ].join("\n")
+        );
+    }
+
+    #[test]
+    fn format_with_utf16() {
+        let input = "𝄞ab";
+        let index = LineIndex::new(input).with_encoding(ColumnEncoding::Utf16);
+        let pos = unsafe { position::new(input, 5) };
+        let error: Error<&str> = Error::CustomErrorPos {
+            message: "error: big one".to_owned(),
+            pos: pos
+        };
+
+        assert_eq!(
+            error.format_with(&index),
+            vec![
+                " --> 1:4",
+                "  |",
+                "1 | 𝄞ab",
+                "  |   ^---",
+                "  |",
+                "  = error: big one",
+            ].join("\n")
+        );
+    }
+
     #[test]
     fn mapped_parsing_error() {
         let input = "ab\ncd\nef";
@@ -332,6 +490,8 @@ mod tests {
         let error: Error<u32> = Error::ParsingError {
             positives: vec![1, 2, 3],
             negatives: vec![4, 5, 6],
+            literals: vec![],
+            rule_stack: vec![],
             pos: pos
         }.renamed_rules(|n| format!("{}", n + 1));
 
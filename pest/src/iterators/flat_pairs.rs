@@ -8,11 +8,11 @@
 // modified, or distributed except according to those terms.
 
 use std::fmt;
-use std::rc::Rc;
 
 use super::pair::{self, Pair};
 use super::queueable_token::QueueableToken;
 use super::tokens::{self, Tokens};
+use super::Rc;
 use RuleType;
 
 /// A `struct` containing `Pairs`. It is created by
@@ -10,13 +10,26 @@
 //! A `mod` containing iterators and constructs to aid in parser output manipulation.
 
 mod flat_pairs;
+mod owned_pair;
+mod owned_pairs;
 mod pair;
 pub(crate) mod pairs;
 mod queueable_token;
+mod tagged_pairs;
 mod tokens;
 
 pub use self::flat_pairs::FlatPairs;
+pub use self::owned_pair::OwnedPair;
+pub use self::owned_pairs::OwnedPairs;
 pub use self::pair::Pair;
 pub use self::pairs::Pairs;
 pub(crate) use self::queueable_token::QueueableToken;
+pub use self::tagged_pairs::TaggedPairs;
 pub use self::tokens::Tokens;
+
+// With the `sync` feature, token queues are shared through `Arc` so that `Pairs`, `Pair`, and
+// their iterators are `Send` and `Sync`. `Rc` is kept as the name in both cases.
+#[cfg(not(feature = "sync"))]
+pub(crate) use std::rc::Rc;
+#[cfg(feature = "sync")]
+pub(crate) use std::sync::Arc as Rc;
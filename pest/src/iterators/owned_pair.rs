@@ -0,0 +1,266 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use super::owned_pairs::{self, OwnedPairs};
+use super::pair::{self, Pair};
+use super::queueable_token::QueueableToken;
+use super::tokens::Tokens;
+use super::Rc;
+use RuleType;
+use span::Span;
+
+/// A `struct` like [`Pair`](struct.Pair.html) that owns its input, and can therefore be stored
+/// without a lifetime. It is created by [`Pair::into_owned`](struct.Pair.html#method.into_owned)
+/// and [`OwnedPairs`](struct.OwnedPairs.html).
+#[derive(Clone)]
+pub struct OwnedPair<R> {
+    queue: Rc<Vec<QueueableToken<R>>>,
+    input: Rc<str>,
+    start: usize
+}
+
+pub fn new<R: RuleType>(
+    queue: Rc<Vec<QueueableToken<R>>>,
+    input: Rc<str>,
+    start: usize
+) -> OwnedPair<R> {
+    OwnedPair {
+        queue,
+        input,
+        start
+    }
+}
+
+impl<R: RuleType> OwnedPair<R> {
+    /// Returns the `Rule` of the `OwnedPair`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = "";
+    /// let pair = pest::state(input, |state, pos| {
+    ///     // generating Token pair with Rule::a ...
+    /// #     state.rule(Rule::a, pos, |_, p| Ok(p))
+    /// }).unwrap().next().unwrap().into_owned();
+    ///
+    /// assert_eq!(pair.as_rule(), Rule::a);
+    /// ```
+    #[inline]
+    pub fn as_rule(&self) -> R {
+        self.as_pair().as_rule()
+    }
+
+    /// Returns the node tag of the `OwnedPair`, or `None` if it was not tagged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = "";
+    /// let pair = pest::state(input, |state, pos| {
+    ///     // generating Token pair with Rule::a tagged with lhs ...
+    /// #     state.tag_node("lhs", move |state| state.rule(Rule::a, pos, |_, p| Ok(p)))
+    /// }).unwrap().next().unwrap().into_owned();
+    ///
+    /// assert_eq!(pair.as_node_tag(), Some("lhs"));
+    /// ```
+    #[inline]
+    pub fn as_node_tag(&self) -> Option<&str> {
+        match self.queue[self.pair()] {
+            QueueableToken::End { ref tag, .. } => tag.as_ref().map(|tag| &**tag),
+            _ => unreachable!()
+        }
+    }
+
+    /// Captures a slice from the owned input defined by the token `OwnedPair`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     ab
+    /// }
+    ///
+    /// let input = "ab";
+    /// let pair = pest::state(input, |state, pos| {
+    ///     // generating Token pair with Rule::ab ...
+    /// #     state.rule(Rule::ab, pos, |_, p| p.match_string("ab"))
+    /// }).unwrap().next().unwrap().into_owned();
+    ///
+    /// assert_eq!(pair.as_str(), "ab");
+    /// ```
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.as_pair().as_str()
+    }
+
+    /// Returns the `Span` defined by the `OwnedPair`. Since a `Span` borrows its input, it is
+    /// bound to the `OwnedPair` rather than consuming it like
+    /// [`Pair::into_span`](struct.Pair.html#method.into_span).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     ab
+    /// }
+    ///
+    /// let input = "ab";
+    /// let pair = pest::state(input, |state, pos| {
+    ///     // generating Token pair with Rule::ab ...
+    /// #     state.rule(Rule::ab, pos, |_, p| p.match_string("ab"))
+    /// }).unwrap().next().unwrap().into_owned();
+    ///
+    /// assert_eq!(pair.as_span().as_str(), "ab");
+    /// ```
+    #[inline]
+    pub fn as_span(&self) -> Span<'_> {
+        self.as_pair().into_span()
+    }
+
+    /// Returns the inner `OwnedPairs` between the `OwnedPair`, consuming it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = "";
+    /// let pair = pest::state(input, |state, pos| {
+    ///     // generating Token pair with Rule::a ...
+    /// #     state.rule(Rule::a, pos, |_, p| Ok(p))
+    /// }).unwrap().next().unwrap().into_owned();
+    ///
+    /// assert!(pair.into_inner().next().is_none());
+    /// ```
+    #[inline]
+    pub fn into_inner(self) -> OwnedPairs<R> {
+        let pair = self.pair();
+
+        owned_pairs::new(self.queue, self.input, self.start + 1, pair - 1)
+    }
+
+    /// Returns a `TokenIterator` over the `OwnedPair`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = "";
+    /// let pair = pest::state(input, |state, pos| {
+    ///     // generating Token pair with Rule::a ...
+    /// #     state.rule(Rule::a, pos, |_, p| Ok(p))
+    /// }).unwrap().next().unwrap().into_owned();
+    /// let tokens: Vec<_> = pair.tokens().collect();
+    ///
+    /// assert_eq!(tokens.len(), 2);
+    /// ```
+    #[inline]
+    pub fn tokens(&self) -> Tokens<'_, R> {
+        self.as_pair().tokens()
+    }
+
+    /// Borrows the `OwnedPair` as a `Pair` without copying the input or the tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = "";
+    /// let pair = pest::state(input, |state, pos| {
+    ///     // generating Token pair with Rule::a ...
+    /// #     state.rule(Rule::a, pos, |_, p| Ok(p))
+    /// }).unwrap().next().unwrap().into_owned();
+    ///
+    /// assert_eq!(pair.as_pair().as_rule(), Rule::a);
+    /// ```
+    #[inline]
+    pub fn as_pair(&self) -> Pair<'_, R> {
+        pair::new(Rc::clone(&self.queue), &self.input, self.start)
+    }
+
+    fn pair(&self) -> usize {
+        match self.queue[self.start] {
+            QueueableToken::Start { pair, .. } => pair,
+            _ => unreachable!()
+        }
+    }
+}
+
+impl<R: RuleType> fmt::Debug for OwnedPair<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_pair(), f)
+    }
+}
+
+impl<R: RuleType> fmt::Display for OwnedPair<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.as_pair(), f)
+    }
+}
+
+impl<R: RuleType> PartialEq for OwnedPair<R> {
+    fn eq(&self, other: &OwnedPair<R>) -> bool {
+        self.as_pair() == other.as_pair()
+    }
+}
+
+impl<R: RuleType> Eq for OwnedPair<R> {}
+
+impl<R: RuleType> Hash for OwnedPair<R> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_pair().hash(state);
+    }
+}
@@ -0,0 +1,199 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use super::owned_pair::{self, OwnedPair};
+use super::pairs::{self, Pairs};
+use super::queueable_token::QueueableToken;
+use super::tokens::Tokens;
+use super::Rc;
+use RuleType;
+
+/// A `struct` like [`Pairs`](struct.Pairs.html) that owns its input, and can therefore be stored
+/// without a lifetime. It is created by [`Pairs::into_owned`](struct.Pairs.html#method.into_owned)
+/// and [`OwnedPair::into_inner`](struct.OwnedPair.html#method.into_inner).
+#[derive(Clone)]
+pub struct OwnedPairs<R> {
+    queue: Rc<Vec<QueueableToken<R>>>,
+    input: Rc<str>,
+    start: usize,
+    end: usize
+}
+
+pub fn new<R: RuleType>(
+    queue: Rc<Vec<QueueableToken<R>>>,
+    input: Rc<str>,
+    start: usize,
+    end: usize
+) -> OwnedPairs<R> {
+    OwnedPairs {
+        queue,
+        input,
+        start,
+        end
+    }
+}
+
+impl<R: RuleType> OwnedPairs<R> {
+    /// Returns a `TokenIterator` over the `OwnedPairs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = "";
+    /// let pairs = pest::state(input, |state, pos| {
+    ///     // generating Token pair with Rule::a ...
+    /// #     state.rule(Rule::a, pos, |_, p| Ok(p))
+    /// }).unwrap().into_owned();
+    /// let tokens: Vec<_> = pairs.tokens().collect();
+    ///
+    /// assert_eq!(tokens.len(), 2);
+    /// ```
+    #[inline]
+    pub fn tokens(&self) -> Tokens<'_, R> {
+        self.as_pairs().tokens()
+    }
+
+    /// Borrows the `OwnedPairs` as `Pairs` without copying the input or the tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = "";
+    /// let pairs = pest::state(input, |state, pos| {
+    ///     // generating Token pair with Rule::a ...
+    /// #     state.rule(Rule::a, pos, |_, p| Ok(p))
+    /// }).unwrap().into_owned();
+    ///
+    /// assert_eq!(pairs.as_pairs().next().unwrap().as_rule(), Rule::a);
+    /// ```
+    #[inline]
+    pub fn as_pairs(&self) -> Pairs<'_, R> {
+        pairs::new(Rc::clone(&self.queue), &self.input, self.start, self.end)
+    }
+
+    fn pair(&self) -> usize {
+        match self.queue[self.start] {
+            QueueableToken::Start { pair, .. } => pair,
+            _ => unreachable!()
+        }
+    }
+}
+
+impl<R: RuleType> Iterator for OwnedPairs<R> {
+    type Item = OwnedPair<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+
+        let pair = owned_pair::new(Rc::clone(&self.queue), Rc::clone(&self.input), self.start);
+
+        self.start = self.pair() + 1;
+
+        Some(pair)
+    }
+}
+
+impl<'i, R: RuleType> From<Pairs<'i, R>> for OwnedPairs<R> {
+    fn from(pairs: Pairs<'i, R>) -> OwnedPairs<R> {
+        pairs.into_owned()
+    }
+}
+
+impl<R: RuleType> fmt::Debug for OwnedPairs<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_pairs(), f)
+    }
+}
+
+impl<R: RuleType> fmt::Display for OwnedPairs<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.as_pairs(), f)
+    }
+}
+
+impl<R: RuleType> PartialEq for OwnedPairs<R> {
+    fn eq(&self, other: &OwnedPairs<R>) -> bool {
+        self.as_pairs() == other.as_pairs()
+    }
+}
+
+impl<R: RuleType> Eq for OwnedPairs<R> {}
+
+impl<R: RuleType> Hash for OwnedPairs<R> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_pairs().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::Parser;
+    use super::super::super::macros::tests::*;
+    use super::OwnedPairs;
+
+    fn parse_owned(input: String) -> OwnedPairs<Rule> {
+        AbcParser::parse(Rule::a, &input).unwrap().into_owned()
+    }
+
+    #[test]
+    fn owned_pairs_outlive_input() {
+        let pairs = parse_owned("abcde".to_owned());
+
+        assert_eq!(format!("{}", pairs), "[a(0, 3, [b(1, 2)]), c(4, 5)]");
+
+        let strs: Vec<_> = pairs.map(|pair| pair.as_str().to_owned()).collect();
+
+        assert_eq!(strs, vec!["abc", "e"]);
+    }
+
+    #[test]
+    fn owned_pair_into_inner() {
+        let pair = parse_owned("abcde".to_owned()).next().unwrap();
+
+        assert_eq!(pair.as_rule(), Rule::a);
+        assert_eq!(pair.as_span().start(), 0);
+        assert_eq!(pair.as_span().end(), 3);
+
+        let inner: Vec<_> = pair.into_inner().collect();
+
+        assert_eq!(inner.len(), 1);
+        assert_eq!(inner[0].as_rule(), Rule::b);
+        assert_eq!(inner[0].as_str(), "b");
+    }
+
+    #[test]
+    fn owned_pairs_share_tokens() {
+        let pairs = parse_owned("abcde".to_owned());
+        let borrowed = pairs.as_pairs();
+
+        assert_eq!(borrowed, pairs.as_pairs());
+        assert_eq!(format!("{:?}", borrowed), format!("{:?}", pairs));
+    }
+}
@@ -10,11 +10,12 @@
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::ptr;
-use std::rc::Rc;
 
+use super::owned_pair::{self, OwnedPair};
 use super::pairs::{self, Pairs};
 use super::queueable_token::QueueableToken;
 use super::tokens::{self, Tokens};
+use super::Rc;
 use RuleType;
 use span::{self, Span};
 
@@ -69,6 +70,36 @@ impl<'i, R: RuleType> Pair<'i, R> {
         }
     }
 
+    /// Returns the node tag of the `Pair`, given to it in the grammar with `#tag = expression`, or
+    /// `None` if it was not tagged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = "";
+    /// let pair = pest::state(input, |state, pos| {
+    ///     // generating Token pair with Rule::a tagged with lhs ...
+    /// #     state.tag_node("lhs", move |state| state.rule(Rule::a, pos, |_, p| Ok(p)))
+    /// }).unwrap().next().unwrap();
+    ///
+    /// assert_eq!(pair.as_node_tag(), Some("lhs"));
+    /// ```
+    #[inline]
+    pub fn as_node_tag(&self) -> Option<&str> {
+        match self.queue[self.pair()] {
+            QueueableToken::End { ref tag, .. } => tag.as_ref().map(|tag| &**tag),
+            _ => unreachable!()
+        }
+    }
+
     /// Captures a slice from the `&str` defined by the token `Pair`.
     ///
     /// # Examples
@@ -184,6 +215,78 @@ impl<'i, R: RuleType> Pair<'i, R> {
         tokens::new(self.queue, self.input, self.start, end + 1)
     }
 
+    /// Converts the `Pair` into an `OwnedPair` which owns a copy of the input. The tokens are
+    /// shared rather than copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = String::from("a");
+    /// let pair = pest::state(&input, |state, pos| {
+    ///     // generating Token pair with Rule::a ...
+    /// #     state.rule(Rule::a, pos, |_, p| p.match_string("a"))
+    /// }).unwrap().next().unwrap().into_owned();
+    ///
+    /// drop(input);
+    ///
+    /// assert_eq!(pair.as_str(), "a");
+    /// ```
+    #[inline]
+    pub fn into_owned(self) -> OwnedPair<R> {
+        owned_pair::new(self.queue, Rc::from(self.input), self.start)
+    }
+
+    /// Converts the `Pair` into an `OwnedPair` which shares `input` instead of copying it. Useful
+    /// when converting many `Pair`s of the same input, which then all share one allocation. With
+    /// the `sync` feature, `input` is an `Arc<str>` rather than an `Rc<str>`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` differs from the input of the `Pair`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "sync"))]
+    /// # use std::rc::Rc;
+    /// # #[cfg(feature = "sync")]
+    /// # use std::sync::Arc as Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input: Rc<str> = Rc::from("aa");
+    /// let pairs: Vec<_> = pest::state(&input, |state, pos| {
+    ///     // generating Token pairs with Rule::a ...
+    /// #     state.rule(Rule::a, pos, |_, p| p.match_string("a"))
+    /// #         .and_then(|p| state.rule(Rule::a, p, |_, p| p.match_string("a")))
+    /// }).unwrap().map(|pair| pair.into_owned_with(&input)).collect();
+    ///
+    /// assert_eq!(pairs.len(), 2);
+    /// assert_eq!(Rc::strong_count(&input), 3);
+    /// ```
+    #[inline]
+    pub fn into_owned_with(self, input: &Rc<str>) -> OwnedPair<R> {
+        assert!(
+            ptr::eq(input.as_ptr(), self.input.as_ptr()) && input.len() == self.input.len()
+                || **input == *self.input,
+            "input differs from the input of the Pair"
+        );
+
+        owned_pair::new(self.queue, Rc::clone(input), self.start)
+    }
+
     fn pair(&self) -> usize {
         match self.queue[self.start] {
             QueueableToken::Start { pair, .. } => pair,
@@ -217,6 +320,10 @@ impl<'i, R: RuleType> fmt::Display for Pair<'i, R> {
         let end = self.pos(self.pair());
         let mut pairs = self.clone().into_inner().peekable();
 
+        if let Some(tag) = self.as_node_tag() {
+            write!(f, "#{} = ", tag)?;
+        }
+
         if pairs.peek().is_none() {
             write!(f, "{:?}({}, {})", rule, start, end)
         } else {
@@ -10,12 +10,14 @@
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::ptr;
-use std::rc::Rc;
 
 use super::flat_pairs::{self, FlatPairs};
+use super::owned_pairs::{self, OwnedPairs};
 use super::pair::{self, Pair};
 use super::queueable_token::QueueableToken;
+use super::tagged_pairs::{self, TaggedPairs};
 use super::tokens::{self, Tokens};
+use super::Rc;
 use RuleType;
 
 /// A `struct` containing `Pairs`. It is created by [`pest::state`](../fn.state.html) and
@@ -73,6 +75,41 @@ impl<'i, R: RuleType> Pairs<'i, R> {
         flat_pairs::new(self.queue, self.input, self.start, self.end)
     }
 
+    /// Finds the `Pair`s with the node `tag` among the `Pairs` and all of the pairs nested inside
+    /// of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a,
+    ///     b
+    /// }
+    ///
+    /// let input = "ab";
+    /// let pairs = pest::state(input, |state, pos| {
+    ///     // generating Token pair with Rule::a and nested Rule::b tagged with rhs ...
+    /// #     state.rule(Rule::a, pos, |state, pos| {
+    /// #         let pos = pos.match_string("a").unwrap();
+    /// #         state.tag_node("rhs", move |state| {
+    /// #             state.rule(Rule::b, pos, |_, p| p.match_string("b"))
+    /// #         })
+    /// #     })
+    /// }).unwrap();
+    /// let tagged: Vec<_> = pairs.find_tagged("rhs").collect();
+    ///
+    /// assert_eq!(tagged.len(), 1);
+    /// assert_eq!(tagged[0].as_rule(), Rule::b);
+    /// ```
+    #[inline]
+    pub fn find_tagged<'t>(self, tag: &'t str) -> TaggedPairs<'i, 't, R> {
+        tagged_pairs::new(self.flatten(), tag)
+    }
+
     /// Converts the `Pairs` into a `TokenIterator`.
     ///
     /// # Examples
@@ -100,6 +137,76 @@ impl<'i, R: RuleType> Pairs<'i, R> {
         tokens::new(self.queue, self.input, self.start, self.end)
     }
 
+    /// Converts the `Pairs` into `OwnedPairs` which own a copy of the input. The tokens are shared
+    /// rather than copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = String::from("a");
+    /// let pairs = pest::state(&input, |state, pos| {
+    ///     // generating Token pair with Rule::a ...
+    /// #     state.rule(Rule::a, pos, |_, p| p.match_string("a"))
+    /// }).unwrap().into_owned();
+    ///
+    /// drop(input);
+    ///
+    /// assert_eq!(pairs.as_pairs().next().unwrap().as_str(), "a");
+    /// ```
+    #[inline]
+    pub fn into_owned(self) -> OwnedPairs<R> {
+        owned_pairs::new(self.queue, Rc::from(self.input), self.start, self.end)
+    }
+
+    /// Converts the `Pairs` into `OwnedPairs` which share `input` instead of copying it, just
+    /// like [`Pair::into_owned_with`](struct.Pair.html#method.into_owned_with).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` differs from the input of the `Pairs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "sync"))]
+    /// # use std::rc::Rc;
+    /// # #[cfg(feature = "sync")]
+    /// # use std::sync::Arc as Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input: Rc<str> = Rc::from("a");
+    /// let pairs = pest::state(&input, |state, pos| {
+    ///     // generating Token pair with Rule::a ...
+    /// #     state.rule(Rule::a, pos, |_, p| p.match_string("a"))
+    /// }).unwrap().into_owned_with(&input);
+    ///
+    /// assert_eq!(pairs.as_pairs().next().unwrap().as_str(), "a");
+    /// assert_eq!(Rc::strong_count(&input), 2);
+    /// ```
+    #[inline]
+    pub fn into_owned_with(self, input: &Rc<str>) -> OwnedPairs<R> {
+        assert!(
+            ptr::eq(input.as_ptr(), self.input.as_ptr()) && input.len() == self.input.len()
+                || **input == *self.input,
+            "input differs from the input of the Pairs"
+        );
+
+        owned_pairs::new(self.queue, Rc::clone(input), self.start, self.end)
+    }
+
     fn pair(&self) -> usize {
         match self.queue[self.start] {
             QueueableToken::Start { pair, .. } => pair,
@@ -169,6 +276,7 @@ impl<'i, R: Hash> Hash for Pairs<'i, R> {
 mod tests {
     use super::super::super::Parser;
     use super::super::super::macros::tests::*;
+    use super::super::Rc;
 
     #[test]
     fn pairs_debug() {
@@ -195,4 +303,35 @@ mod tests {
             "[a(0, 3, [b(1, 2)]), c(4, 5)]".to_owned()
         );
     }
+
+    #[test]
+    fn pairs_into_owned_with_same_contents() {
+        let input = "abcde".to_owned();
+        let shared: Rc<str> = Rc::from("abcde");
+        let pairs = AbcParser::parse(Rule::a, &input).unwrap().into_owned_with(&shared);
+
+        assert_eq!(format!("{}", pairs), "[a(0, 3, [b(1, 2)]), c(4, 5)]");
+    }
+
+    #[test]
+    #[should_panic(expected = "input differs from the input of the Pairs")]
+    fn pairs_into_owned_with_other_input() {
+        let pairs = AbcParser::parse(Rule::a, "abcde").unwrap();
+
+        pairs.into_owned_with(&Rc::from("abcdf"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn pairs_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<::iterators::Pairs<'static, Rule>>();
+        assert_send_sync::<::iterators::Pair<'static, Rule>>();
+        assert_send_sync::<::iterators::FlatPairs<'static, Rule>>();
+        assert_send_sync::<::iterators::TaggedPairs<'static, 'static, Rule>>();
+        assert_send_sync::<::iterators::Tokens<'static, Rule>>();
+        assert_send_sync::<::iterators::OwnedPairs<Rule>>();
+        assert_send_sync::<::iterators::OwnedPair<Rule>>();
+    }
 }
@@ -7,14 +7,18 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
+use super::Rc;
+
 // This structure serves to improve performance over Token objects in two ways:
 //
 //   * it is smaller than a Token, leading to both less memory use when stored in the queue but also
 //     increased speed when pushing to the queue
 //   * it finds its pair in O(1) time instead of O(N), since pair positions are known at parse time
 //     and can easily be stored instead of recomputed
-#[derive(Debug)]
+//
+// The node tag of a pair is stored in its End token since it is only known once the pair matched.
+#[derive(Clone, Debug)]
 pub enum QueueableToken<R> {
     Start { pair: usize, pos: usize },
-    End { rule: R, pos: usize }
+    End { rule: R, tag: Option<Rc<str>>, pos: usize }
 }
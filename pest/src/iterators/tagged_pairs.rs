@@ -0,0 +1,55 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::fmt;
+
+use super::flat_pairs::FlatPairs;
+use super::pair::Pair;
+use RuleType;
+
+/// A `struct` containing the nested `Pair`s which carry a given node tag. It is created by
+/// [`Pairs::find_tagged`](struct.Pairs.html#method.find_tagged).
+pub struct TaggedPairs<'i, 't, R> {
+    pairs: FlatPairs<'i, R>,
+    tag: &'t str
+}
+
+pub fn new<'i, 't, R: RuleType>(pairs: FlatPairs<'i, R>, tag: &'t str) -> TaggedPairs<'i, 't, R> {
+    TaggedPairs { pairs, tag }
+}
+
+impl<'i, 't, R: RuleType> Iterator for TaggedPairs<'i, 't, R> {
+    type Item = Pair<'i, R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tag = self.tag;
+
+        self.pairs.find(|pair| pair.as_node_tag() == Some(tag))
+    }
+}
+
+impl<'i, 't, R: RuleType> fmt::Debug for TaggedPairs<'i, 't, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TaggedPairs {{ tag: {:?}, pairs: {:?} }}",
+            self.tag,
+            self.clone().collect::<Vec<_>>()
+        )
+    }
+}
+
+impl<'i, 't, R: Clone> Clone for TaggedPairs<'i, 't, R> {
+    fn clone(&self) -> TaggedPairs<'i, 't, R> {
+        TaggedPairs {
+            pairs: self.pairs.clone(),
+            tag: self.tag
+        }
+    }
+}
@@ -7,9 +7,8 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::rc::Rc;
-
 use super::queueable_token::QueueableToken;
+use super::Rc;
 use RuleType;
 use position;
 use token::Token;
@@ -62,7 +61,7 @@ impl<'i, R: RuleType> Iterator for Tokens<'i, R> {
                     pos: unsafe { position::new(self.input, pos) }
                 }
             }
-            QueueableToken::End { rule, pos } => {
+            QueueableToken::End { rule, pos, .. } => {
                 Token::End {
                     rule,
                     // QueueableTokens are safely created.
@@ -53,21 +53,41 @@
 //!
 //! The grammar of `.pest` files is documented in the
 //! [`pest_derive` crate](https://docs.rs/pest_derive/#Grammar).
+//!
+//! ## Thread safety
+//!
+//! By default, `Pairs` and `Pair` share their tokens through an `Rc` and cannot be sent across
+//! threads. Enabling the `sync` feature backs them by an `Arc` instead, making them `Send` and
+//! `Sync` as long as the `Rule` type is.
+//!
+//! ```toml
+//! pest = { version = "*", features = ["sync"] }
+//! ```
 
 #![doc(html_root_url = "https://docs.rs/pest")]
 
+extern crate unicode_segmentation;
+extern crate unicode_width;
+
 use std::fmt::Debug;
 use std::hash::Hash;
 
+mod diagnostic;
+mod encoding;
 mod error;
 pub mod iterators;
+mod line_index;
 mod macros;
 mod parser;
 mod parser_state;
 mod position;
 pub mod prec_climber;
+mod renderer;
+mod source_map;
 mod span;
+mod stack;
 mod token;
+pub mod unicode;
 
 /// A `trait` which parser rules must implement.
 ///
@@ -79,9 +99,15 @@ mod token;
 pub trait RuleType: Copy + Debug + Eq + Hash + Ord {}
 impl<T: Copy + Debug + Eq + Hash + Ord> RuleType for T {}
 
+pub use diagnostic::Diagnostic;
+pub use encoding::ColumnEncoding;
 pub use error::Error;
+pub use line_index::LineIndex;
 pub use parser::Parser;
-pub use parser_state::{state, Atomicity, Lookahead, ParserState};
+pub use parser_state::{recovering_state, state, Atomicity, Lookahead, ParserState};
 pub use position::Position;
+pub use renderer::Renderer;
+pub use source_map::{FileId, SourceMap};
 pub use span::Span;
+pub use stack::Stack;
 pub use token::Token;
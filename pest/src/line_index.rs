@@ -0,0 +1,295 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use encoding::ColumnEncoding;
+
+/// A `struct` which caches the starts of all lines of a `&str`. It is built once per input and
+/// answers line lookups in `O(log n)`, whereas
+/// [`Position::line_col`](struct.Position.html#method.line_col) and
+/// [`Position::line_of`](struct.Position.html#method.line_of) rescan the input on every call.
+///
+/// All offsets are byte offsets into the indexed input, as returned by
+/// [`Position::pos`](struct.Position.html#method.pos) or
+/// [`Span::start`](struct.Span.html#method.start). Lines are separated by `"\n"` and `"\r\n"`.
+/// Columns are counted in `char`s unless another
+/// [`ColumnEncoding`](enum.ColumnEncoding.html) is set with
+/// [`with_encoding`](#method.with_encoding).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LineIndex<'i> {
+    input: &'i str,
+    lines: Vec<usize>,
+    encoding: ColumnEncoding
+}
+
+impl<'i> LineIndex<'i> {
+    /// Creates a new `LineIndex` from an `input`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::LineIndex;
+    /// let index = LineIndex::new("ab\ncd");
+    ///
+    /// assert_eq!(index.line_count(), 2);
+    /// ```
+    pub fn new(input: &'i str) -> LineIndex<'i> {
+        let mut lines = vec![0];
+
+        lines.extend(
+            input
+                .bytes()
+                .enumerate()
+                .filter(|&(_, b)| b == b'\n')
+                .map(|(i, _)| i + 1)
+        );
+
+        LineIndex {
+            input,
+            lines,
+            encoding: ColumnEncoding::Chars
+        }
+    }
+
+    /// Sets the `ColumnEncoding` used to count columns, consuming the `LineIndex`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{ColumnEncoding, LineIndex};
+    /// let index = LineIndex::new("\ta").with_encoding(ColumnEncoding::Display { tab_width: 4 });
+    ///
+    /// assert_eq!(index.line_col(1), (1, 5));
+    /// ```
+    #[inline]
+    pub fn with_encoding(mut self, encoding: ColumnEncoding) -> LineIndex<'i> {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns the `ColumnEncoding` used to count columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{ColumnEncoding, LineIndex};
+    /// let index = LineIndex::new("");
+    ///
+    /// assert_eq!(index.encoding(), ColumnEncoding::Chars);
+    /// ```
+    #[inline]
+    pub fn encoding(&self) -> ColumnEncoding {
+        self.encoding
+    }
+
+    /// Returns the indexed input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::LineIndex;
+    /// let index = LineIndex::new("ab");
+    ///
+    /// assert_eq!(index.input(), "ab");
+    /// ```
+    #[inline]
+    pub fn input(&self) -> &'i str {
+        self.input
+    }
+
+    /// Returns the number of lines in the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::LineIndex;
+    /// assert_eq!(LineIndex::new("").line_count(), 1);
+    /// assert_eq!(LineIndex::new("a\n").line_count(), 2);
+    /// ```
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns the line - and column number pair of the byte offset `pos`, just like
+    /// [`Position::line_col_with`](struct.Position.html#method.line_col_with) with the
+    /// `LineIndex`'s encoding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is out of bounds or not on a `char` boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::LineIndex;
+    /// let index = LineIndex::new("\na");
+    ///
+    /// assert_eq!(index.line_col(2), (2, 2));
+    /// ```
+    #[inline]
+    pub fn line_col(&self, pos: usize) -> (usize, usize) {
+        let line = self.find_line(pos);
+        let col = self.encoding.width(&self.input[self.lines[line]..pos]) + 1;
+
+        (line + 1, col)
+    }
+
+    /// Returns the line containing the byte offset `pos`, without its line ending, just like
+    /// [`Position::line_of`](struct.Position.html#method.line_of).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::LineIndex;
+    /// let index = LineIndex::new("\na");
+    ///
+    /// assert_eq!(index.line_of(2), "a");
+    /// ```
+    #[inline]
+    pub fn line_of(&self, pos: usize) -> &'i str {
+        self.line_str(self.find_line(pos))
+    }
+
+    /// Returns the byte offset where the 1-based `line` starts, or `None` if there is no such
+    /// line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::LineIndex;
+    /// let index = LineIndex::new("ab\ncd");
+    ///
+    /// assert_eq!(index.line_start(2), Some(3));
+    /// assert_eq!(index.line_start(3), None);
+    /// ```
+    #[inline]
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        if line == 0 {
+            return None;
+        }
+
+        self.lines.get(line - 1).cloned()
+    }
+
+    /// Returns the 1-based `line` without its line ending, or `None` if there is no such line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::LineIndex;
+    /// let index = LineIndex::new("ab\r\ncd");
+    ///
+    /// assert_eq!(index.line(1), Some("ab"));
+    /// assert_eq!(index.line(2), Some("cd"));
+    /// ```
+    #[inline]
+    pub fn line(&self, line: usize) -> Option<&'i str> {
+        if line == 0 || line > self.lines.len() {
+            return None;
+        }
+
+        Some(self.line_str(line - 1))
+    }
+
+    fn find_line(&self, pos: usize) -> usize {
+        if pos > self.input.len() {
+            panic!("position out of bounds");
+        }
+
+        match self.lines.binary_search(&pos) {
+            Ok(line) => line,
+            Err(line) => line - 1
+        }
+    }
+
+    fn line_str(&self, line: usize) -> &'i str {
+        let start = self.lines[line];
+        let mut end = match self.lines.get(line + 1) {
+            Some(&next) => next - 1,
+            None => self.input.len()
+        };
+
+        if end > start && self.input.as_bytes()[end - 1] == b'\r' {
+            end -= 1;
+        }
+
+        &self.input[start..end]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::position;
+
+    fn assert_matches_position(input: &str) {
+        let index = LineIndex::new(input);
+
+        for (pos, _) in input.char_indices().chain(Some((input.len(), ' '))) {
+            let position = unsafe { position::new(input, pos) };
+
+            assert_eq!(index.line_col(pos), position.line_col());
+            assert_eq!(index.line_of(pos), position.line_of());
+            assert_eq!(position.line_col_in(&index), position.line_col());
+            assert_eq!(position.line_of_in(&index), position.line_of());
+        }
+    }
+
+    #[test]
+    fn line_col() {
+        let index = LineIndex::new("a\rb\nc\r\nd嗨");
+
+        assert_eq!(index.line_col(0), (1, 1));
+        assert_eq!(index.line_col(3), (1, 4));
+        assert_eq!(index.line_col(4), (2, 1));
+        assert_eq!(index.line_col(6), (2, 3));
+        assert_eq!(index.line_col(7), (3, 1));
+        assert_eq!(index.line_col(11), (3, 3));
+    }
+
+    #[test]
+    fn matches_position() {
+        assert_matches_position("");
+        assert_matches_position("\n");
+        assert_matches_position("\n\n");
+        assert_matches_position("a\rb\nc\r\nd嗨");
+        assert_matches_position("ab\r\n\r\ncd\r");
+    }
+
+    #[test]
+    fn lines() {
+        let index = LineIndex::new("a\n\r\nb");
+
+        assert_eq!(index.line_count(), 3);
+        assert_eq!(index.line(0), None);
+        assert_eq!(index.line(1), Some("a"));
+        assert_eq!(index.line(2), Some(""));
+        assert_eq!(index.line(3), Some("b"));
+        assert_eq!(index.line(4), None);
+        assert_eq!(index.line_start(3), Some(4));
+    }
+
+    #[test]
+    fn line_col_utf16() {
+        let index = LineIndex::new("a\n𝄞b").with_encoding(ColumnEncoding::Utf16);
+
+        assert_eq!(index.line_col(6), (2, 3));
+        assert_eq!(index.line_col(7), (2, 4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_bounds() {
+        LineIndex::new("a").line_col(2);
+    }
+}
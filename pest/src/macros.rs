@@ -10,8 +10,9 @@
 #[doc(hidden)]
 #[macro_export]
 macro_rules! consumes_to {
-    ( $_rules:ident, $tokens:expr, [] ) => ();
-    ( $rules:ident, $tokens:expr, [ $name:ident ( $start:expr, $end:expr ) ] ) => {
+    ( $_rules:ident, $_tokens:expr, $_pairs:expr, [] ) => ();
+    ( $rules:ident, $tokens:expr, $pairs:expr,
+      [ $name:ident ( $start:expr, $end:expr $( , # $tag:ident )* ) ] ) => {
         let expected = format!("expected Start {{ rule: {:?}, pos: Position {{ pos: {} }} }}",
                                $rules::$name, $start);
         match $tokens.next().expect(&format!("{} but found nothing", expected)) {
@@ -26,6 +27,18 @@ macro_rules! consumes_to {
             token => panic!("{}", format!("{} but found {:?}", expected, token))
         };
 
+        // Pairs are flattened in the order of their Start tokens.
+        #[allow(unused_variables)]
+        let pair = $pairs.next().unwrap();
+        $(
+            let expected = format!("expected {:?} to be tagged with {}", $rules::$name,
+                                   stringify!($tag));
+
+            if pair.as_node_tag() != Some(stringify!($tag)) {
+                panic!("{} but found {:?}", expected, pair.as_node_tag());
+            }
+        )*
+
         let expected = format!("expected End {{ rule: {:?}, pos: Position {{ pos: {} }} }}",
                                $rules::$name, $end);
         match $tokens.next().expect(&format!("{} but found nothing", expected)) {
@@ -40,8 +53,9 @@ macro_rules! consumes_to {
             token => panic!("{}", format!("{} but found {:?}", expected, token))
         };
     };
-    ( $rules:ident, $tokens:expr, [ $name:ident ( $start:expr, $end:expr ),
-                                    $( $names:ident $calls:tt ),* $(,)* ] ) => {
+    ( $rules:ident, $tokens:expr, $pairs:expr,
+      [ $name:ident ( $start:expr, $end:expr $( , # $tag:ident )* ),
+        $( $names:ident $calls:tt ),* $(,)* ] ) => {
 
         let expected = format!("expected Start {{ rule: {:?}, pos: Position {{ pos: {} }} }}",
                                $rules::$name, $start);
@@ -57,6 +71,18 @@ macro_rules! consumes_to {
             token => panic!("{}", format!("{} but found {:?}", expected, token))
         };
 
+        // Pairs are flattened in the order of their Start tokens.
+        #[allow(unused_variables)]
+        let pair = $pairs.next().unwrap();
+        $(
+            let expected = format!("expected {:?} to be tagged with {}", $rules::$name,
+                                   stringify!($tag));
+
+            if pair.as_node_tag() != Some(stringify!($tag)) {
+                panic!("{} but found {:?}", expected, pair.as_node_tag());
+            }
+        )*
+
         let expected = format!("expected End {{ rule: {:?}, pos: Position {{ pos: {} }} }}",
                                $rules::$name, $end);
         match $tokens.next().expect(&format!("{} but found nothing", expected)) {
@@ -71,10 +97,11 @@ macro_rules! consumes_to {
             token => panic!("{}", format!("{} but found {:?}", expected, token))
         };
 
-        consumes_to!($rules, $tokens, [ $( $names $calls ),* ]);
+        consumes_to!($rules, $tokens, $pairs, [ $( $names $calls ),* ]);
     };
-    ( $rules:ident, $tokens:expr, [ $name:ident ( $start:expr, $end:expr,
-                                                  [ $( $names:ident $calls:tt ),* $(,)* ] ) ] ) => {
+    ( $rules:ident, $tokens:expr, $pairs:expr,
+      [ $name:ident ( $start:expr, $end:expr $( , # $tag:ident )*,
+                      [ $( $names:ident $calls:tt ),* $(,)* ] ) ] ) => {
         let expected = format!("expected Start {{ rule: {:?}, pos: Position {{ pos: {} }} }}",
                                $rules::$name, $start);
         match $tokens.next().expect(&format!("{} but found nothing", expected)) {
@@ -89,7 +116,19 @@ macro_rules! consumes_to {
             token => panic!("{}", format!("{} but found {:?}", expected, token))
         };
 
-        consumes_to!($rules, $tokens, [ $( $names $calls ),* ]);
+        // Pairs are flattened in the order of their Start tokens.
+        #[allow(unused_variables)]
+        let pair = $pairs.next().unwrap();
+        $(
+            let expected = format!("expected {:?} to be tagged with {}", $rules::$name,
+                                   stringify!($tag));
+
+            if pair.as_node_tag() != Some(stringify!($tag)) {
+                panic!("{} but found {:?}", expected, pair.as_node_tag());
+            }
+        )*
+
+        consumes_to!($rules, $tokens, $pairs, [ $( $names $calls ),* ]);
 
         let expected = format!("expected End {{ rule: {:?}, pos: Position {{ pos: {} }} }}",
                                $rules::$name, $end);
@@ -105,10 +144,10 @@ macro_rules! consumes_to {
             token => panic!("{}", format!("{} but found {:?}", expected, token))
         };
     };
-    ( $rules:ident, $tokens:expr, [ $name:ident ( $start:expr, $end:expr,
-                                                  [ $( $nested_names:ident $nested_calls:tt ),*
-                                                  $(,)* ] ),
-                                    $( $names:ident $calls:tt ),* ] ) => {
+    ( $rules:ident, $tokens:expr, $pairs:expr,
+      [ $name:ident ( $start:expr, $end:expr $( , # $tag:ident )*,
+                      [ $( $nested_names:ident $nested_calls:tt ),* $(,)* ] ),
+        $( $names:ident $calls:tt ),* ] ) => {
 
         let expected = format!("expected Start {{ rule: {:?}, pos: Position {{ pos: {} }} }}",
                                $rules::$name, $start);
@@ -124,7 +163,19 @@ macro_rules! consumes_to {
             token => panic!("{}", format!("{} but found {:?}", expected, token))
         };
 
-        consumes_to!($rules, $tokens, [ $( $nested_names $nested_calls ),* ]);
+        // Pairs are flattened in the order of their Start tokens.
+        #[allow(unused_variables)]
+        let pair = $pairs.next().unwrap();
+        $(
+            let expected = format!("expected {:?} to be tagged with {}", $rules::$name,
+                                   stringify!($tag));
+
+            if pair.as_node_tag() != Some(stringify!($tag)) {
+                panic!("{} but found {:?}", expected, pair.as_node_tag());
+            }
+        )*
+
+        consumes_to!($rules, $tokens, $pairs, [ $( $nested_names $nested_calls ),* ]);
 
         let expected = format!("expected End {{ rule: {:?}, pos: Position {{ pos: {} }} }}",
                                $rules::$name, $end);
@@ -140,7 +191,7 @@ macro_rules! consumes_to {
             token => panic!("{}", format!("{} but found {:?}", expected, token))
         };
 
-        consumes_to!($rules, $tokens, [ $( $names $calls ),* ]);
+        consumes_to!($rules, $tokens, $pairs, [ $( $names $calls ),* ]);
     };
 }
 
@@ -155,6 +206,10 @@ macro_rules! consumes_to {
 ///
 /// *Note:* `start_pos` and `end_pos` are byte positions.
 ///
+/// A token pair can also assert the node tag of its `Pair` with `name(start_pos, end_pos, #tag)`
+/// or `name(start_pos, end_pos, #tag, [nested_child_tokens])`. The tags of token pairs without
+/// one are not checked.
+///
 /// # Examples
 ///
 /// ```
@@ -206,13 +261,15 @@ macro_rules! parses_to {
     ( parser: $parser:ident, input: $string:expr, rule: $rules:tt :: $rule:tt,
       tokens: [ $( $names:ident $calls:tt ),* $(,)* ] ) => {
 
-        #[allow(unused_mut)]
+        #[allow(unused_mut, unused_variables)]
         {
             use $crate::Parser;
 
-            let mut tokens = $parser::parse($rules::$rule, $string).unwrap().tokens();
+            let pairs = $parser::parse($rules::$rule, $string).unwrap();
+            let mut tokens = pairs.clone().tokens();
+            let mut flat_pairs = pairs.flatten();
 
-            consumes_to!($rules, &mut tokens, [ $( $names $calls ),* ]);
+            consumes_to!($rules, &mut tokens, &mut flat_pairs, [ $( $names $calls ),* ]);
 
             let rest: Vec<_> = tokens.collect();
 
@@ -287,7 +344,7 @@ macro_rules! fails_with {
             let error = $parser::parse($rules::$rule, $string).unwrap_err();
 
             match error {
-                $crate::Error::ParsingError { positives, negatives, pos } => {
+                $crate::Error::ParsingError { positives, negatives, pos, .. } => {
                     assert_eq!(positives, $positives);
                     assert_eq!(negatives, $negatives);
                     assert_eq!(pos.pos(), $pos);
@@ -331,6 +388,22 @@ pub mod tests {
         }
     }
 
+    struct TaggedParser;
+
+    impl Parser<Rule> for TaggedParser {
+        fn parse<'i>(_: Rule, input: &'i str) -> Result<Pairs<'i, Rule>, Error<'i, Rule>> {
+            state(input, |state, pos| {
+                state.rule(Rule::a, pos, |state, pos| {
+                    state
+                        .tag_node("lhs", move |state| {
+                            state.rule(Rule::b, pos, |_, pos| pos.match_string("b"))
+                        })
+                        .and_then(|pos| state.rule(Rule::c, pos, |_, pos| pos.match_string("c")))
+                })
+            })
+        }
+    }
+
     #[test]
     fn parses_to() {
         parses_to! {
@@ -346,6 +419,53 @@ pub mod tests {
         };
     }
 
+    #[test]
+    fn parses_to_tags() {
+        parses_to! {
+            parser: TaggedParser,
+            input: "bc",
+            rule: Rule::a,
+            tokens: [
+                a(0, 2, [
+                    b(0, 1, #lhs),
+                    c(1, 2)
+                ])
+            ]
+        };
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_tag() {
+        parses_to! {
+            parser: TaggedParser,
+            input: "bc",
+            rule: Rule::a,
+            tokens: [
+                a(0, 2, [
+                    b(0, 1, #rhs),
+                    c(1, 2)
+                ])
+            ]
+        };
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_tag() {
+        parses_to! {
+            parser: TaggedParser,
+            input: "bc",
+            rule: Rule::a,
+            tokens: [
+                a(0, 2, #lhs, [
+                    b(0, 1),
+                    c(1, 2)
+                ])
+            ]
+        };
+    }
+
     #[test]
     #[should_panic]
     fn missing_end() {
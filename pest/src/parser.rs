@@ -9,10 +9,25 @@
 
 use RuleType;
 use error::Error;
-use iterators::Pairs;
+use iterators::{pairs, Pairs, Rc};
 
 /// A `trait` that defines a `Parser`.
 pub trait Parser<R: RuleType> {
     /// Parses an `&str` starting from `rule`.
     fn parse(rule: R, input: &str) -> Result<Pairs<R>, Error<R>>;
+
+    /// Parses an `&str` starting from `rule`, recovering from errors with
+    /// [`ParserState::recover`](struct.ParserState.html#method.recover), and returns the resulting
+    /// `Pairs` together with every error that was recovered from, just like
+    /// [`pest::recovering_state`](fn.recovering_state.html).
+    ///
+    /// By default, no errors are recovered from: the `Pairs` returned by
+    /// [`parse`](#tymethod.parse) are returned without errors, or its error is returned with
+    /// empty `Pairs`.
+    fn parse_recovering<'i>(rule: R, input: &'i str) -> (Pairs<'i, R>, Vec<Error<'i, R>>) {
+        match Self::parse(rule, input) {
+            Ok(pairs) => (pairs, vec![]),
+            Err(error) => (pairs::new(Rc::new(vec![]), input, 0, 0), vec![error])
+        }
+    }
 }
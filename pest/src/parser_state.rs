@@ -7,16 +7,19 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::rc::Rc;
+use std::collections::HashMap;
+use std::mem;
+use std::ops::Range;
 
 use RuleType;
 use error::Error;
-use iterators::{pairs, QueueableToken};
+use iterators::{pairs, QueueableToken, Rc};
 use position::{self, Position};
 use span::Span;
+use stack::Stack;
 
 /// An `enum` specifying the current lookahead status of a `ParserState`.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Lookahead {
     Positive,
     Negative,
@@ -24,7 +27,7 @@ pub enum Lookahead {
 }
 
 /// An `enum` specifying the current atomicity of a `ParserState`.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Atomicity {
     Atomic,
     CompoundAtomic,
@@ -34,15 +37,49 @@ pub enum Atomicity {
 /// A `struct` which contains the complete state of a `Parser`.
 #[derive(Debug)]
 pub struct ParserState<'i, R: RuleType> {
+    input: &'i str,
     queue: Vec<QueueableToken<R>>,
     lookahead: Lookahead,
     pos_attempts: Vec<R>,
     neg_attempts: Vec<R>,
+    literal_attempts: Vec<String>,
     attempt_pos: usize,
+    attempt_rule_stack: Vec<(R, usize)>,
+    errors: Vec<Error<'i, R>>,
+    recovered_attempts: Vec<Attempts<R>>,
+    memoized: bool,
+    rule_stack_tracked: bool,
+    rule_stack: Vec<(R, usize)>,
+    memos: HashMap<MemoKey<R>, Memo<'i, R>>,
     /// Specifies current atomicity
     pub atomicity: Atomicity,
-    /// Stack of `Span`s
-    pub stack: Vec<Span<'i>>
+    /// Stack of `Span`s which is restored on backtracking
+    pub stack: Stack<Span<'i>>
+}
+
+// Token generation depends on both the lookahead and the atomicity that a rule is being run with,
+// so these need to be part of the key as well.
+type MemoKey<R> = (R, usize, Lookahead, Atomicity);
+
+// Successful memos store the tokens generated by the rule with pair indices relative to the rule's
+// starting token so that they can be replayed at any index in the queue. Both successful and
+// failed memos store the attempts made by the rule so that errors are the same as without
+// memoization.
+type Memo<'i, R> = (
+    Result<(Position<'i>, Vec<QueueableToken<R>>), Position<'i>>,
+    Attempts<R>
+);
+
+// Attempts made at the deepest position they reached. When stored in a memo, their rule stack is
+// relative to the stack of `rule`s the memoized rule was run inside of, which differs between
+// callers.
+#[derive(Clone, Debug)]
+struct Attempts<R> {
+    pos: usize,
+    positives: Vec<R>,
+    negatives: Vec<R>,
+    literals: Vec<String>,
+    rule_stack: Vec<(R, usize)>
 }
 
 /// Creates a `ParserState` from a `&str`, supplying it to a closure `f`.
@@ -59,39 +96,92 @@ pub struct ParserState<'i, R: RuleType> {
 /// }).unwrap();
 /// ```
 pub fn state<'i, R: RuleType, F>(input: &'i str, f: F) -> Result<pairs::Pairs<'i, R>, Error<'i, R>>
+where
+    F: FnOnce(&mut ParserState<'i, R>, Position<'i>) -> Result<Position<'i>, Position<'i>>
+{
+    let (pairs, mut errors) = recovering_state(input, f);
+
+    if errors.is_empty() {
+        Ok(pairs)
+    } else {
+        Err(errors.remove(0))
+    }
+}
+
+/// Creates a `ParserState` from a `&str`, supplying it to a closure `f`, and returns the resulting
+/// `Pairs` together with every error that was recovered from with
+/// [`ParserState::recover`](struct.ParserState.html#method.recover).
+///
+/// If `f` fails, the `Pairs` are empty and its error is the last one returned.
+///
+/// # Examples
+///
+/// ```
+/// # use std::rc::Rc;
+/// # use pest;
+/// # #[allow(non_camel_case_types)]
+/// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+/// enum Rule {
+///     a,
+///     error
+/// }
+///
+/// let input = "b;a";
+/// let (pairs, errors) = pest::recovering_state(input, |state, pos| {
+///     state.recover(Rule::error, pos, |state, pos| {
+///         state.rule(Rule::a, pos, |_, p| p.match_string("a"))
+///     }, |p| p.match_string(";")).and_then(|p| {
+///         p.match_string(";")
+///     }).and_then(|p| {
+///         state.rule(Rule::a, p, |_, p| p.match_string("a"))
+///     })
+/// });
+///
+/// assert_eq!(format!("{}", pairs), "[error(0, 1), a(2, 3)]");
+/// assert_eq!(errors.len(), 1);
+/// ```
+pub fn recovering_state<'i, R: RuleType, F>(
+    input: &'i str,
+    f: F
+) -> (pairs::Pairs<'i, R>, Vec<Error<'i, R>>)
 where
     F: FnOnce(&mut ParserState<'i, R>, Position<'i>) -> Result<Position<'i>, Position<'i>>
 {
     let mut state = ParserState {
+        input,
         queue: vec![],
         lookahead: Lookahead::None,
         pos_attempts: vec![],
         neg_attempts: vec![],
+        literal_attempts: vec![],
         attempt_pos: 0,
+        attempt_rule_stack: vec![],
+        errors: vec![],
+        recovered_attempts: vec![],
+        memoized: false,
+        rule_stack_tracked: false,
+        rule_stack: vec![],
+        memos: HashMap::new(),
         atomicity: Atomicity::NonAtomic,
-        stack: vec![]
+        stack: Stack::new()
     };
 
-    if f(&mut state, Position::from_start(input)).is_ok() {
-        let len = state.queue.len();
-        Ok(pairs::new(Rc::new(state.queue), input, 0, len))
-    } else {
-        state.pos_attempts.sort();
-        state.pos_attempts.dedup();
-        state.neg_attempts.sort();
-        state.neg_attempts.dedup();
-
-        Err(Error::ParsingError {
-            positives: state.pos_attempts,
-            negatives: state.neg_attempts,
-            // All attempted positions were legal.
-            pos: unsafe { position::new(input, state.attempt_pos) }
-        })
+    if f(&mut state, Position::from_start(input)).is_err() {
+        let attempts = state.take_attempts();
+        let error = state.parsing_error(&attempts);
+
+        state.queue.clear();
+        state.errors.push(error);
     }
+
+    let len = state.queue.len();
+    let pairs = pairs::new(Rc::new(state.queue), input, 0, len);
+
+    (pairs, state.errors)
 }
 
 impl<'i, R: RuleType> ParserState<'i, R> {
-    /// Wrapper needed to generate tokens.
+    /// Wrapper needed to generate tokens. Changes made to the stack by `f` are undone if it fails.
     ///
     /// # Examples
     ///
@@ -111,6 +201,10 @@ impl<'i, R: RuleType> ParserState<'i, R> {
     ///
     /// assert_eq!(pairs.len(), 1);
     /// ```
+    ///
+    /// When run inside of [`ParserState::memoize`](#method.memoize), the result of the `rule`
+    /// together with the `Token`s it generated is cached per position and replayed whenever the
+    /// same `rule` is tried again at that position.
     #[inline]
     pub fn rule<F>(
         &mut self,
@@ -123,14 +217,38 @@ impl<'i, R: RuleType> ParserState<'i, R> {
     {
         let actual_pos = pos.pos();
         let index = self.queue.len();
+        let errors_index = self.errors.len();
 
-        let (pos_attempts_index, neg_attempts_index) = if actual_pos == self.attempt_pos {
-            (self.pos_attempts.len(), self.neg_attempts.len())
+        let attempts_indices = if actual_pos == self.attempt_pos {
+            (
+                self.pos_attempts.len(),
+                self.neg_attempts.len(),
+                self.literal_attempts.len()
+            )
         } else {
             // Attempts have not been cleared yet since the attempt_pos is older.
-            (0, 0)
+            (0, 0, 0)
         };
 
+        // Rules that can see a non-empty stack may depend on its contents, so they are never
+        // memoized.
+        let key = (rule, actual_pos, self.lookahead, self.atomicity);
+        let memoizable = self.memoized && self.stack.is_empty();
+
+        if memoizable {
+            if let Some(result) = self.replay(&key) {
+                return result;
+            }
+        }
+
+        let initial_attempts = (
+            self.attempt_pos,
+            self.pos_attempts.len(),
+            self.neg_attempts.len(),
+            self.literal_attempts.len(),
+            self.rule_stack.len()
+        );
+
         if self.lookahead == Lookahead::None && self.atomicity != Atomicity::Atomic {
             // Pair's position will only be known after running the closure.
             self.queue.push(QueueableToken::Start {
@@ -140,17 +258,29 @@ impl<'i, R: RuleType> ParserState<'i, R> {
         }
 
         let attempts = self.pos_attempts.len() + self.neg_attempts.len();
+        let rule_stack_tracked = self.rule_stack_tracked;
+
+        self.stack.snapshot();
+
+        if rule_stack_tracked {
+            self.rule_stack.push((rule, actual_pos));
+        }
 
         let result = f(self, pos);
 
+        if rule_stack_tracked {
+            self.rule_stack.pop();
+        }
+
+        if result.is_err() {
+            self.stack.restore();
+            self.discard_errors(errors_index);
+        } else {
+            self.stack.clear_snapshot();
+        }
+
         if result.is_err() ^ (self.lookahead == Lookahead::Negative) {
-            self.track(
-                rule,
-                actual_pos,
-                pos_attempts_index,
-                neg_attempts_index,
-                attempts
-            );
+            self.track(rule, actual_pos, attempts_indices, attempts);
         }
 
         if self.lookahead == Lookahead::None && self.atomicity != Atomicity::Atomic {
@@ -165,6 +295,7 @@ impl<'i, R: RuleType> ParserState<'i, R> {
 
                 self.queue.push(QueueableToken::End {
                     rule,
+                    tag: None,
                     pos: pos.pos()
                 });
             } else {
@@ -172,15 +303,125 @@ impl<'i, R: RuleType> ParserState<'i, R> {
             }
         }
 
+        // Recovered errors are not replayed, so rules that recovered are not memoized.
+        if memoizable && self.stack.is_empty() && self.errors.len() == errors_index {
+            let memo = match result {
+                Ok(ref pos) => {
+                    let tokens = self.queue[index..]
+                        .iter()
+                        .map(|token| match *token {
+                            QueueableToken::Start { pair, pos } => QueueableToken::Start {
+                                pair: pair - index,
+                                pos
+                            },
+                            ref token => token.clone()
+                        })
+                        .collect();
+
+                    Ok((pos.clone(), tokens))
+                }
+                Err(ref pos) => Err(pos.clone())
+            };
+            let attempts = self.memo_attempts(initial_attempts);
+
+            self.memos.insert(key, (memo, attempts));
+        }
+
         result
     }
 
+    fn replay(&mut self, key: &MemoKey<R>) -> Option<Result<Position<'i>, Position<'i>>> {
+        let index = self.queue.len();
+
+        let (result, attempts) = match *self.memos.get(key)? {
+            (Ok((ref pos, ref tokens)), ref attempts) => {
+                self.queue
+                    .extend(tokens.iter().map(|token| match *token {
+                        QueueableToken::Start { pair, pos } => QueueableToken::Start {
+                            pair: pair + index,
+                            pos
+                        },
+                        ref token => token.clone()
+                    }));
+
+                (Ok(pos.clone()), attempts.clone())
+            }
+            (Err(ref pos), ref attempts) => (Err(pos.clone()), attempts.clone())
+        };
+
+        let mut rule_stack = self.rule_stack.clone();
+        rule_stack.extend(attempts.rule_stack);
+
+        self.merge_attempts(Attempts {
+            rule_stack,
+            ..attempts
+        });
+
+        Some(result)
+    }
+
+    // Collects the attempts made since `initial_attempts` were taken at the start of a rule. When
+    // the attempts have not advanced, those made before the rule are still kept in front of its
+    // own.
+    fn memo_attempts(
+        &self,
+        (attempt_pos, positives, negatives, literals, depth): (usize, usize, usize, usize, usize)
+    ) -> Attempts<R> {
+        let (positives, negatives, literals) = if self.attempt_pos == attempt_pos {
+            (positives, negatives, literals)
+        } else {
+            (0, 0, 0)
+        };
+
+        Attempts {
+            pos: self.attempt_pos,
+            positives: self.pos_attempts[positives.min(self.pos_attempts.len())..].to_vec(),
+            negatives: self.neg_attempts[negatives.min(self.neg_attempts.len())..].to_vec(),
+            literals: self.literal_attempts[literals.min(self.literal_attempts.len())..].to_vec(),
+            rule_stack: self.attempt_rule_stack
+                .get(depth..)
+                .map(|rule_stack| rule_stack.to_vec())
+                .unwrap_or_default()
+        }
+    }
+
+    // Adds `attempts` to the ones tracked so far, as if they had been made right now.
+    fn merge_attempts(&mut self, attempts: Attempts<R>) {
+        let count = attempts.positives.len() + attempts.negatives.len() + attempts.literals.len();
+
+        if count == 0 {
+            return;
+        }
+
+        self.advance_attempts(attempts.pos);
+
+        if attempts.pos == self.attempt_pos {
+            let tracked =
+                self.pos_attempts.len() + self.neg_attempts.len() + self.literal_attempts.len();
+
+            if tracked == 0 {
+                self.attempt_rule_stack = attempts.rule_stack;
+            } else {
+                let common = self.attempt_rule_stack
+                    .iter()
+                    .zip(&attempts.rule_stack)
+                    .take_while(|&(a, b)| a == b)
+                    .count();
+
+                self.attempt_rule_stack.truncate(common);
+            }
+
+            self.pos_attempts.extend(attempts.positives);
+            self.neg_attempts.extend(attempts.negatives);
+            self.literal_attempts.extend(attempts.literals);
+        }
+    }
+
     fn track(
         &mut self,
         rule: R,
         pos: usize,
-        pos_attempts_index: usize,
-        neg_attempts_index: usize,
+        (pos_attempts_index, neg_attempts_index, literal_attempts_index): (usize, usize, usize),
         prev_attempts: usize
     ) {
         if self.atomicity == Atomicity::Atomic {
@@ -189,7 +430,7 @@ impl<'i, R: RuleType> ParserState<'i, R> {
 
         // If nested rules made no progress, there is no use to report them; it's only useful to
         // track the current rule, the exception being when only one attempt has been made during
-        // the children rules.
+        // the children rules. Literals attempted by the current rule are always replaced by it.
         let curr_attempts = self.pos_attempts.len() + self.neg_attempts.len();
         if curr_attempts > prev_attempts && curr_attempts - prev_attempts == 1 {
             return;
@@ -198,26 +439,203 @@ impl<'i, R: RuleType> ParserState<'i, R> {
         if pos == self.attempt_pos {
             self.pos_attempts.truncate(pos_attempts_index);
             self.neg_attempts.truncate(neg_attempts_index);
+            self.literal_attempts.truncate(literal_attempts_index);
+        }
+
+        self.advance_attempts(pos);
+
+        if pos == self.attempt_pos {
+            self.narrow_attempt_rule_stack();
+
+            if self.lookahead != Lookahead::Negative {
+                self.pos_attempts.push(rule);
+            } else {
+                self.neg_attempts.push(rule);
+            }
+        }
+    }
+
+    // Tracks a failed literal just like a failed rule, but only outside of negative lookaheads,
+    // where it would have to be reported as unexpected without having matched anything.
+    fn track_literal<F>(&mut self, pos: usize, literal: F)
+    where
+        F: FnOnce() -> String
+    {
+        if self.atomicity == Atomicity::Atomic || self.lookahead == Lookahead::Negative {
+            return;
+        }
+
+        self.advance_attempts(pos);
+
+        if pos == self.attempt_pos {
+            self.narrow_attempt_rule_stack();
+            self.literal_attempts.push(literal());
+        }
+    }
+
+    // Keeps the rules that every attempt at the deepest position was made inside of, which is
+    // the common start of their rule stacks.
+    fn narrow_attempt_rule_stack(&mut self) {
+        let attempts = self.pos_attempts.len() + self.neg_attempts.len();
+
+        if attempts + self.literal_attempts.len() == 0 {
+            self.attempt_rule_stack = self.rule_stack.clone();
+        } else {
+            let common = self.attempt_rule_stack
+                .iter()
+                .zip(&self.rule_stack)
+                .take_while(|&(a, b)| a == b)
+                .count();
+
+            self.attempt_rule_stack.truncate(common);
         }
+    }
 
+    // Drops all attempts when `pos` is deeper than the position they were made at.
+    fn advance_attempts(&mut self, pos: usize) {
         if pos > self.attempt_pos {
             self.pos_attempts.clear();
             self.neg_attempts.clear();
+            self.literal_attempts.clear();
             self.attempt_pos = pos;
         }
+    }
 
-        let attempts = if self.lookahead != Lookahead::Negative {
-            &mut self.pos_attempts
-        } else {
-            &mut self.neg_attempts
-        };
+    /// Wrapper which recovers from the failure of `f` by skipping input up to the next position
+    /// where `sync` matches.
+    ///
+    /// When `f` fails, the error it produced is recorded and the input is skipped up to the first
+    /// position where `sync` matches, or up to the end of the input. The skipped input is covered by
+    /// a `Token` pair of `rule`, which marks the error in the resulting `Pairs`, and the `Position`
+    /// right before the synchronization point is returned. Recorded errors are returned by
+    /// [`pest::recovering_state`](fn.recovering_state.html), while
+    /// [`pest::state`](fn.state.html) fails with the first one.
+    ///
+    /// Errors recorded inside of sequences or `rule`s that end up failing are discarded, and no
+    /// recovery takes place inside of lookaheads. Since `recover` does not make progress when it
+    /// is already at a synchronization point, it should not be repeated on its own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a,
+    ///     error
+    /// }
+    ///
+    /// let input = "bc;";
+    /// let (pairs, errors) = pest::recovering_state(input, |state, pos| {
+    ///     state.recover(Rule::error, pos, |state, pos| {
+    ///         state.rule(Rule::a, pos, |_, p| p.match_string("a"))
+    ///     }, |p| p.match_string(";"))
+    /// });
+    ///
+    /// assert_eq!(format!("{}", pairs), "[error(0, 2)]");
+    /// assert_eq!(errors.len(), 1);
+    /// ```
+    #[inline]
+    pub fn recover<F, G>(
+        &mut self,
+        rule: R,
+        pos: Position<'i>,
+        f: F,
+        mut sync: G
+    ) -> Result<Position<'i>, Position<'i>>
+    where
+        F: FnOnce(&mut ParserState<'i, R>, Position<'i>) -> Result<Position<'i>, Position<'i>>,
+        G: FnMut(Position<'i>) -> Result<Position<'i>, Position<'i>>
+    {
+        if self.lookahead != Lookahead::None {
+            return f(self, pos);
+        }
 
-        if pos == self.attempt_pos {
-            attempts.push(rule);
+        let start = pos.clone();
+
+        if let Ok(pos) = self.sequence(move |state| f(state, pos)) {
+            return Ok(pos);
+        }
+
+        // The attempts are put aside, so that the errors found after recovering do not report
+        // them, until the errors are discarded on backtracking.
+        let attempts = self.take_attempts();
+        let error = self.parsing_error(&attempts);
+        self.errors.push(error);
+        self.recovered_attempts.push(attempts);
+
+        self.rule(rule, start, |_, mut pos| loop {
+            pos = match pos.lookahead(true, &mut sync) {
+                Ok(pos) => return Ok(pos),
+                Err(pos) => match pos.skip(1) {
+                    Ok(pos) => pos,
+                    Err(pos) => return Ok(pos)
+                }
+            };
+        })
+    }
+
+    // Resets the attempts, returning the ones at the deepest position.
+    fn take_attempts(&mut self) -> Attempts<R> {
+        let pos = self.attempt_pos;
+        self.attempt_pos = 0;
+
+        Attempts {
+            pos,
+            positives: mem::take(&mut self.pos_attempts),
+            negatives: mem::take(&mut self.neg_attempts),
+            literals: mem::take(&mut self.literal_attempts),
+            rule_stack: mem::take(&mut self.attempt_rule_stack)
+        }
+    }
+
+    // Creates an error out of a copy of `attempts`.
+    fn parsing_error(&self, attempts: &Attempts<R>) -> Error<'i, R> {
+        let mut positives = attempts.positives.clone();
+        let mut negatives = attempts.negatives.clone();
+        let mut literals = attempts.literals.clone();
+
+        positives.sort();
+        positives.dedup();
+        negatives.sort();
+        negatives.dedup();
+        literals.sort();
+        literals.dedup();
+
+        // All attempted positions were legal.
+        let input = self.input;
+        let rule_stack = attempts
+            .rule_stack
+            .iter()
+            .map(|&(rule, pos)| (rule, unsafe { position::new(input, pos) }))
+            .collect();
+        let pos = unsafe { position::new(input, attempts.pos) };
+
+        Error::ParsingError {
+            positives,
+            negatives,
+            literals,
+            rule_stack,
+            pos
+        }
+    }
+
+    // Discards the errors recorded since `index` on backtracking. The attempts that were put aside
+    // when recovering from them are tracked again, since the input they were made on is about to
+    // be parsed differently.
+    fn discard_errors(&mut self, index: usize) {
+        self.errors.truncate(index);
+
+        if index < self.recovered_attempts.len() {
+            for attempts in self.recovered_attempts.split_off(index) {
+                self.merge_attempts(attempts);
+            }
         }
     }
 
-    /// Wrapper which removes `Tokens` in case of a sequence's failure.
+    /// Wrapper which removes `Tokens` and restores the stack in case of a sequence's failure.
     ///
     /// Usually used in conjunction with
     /// [`Position::sequence`](struct.Position.html#method.sequence).
@@ -254,17 +672,78 @@ impl<'i, R: RuleType> ParserState<'i, R> {
         F: FnOnce(&mut ParserState<'i, R>) -> Result<Position<'i>, Position<'i>>
     {
         let index = self.queue.len();
+        let errors_index = self.errors.len();
+
+        self.stack.snapshot();
 
         let result = f(self);
 
         if result.is_err() {
             self.queue.truncate(index);
+            self.discard_errors(errors_index);
+            self.stack.restore();
+        } else {
+            self.stack.clear_snapshot();
+        }
+
+        result
+    }
+
+    /// Wrapper which tags the `Token` pairs generated by `f` with the node `tag`. Only the
+    /// outermost pairs are tagged, while the pairs nested inside of them keep their own tags. Tags
+    /// are returned by [`Pair::as_node_tag`](iterators/struct.Pair.html#method.as_node_tag).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = "a";
+    /// let pair = pest::state(input, |state, pos| {
+    ///     state.tag_node("lhs", move |state| {
+    ///         state.rule(Rule::a, pos, |_, p| p.match_string("a"))
+    ///     })
+    /// }).unwrap().next().unwrap();
+    ///
+    /// assert_eq!(pair.as_node_tag(), Some("lhs"));
+    /// ```
+    #[inline]
+    pub fn tag_node<F>(&mut self, tag: &str, f: F) -> Result<Position<'i>, Position<'i>>
+    where
+        F: FnOnce(&mut ParserState<'i, R>) -> Result<Position<'i>, Position<'i>>
+    {
+        let mut index = self.queue.len();
+
+        let result = f(self);
+
+        if result.is_ok() {
+            let tag: Rc<str> = Rc::from(tag);
+
+            while index < self.queue.len() {
+                let end = match self.queue[index] {
+                    QueueableToken::Start { pair, .. } => pair,
+                    _ => unreachable!()
+                };
+
+                if let QueueableToken::End { tag: ref mut end_tag, .. } = self.queue[end] {
+                    *end_tag = Some(Rc::clone(&tag));
+                }
+
+                index = end + 1;
+            }
         }
 
         result
     }
 
-    /// Wrapper which stops `Token`s from being generated.
+    /// Wrapper which stops `Token`s from being generated. Changes made to the stack by `f` are
+    /// always undone.
     ///
     /// Usually used in conjunction with
     /// [`Position::lookahead`](struct.Position.html#method.lookahead).
@@ -308,8 +787,11 @@ impl<'i, R: RuleType> ParserState<'i, R> {
             }
         };
 
+        self.stack.snapshot();
+
         let result = f(self);
 
+        self.stack.restore();
         self.lookahead = initial_lookahead;
 
         result
@@ -357,4 +839,955 @@ impl<'i, R: RuleType> ParserState<'i, R> {
 
         result
     }
+
+    /// Wrapper which turns memoization of `rule`s on or off according to `is_memoized`.
+    ///
+    /// Memoization (or packrat parsing) guarantees that every `rule` is run at most once per
+    /// position, which keeps parsing linear for grammars that would otherwise backtrack
+    /// exponentially, at the cost of caching every result. Just like atomicity, it cascades to
+    /// every `rule` run inside of `f`; wrapping the starting rule enables it for the whole parser.
+    ///
+    /// Rules that are run while the stack is not empty are never cached since their result might
+    /// depend on its contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a
+    /// }
+    ///
+    /// let input = "aab";
+    /// let pairs: Vec<_> = pest::state(input, |state, pos| {
+    ///     state.memoize(true, move |state| {
+    ///         state.sequence(move |state| {
+    ///             pos.sequence(|p| {
+    ///                 state.rule(Rule::a, p, |_, p| p.match_string("a")).and_then(|p| {
+    ///                     p.match_string("c")
+    ///                 })
+    ///             })
+    ///         }).or_else(|p| {
+    ///             // Rule::a is replayed from the cache instead of being run again.
+    ///             state.rule(Rule::a, p, |_, p| p.match_string("a"))
+    ///         })
+    ///     })
+    /// }).unwrap().collect();
+    ///
+    /// assert_eq!(pairs.len(), 1);
+    /// ```
+    #[inline]
+    pub fn memoize<F>(&mut self, is_memoized: bool, f: F) -> Result<Position<'i>, Position<'i>>
+    where
+        F: FnOnce(&mut ParserState<'i, R>) -> Result<Position<'i>, Position<'i>>
+    {
+        let initial_memoized = self.memoized;
+        self.memoized = is_memoized;
+
+        let result = f(self);
+
+        self.memoized = initial_memoized;
+
+        result
+    }
+
+    /// Wrapper which turns tracking of the stack of `rule`s being run on or off according to
+    /// `is_tracked`. Just like memoization, it cascades to every `rule` run inside of `f`.
+    ///
+    /// While tracked, the `rule`s that every attempt at the deepest position was made inside of
+    /// are kept in the `rule_stack` of the
+    /// [`Error::ParsingError`](enum.Error.html#variant.ParsingError), together with their
+    /// starting `Position`s, which tells which part of a deeply nested input failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest::{self, Error};
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// enum Rule {
+    ///     a,
+    ///     b
+    /// }
+    ///
+    /// let input = "ab";
+    /// let error = pest::state(input, |state, pos| {
+    ///     state.track_rule_stack(true, move |state| {
+    ///         state.rule(Rule::a, pos, |state, p| {
+    ///             p.match_string("a").and_then(|p| {
+    ///                 state.rule(Rule::b, p, |state, p| {
+    ///                     p.match_string("b").and_then(|p| state.match_string(p, "c"))
+    ///                 })
+    ///             })
+    ///         })
+    ///     })
+    /// }).unwrap_err();
+    ///
+    /// match error {
+    ///     Error::ParsingError { rule_stack, .. } => {
+    ///         assert_eq!(rule_stack.len(), 2);
+    ///         assert_eq!(rule_stack[0].0, Rule::a);
+    ///         assert_eq!(rule_stack[1].0, Rule::b);
+    ///         assert_eq!(rule_stack[1].1.pos(), 1);
+    ///     }
+    ///     _ => unreachable!()
+    /// };
+    /// ```
+    #[inline]
+    pub fn track_rule_stack<F>(
+        &mut self,
+        is_tracked: bool,
+        f: F
+    ) -> Result<Position<'i>, Position<'i>>
+    where
+        F: FnOnce(&mut ParserState<'i, R>) -> Result<Position<'i>, Position<'i>>
+    {
+        let initial_tracked = self.rule_stack_tracked;
+        self.rule_stack_tracked = is_tracked;
+
+        let result = f(self);
+
+        self.rule_stack_tracked = initial_tracked;
+
+        result
+    }
+
+    /// Matches `string` like [`Position::match_string`](struct.Position.html#method.match_string),
+    /// but also records it as expected when it fails at the deepest position, so that it is
+    /// listed as e.g. `` `}` `` in the `literals` of the
+    /// [`Error::ParsingError`](enum.Error.html#variant.ParsingError).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest::{self, Error};
+    /// let input = "a";
+    /// let error = pest::state::<(), _>(input, |state, pos| {
+    ///     state.match_string(pos, "a").and_then(|p| {
+    ///         state.match_string(p, ",").or_else(|p| state.match_string(p, "}"))
+    ///     })
+    /// }).unwrap_err();
+    ///
+    /// match error {
+    ///     Error::ParsingError { literals, .. } => assert_eq!(literals, vec!["`,`", "`}`"]),
+    ///     _ => unreachable!()
+    /// };
+    /// ```
+    #[inline]
+    pub fn match_string(
+        &mut self,
+        pos: Position<'i>,
+        string: &'i str
+    ) -> Result<Position<'i>, Position<'i>> {
+        let result = pos.match_string(string);
+
+        if let Err(ref pos) = result {
+            self.track_literal(pos.pos(), || format!("`{}`", escape(string)));
+        }
+
+        result
+    }
+
+    /// Matches `string` case insensitively like
+    /// [`Position::match_insensitive`](struct.Position.html#method.match_insensitive) and records
+    /// it as expected when it fails at the deepest position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest::{self, Error};
+    /// let input = "b";
+    /// let error = pest::state::<(), _>(input, |state, pos| {
+    ///     state.match_insensitive(pos, "a")
+    /// }).unwrap_err();
+    ///
+    /// match error {
+    ///     Error::ParsingError { literals, .. } => {
+    ///         assert_eq!(literals, vec!["`a` (case insensitive)"])
+    ///     }
+    ///     _ => unreachable!()
+    /// };
+    /// ```
+    #[inline]
+    pub fn match_insensitive(
+        &mut self,
+        pos: Position<'i>,
+        string: &str
+    ) -> Result<Position<'i>, Position<'i>> {
+        let result = pos.match_insensitive(string);
+
+        if let Err(ref pos) = result {
+            self.track_literal(pos.pos(), || format!("`{}` (case insensitive)", escape(string)));
+        }
+
+        result
+    }
+
+    /// Matches a `char` in `range` like
+    /// [`Position::match_range`](struct.Position.html#method.match_range) and records the
+    /// `range` as expected when it fails at the deepest position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest::{self, Error};
+    /// let input = "b";
+    /// let error = pest::state::<(), _>(input, |state, pos| {
+    ///     state.match_range(pos, '0'..'9')
+    /// }).unwrap_err();
+    ///
+    /// match error {
+    ///     Error::ParsingError { literals, .. } => assert_eq!(literals, vec!["`0`..`9`"]),
+    ///     _ => unreachable!()
+    /// };
+    /// ```
+    #[inline]
+    pub fn match_range(
+        &mut self,
+        pos: Position<'i>,
+        range: Range<char>
+    ) -> Result<Position<'i>, Position<'i>> {
+        let result = pos.match_range(range.clone());
+
+        if let Err(ref pos) = result {
+            self.track_literal(pos.pos(), || format_range(&range));
+        }
+
+        result
+    }
+
+    /// Matches a `char` in `range` case insensitively like
+    /// [`Position::match_range_insensitive`](struct.Position.html#method.match_range_insensitive)
+    /// and records the `range` as expected when it fails at the deepest position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest::{self, Error};
+    /// let input = "1";
+    /// let error = pest::state::<(), _>(input, |state, pos| {
+    ///     state.match_range_insensitive(pos, 'a'..'z')
+    /// }).unwrap_err();
+    ///
+    /// match error {
+    ///     Error::ParsingError { literals, .. } => {
+    ///         assert_eq!(literals, vec!["`a`..`z` (case insensitive)"])
+    ///     }
+    ///     _ => unreachable!()
+    /// };
+    /// ```
+    #[inline]
+    pub fn match_range_insensitive(
+        &mut self,
+        pos: Position<'i>,
+        range: Range<char>
+    ) -> Result<Position<'i>, Position<'i>> {
+        let result = pos.match_range_insensitive(range.clone());
+
+        if let Err(ref pos) = result {
+            self.track_literal(pos.pos(), || {
+                format!("{} (case insensitive)", format_range(&range))
+            });
+        }
+
+        result
+    }
+
+    /// Matches the `Span` at the top of the stack.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stack is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// let input = "aa";
+    /// pest::state::<(), _>(input, |state, pos| {
+    ///     let end = pos.clone().match_string("a").unwrap();
+    ///     state.stack.push(pos.span(&end));
+    ///
+    ///     state.stack_peek(end)
+    /// }).unwrap();
+    /// ```
+    #[inline]
+    pub fn stack_peek(&self, pos: Position<'i>) -> Result<Position<'i>, Position<'i>> {
+        let string = self.stack
+            .peek()
+            .expect("peek was called on empty stack")
+            .as_str();
+
+        pos.match_string(string)
+    }
+
+    /// Matches the `Span` at the top of the stack and pops it if it matched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stack is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// let input = "aa";
+    /// pest::state::<(), _>(input, |state, pos| {
+    ///     let end = pos.clone().match_string("a").unwrap();
+    ///     state.stack.push(pos.span(&end));
+    ///
+    ///     let result = state.stack_pop(end);
+    ///     assert!(state.stack.is_empty());
+    ///
+    ///     result
+    /// }).unwrap();
+    /// ```
+    #[inline]
+    pub fn stack_pop(&mut self, pos: Position<'i>) -> Result<Position<'i>, Position<'i>> {
+        let string = self.stack
+            .peek()
+            .expect("pop was called on empty stack")
+            .as_str();
+        let result = pos.match_string(string);
+
+        if result.is_ok() {
+            self.stack.pop();
+        }
+
+        result
+    }
+
+    /// Pops the top of the stack without matching it. Fails if the stack is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// let input = "a";
+    /// pest::state::<(), _>(input, |state, pos| {
+    ///     assert!(state.stack_drop(pos.clone()).is_err());
+    ///
+    ///     let end = pos.clone().match_string("a").unwrap();
+    ///     state.stack.push(pos.span(&end));
+    ///
+    ///     state.stack_drop(end)
+    /// }).unwrap();
+    /// ```
+    #[inline]
+    pub fn stack_drop(&mut self, pos: Position<'i>) -> Result<Position<'i>, Position<'i>> {
+        match self.stack.pop() {
+            Some(_) => Ok(pos),
+            None => Err(pos)
+        }
+    }
+
+    /// Matches every `Span` of the stack, from the top to the bottom. Matches nothing if the stack
+    /// is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// let input = "ab";
+    /// pest::state::<(), _>(input, |state, pos| {
+    ///     let middle = pos.clone().match_string("a").unwrap();
+    ///     let end = middle.clone().match_string("b").unwrap();
+    ///     // the stack is ["b", "a"] with "a" at the top
+    ///     state.stack.push(middle.span(&end));
+    ///     state.stack.push(pos.span(&middle));
+    ///
+    ///     state.stack_peek_all(pos)
+    /// }).unwrap();
+    /// ```
+    #[inline]
+    pub fn stack_peek_all(&self, pos: Position<'i>) -> Result<Position<'i>, Position<'i>> {
+        let spans = self.stack.as_slice();
+
+        pos.sequence(|mut pos| {
+            for span in spans.iter().rev() {
+                pos = pos.match_string(span.as_str())?;
+            }
+
+            Ok(pos)
+        })
+    }
+
+    /// Matches every `Span` of the stack, from the top to the bottom, and empties the stack if
+    /// they matched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// let input = "aa";
+    /// pest::state::<(), _>(input, |state, pos| {
+    ///     let end = pos.clone().match_string("a").unwrap();
+    ///     state.stack.push(pos.span(&end));
+    ///
+    ///     let result = state.stack_pop_all(end);
+    ///     assert!(state.stack.is_empty());
+    ///
+    ///     result
+    /// }).unwrap();
+    /// ```
+    #[inline]
+    pub fn stack_pop_all(&mut self, pos: Position<'i>) -> Result<Position<'i>, Position<'i>> {
+        let result = self.stack_peek_all(pos);
+
+        if result.is_ok() {
+            while self.stack.pop().is_some() {}
+        }
+
+        result
+    }
+
+    /// Matches the `Span`s of the stack between the indices `start` and `end`, from the bottom to
+    /// the top. Indices count from the bottom of the stack, or from its top if they are negative,
+    /// and a missing `end` stands for the top of the stack. Fails if the indices are out of
+    /// bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use pest;
+    /// let input = "abb";
+    /// pest::state::<(), _>(input, |state, pos| {
+    ///     let middle = pos.clone().match_string("a").unwrap();
+    ///     let end = middle.clone().match_string("b").unwrap();
+    ///     state.stack.push(pos.span(&middle));
+    ///     state.stack.push(middle.span(&end));
+    ///
+    ///     // matches "b", the slice [1..] of ["a", "b"]
+    ///     state.stack_peek_slice(end, 1, None)
+    /// }).unwrap();
+    /// ```
+    #[inline]
+    pub fn stack_peek_slice(
+        &self,
+        pos: Position<'i>,
+        start: i32,
+        end: Option<i32>
+    ) -> Result<Position<'i>, Position<'i>> {
+        let len = self.stack.len();
+        let start = self.stack_index(start);
+        let end = match end {
+            Some(end) => self.stack_index(end),
+            None => Some(len)
+        };
+
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end => {
+                let spans = &self.stack.as_slice()[start..end];
+
+                pos.sequence(|mut pos| {
+                    for span in spans {
+                        pos = pos.match_string(span.as_str())?;
+                    }
+
+                    Ok(pos)
+                })
+            }
+            _ => Err(pos)
+        }
+    }
+
+    // Converts a possibly negative index into the stack into an absolute one.
+    fn stack_index(&self, index: i32) -> Option<usize> {
+        let len = self.stack.len() as i64;
+        let index = if index < 0 {
+            len + i64::from(index)
+        } else {
+            i64::from(index)
+        };
+
+        if index >= 0 && index <= len {
+            Some(index as usize)
+        } else {
+            None
+        }
+    }
+}
+
+// Escapes control characters, which would otherwise break the formatting of the error message.
+fn escape(string: &str) -> String {
+    string
+        .chars()
+        .map(|c| {
+            if c.is_control() {
+                c.escape_default().collect()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+fn format_range(range: &Range<char>) -> String {
+    format!(
+        "`{}`..`{}`",
+        escape(&range.start.to_string()),
+        escape(&range.end.to_string())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[allow(non_camel_case_types)]
+    #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    enum Rule {
+        a,
+        b,
+        c,
+        error
+    }
+
+    fn a<'i>(
+        pos: Position<'i>,
+        state: &mut ParserState<'i, Rule>,
+        runs: &Cell<usize>
+    ) -> Result<Position<'i>, Position<'i>> {
+        state.rule(Rule::a, pos, |state, pos| {
+            runs.set(runs.get() + 1);
+
+            state.rule(Rule::b, pos, |_, pos| pos.match_string("a"))
+        })
+    }
+
+    fn count_runs(is_memoized: bool) -> (usize, usize) {
+        let runs = Cell::new(0);
+        let input = "ab";
+
+        let pairs = state(input, |state, pos| {
+            state.memoize(is_memoized, |state| {
+                state
+                    .sequence(|state| {
+                        pos.clone().sequence(|pos| {
+                            a(pos, state, &runs).and_then(|pos| pos.match_string("c"))
+                        })
+                    })
+                    .or_else(|pos| {
+                        state.sequence(|state| {
+                            pos.sequence(|pos| {
+                                a(pos, state, &runs).and_then(|pos| pos.match_string("b"))
+                            })
+                        })
+                    })
+            })
+        }).unwrap();
+
+        (runs.get(), pairs.flatten().count())
+    }
+
+    #[test]
+    fn memoized_rule_runs_once() {
+        assert_eq!(count_runs(false), (2, 2));
+        assert_eq!(count_runs(true), (1, 2));
+    }
+
+    #[test]
+    fn memoized_failure() {
+        let runs = Cell::new(0);
+        let input = "b";
+
+        let error = state(input, |state, pos| {
+            state.memoize(true, |state| {
+                a(pos, state, &runs).or_else(|pos| a(pos, state, &runs))
+            })
+        }).unwrap_err();
+
+        assert_eq!(runs.get(), 1);
+        assert_eq!(
+            error,
+            Error::ParsingError {
+                positives: vec![Rule::b],
+                negatives: vec![],
+                literals: vec![],
+                rule_stack: vec![],
+                pos: Position::from_start(input)
+            }
+        );
+    }
+
+    fn memoized_attempts(is_memoized: bool) -> Error<'static, Rule> {
+        let input = "y";
+
+        state(input, |state, pos| {
+            state.memoize(is_memoized, |state| {
+                state
+                    .rule(Rule::b, pos, |state, pos| {
+                        state.rule(Rule::a, pos, |_, pos| pos.match_string("x"))
+                    })
+                    .or_else(|pos| {
+                        state.rule(Rule::c, pos, |state, pos| {
+                            state.rule(Rule::a, pos, |_, pos| pos.match_string("x"))
+                        })
+                    })
+            })
+        }).unwrap_err()
+    }
+
+    #[test]
+    fn memoized_attempts_are_replayed() {
+        let error = memoized_attempts(true);
+
+        assert_eq!(error, memoized_attempts(false));
+        assert_eq!(
+            error,
+            Error::ParsingError {
+                positives: vec![Rule::a],
+                negatives: vec![],
+                literals: vec![],
+                rule_stack: vec![],
+                pos: Position::from_start("y")
+            }
+        );
+    }
+
+    fn list<'i>(
+        pos: Position<'i>,
+        state: &mut ParserState<'i, Rule>
+    ) -> Result<Position<'i>, Position<'i>> {
+        state.rule(Rule::a, pos, |state, pos| {
+            state.sequence(move |state| {
+                pos.sequence(|pos| {
+                    state.match_string(pos, "[").and_then(|pos| {
+                        state
+                            .match_string(pos, ",")
+                            .or_else(|pos| state.match_string(pos, "]"))
+                    })
+                })
+            })
+        })
+    }
+
+    #[test]
+    fn literal_attempts() {
+        let input = "[}";
+        let error = state(input, |state, pos| list(pos, state)).unwrap_err();
+
+        assert_eq!(
+            error,
+            Error::ParsingError {
+                positives: vec![],
+                negatives: vec![],
+                literals: vec!["`,`".to_owned(), "`]`".to_owned()],
+                rule_stack: vec![],
+                pos: unsafe { position::new(input, 1) }
+            }
+        );
+        assert_eq!(format!("{}", error).lines().last(), Some("  = expected `,` or `]`"));
+    }
+
+    #[test]
+    fn literal_attempts_replaced_by_rule() {
+        let input = "}";
+        let error = state(input, |state, pos| list(pos, state)).unwrap_err();
+
+        assert_eq!(
+            error,
+            Error::ParsingError {
+                positives: vec![Rule::a],
+                negatives: vec![],
+                literals: vec![],
+                rule_stack: vec![],
+                pos: Position::from_start(input)
+            }
+        );
+    }
+
+    fn nested_list<'i>(
+        pos: Position<'i>,
+        state: &mut ParserState<'i, Rule>
+    ) -> Result<Position<'i>, Position<'i>> {
+        state.track_rule_stack(true, move |state| {
+            state.rule(Rule::b, pos, |state, pos| list(pos, state))
+        })
+    }
+
+    #[test]
+    fn rule_stack() {
+        let input = "[}";
+        let error = state(input, |state, pos| nested_list(pos, state)).unwrap_err();
+
+        match error {
+            Error::ParsingError { ref rule_stack, .. } => assert_eq!(
+                *rule_stack,
+                vec![
+                    (Rule::b, Position::from_start(input)),
+                    (Rule::a, Position::from_start(input)),
+                ]
+            ),
+            _ => unreachable!()
+        };
+        assert!(format!("{}", error).ends_with(
+            "= expected `,` or `]`\n  = while parsing `a` starting at 1:1\n  = while parsing `b` \
+             starting at 1:1"
+        ));
+    }
+
+    #[test]
+    fn rule_stack_of_replaced_attempts() {
+        let input = "}";
+        let error = state(input, |state, pos| nested_list(pos, state)).unwrap_err();
+
+        assert_eq!(
+            error,
+            Error::ParsingError {
+                positives: vec![Rule::a],
+                negatives: vec![],
+                literals: vec![],
+                rule_stack: vec![(Rule::b, Position::from_start(input))],
+                pos: Position::from_start(input)
+            }
+        );
+    }
+
+    fn statements<'i>(
+        pos: Position<'i>,
+        state: &mut ParserState<'i, Rule>
+    ) -> Result<Position<'i>, Position<'i>> {
+        pos.repeat(|pos| {
+            state.sequence(move |state| {
+                pos.sequence(|pos| {
+                    state
+                        .recover(
+                            Rule::error,
+                            pos,
+                            |state, pos| state.rule(Rule::a, pos, |_, pos| pos.match_string("a")),
+                            |pos| pos.match_string(";")
+                        )
+                        .and_then(|pos| pos.match_string(";"))
+                })
+            })
+        }).and_then(|pos| pos.at_end())
+    }
+
+    #[test]
+    fn recover() {
+        let input = "a;bb;a;c;";
+        let (pairs, errors) = recovering_state(input, |state, pos| statements(pos, state));
+
+        assert_eq!(
+            format!("{}", pairs),
+            "[a(0, 1), error(2, 4), a(5, 6), error(7, 8)]"
+        );
+        assert_eq!(
+            errors,
+            vec![
+                Error::ParsingError {
+                    positives: vec![Rule::a],
+                    negatives: vec![],
+                    literals: vec![],
+                    rule_stack: vec![],
+                    pos: unsafe { position::new(input, 2) }
+                },
+                Error::ParsingError {
+                    positives: vec![Rule::a],
+                    negatives: vec![],
+                    literals: vec![],
+                    rule_stack: vec![],
+                    pos: unsafe { position::new(input, 7) }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn recover_state_fails_with_first_error() {
+        let input = "b;c;";
+        let error = state(input, |state, pos| statements(pos, state)).unwrap_err();
+
+        assert_eq!(
+            error,
+            Error::ParsingError {
+                positives: vec![Rule::a],
+                negatives: vec![],
+                literals: vec![],
+                rule_stack: vec![],
+                pos: Position::from_start(input)
+            }
+        );
+    }
+
+    #[test]
+    fn recover_backtracked() {
+        let input = "b";
+        let (pairs, errors) = recovering_state(input, |state, pos| {
+            state
+                .sequence(|state| {
+                    pos.clone().sequence(|pos| {
+                        state
+                            .recover(
+                                Rule::error,
+                                pos,
+                                |state, pos| {
+                                    state.rule(Rule::a, pos, |_, pos| pos.match_string("a"))
+                                },
+                                |pos| pos.at_end()
+                            )
+                            .and_then(|pos| pos.match_string("c"))
+                    })
+                })
+                .or_else(|_| state.rule(Rule::b, pos, |_, pos| pos.match_string("b")))
+        });
+
+        assert_eq!(format!("{}", pairs), "[b(0, 1)]");
+        assert!(errors.is_empty());
+    }
+
+    fn backtracked_error(recovering: bool) -> Error<'static, Rule> {
+        let input = "ab;";
+
+        state(input, |state, pos| {
+            state
+                .sequence(|state| {
+                    pos.clone().sequence(|pos| {
+                        let a = |state: &mut ParserState<'static, Rule>, pos| {
+                            state.rule(Rule::a, pos, |state, pos| {
+                                state
+                                    .match_string(pos, "a")
+                                    .and_then(|pos| state.match_string(pos, "c"))
+                            })
+                        };
+
+                        if recovering {
+                            state.recover(Rule::error, pos, a, |pos| pos.match_string(";"))
+                        } else {
+                            a(state, pos)
+                        }.and_then(|pos| pos.match_string("!"))
+                    })
+                })
+                .or_else(|_| pos.match_string("d"))
+        }).unwrap_err()
+    }
+
+    #[test]
+    fn recover_backtracked_error() {
+        let error = backtracked_error(true);
+
+        assert_eq!(error, backtracked_error(false));
+        assert_eq!(
+            error,
+            Error::ParsingError {
+                positives: vec![],
+                negatives: vec![],
+                literals: vec!["`c`".to_owned()],
+                rule_stack: vec![],
+                pos: unsafe { position::new("ab;", 1) }
+            }
+        );
+    }
+
+    #[test]
+    fn stack_restored_on_failure() {
+        let input = "ab";
+        let pairs = state::<Rule, _>(input, |state, pos| {
+            state
+                .sequence(|state| {
+                    pos.clone().sequence(|pos| {
+                        let start = pos.clone();
+
+                        pos.match_string("a")
+                            .map(|end| {
+                                state.stack.push(start.span(&end));
+                                end
+                            })
+                            .and_then(|pos| pos.match_string("c"))
+                    })
+                })
+                .or_else(|_| {
+                    assert!(state.stack.is_empty());
+
+                    state.rule(Rule::b, pos, |state, pos| {
+                        state.stack.push(pos.clone().span(&pos));
+                        pos.match_string("b")
+                    })
+                })
+                .or_else(|pos| {
+                    assert!(state.stack.is_empty());
+
+                    pos.match_string("ab")
+                })
+        });
+
+        assert!(pairs.is_ok());
+    }
+
+    #[test]
+    fn stack_restored_after_lookahead() {
+        let input = "a";
+        let start = Position::from_start(input);
+        let end = start.clone().match_string("a").unwrap();
+
+        state::<Rule, _>(input, |state, pos| {
+            state.stack.push(start.span(&end));
+
+            let result = state.lookahead(true, |state| {
+                state.stack.pop();
+                Ok(pos)
+            });
+
+            assert_eq!(state.stack.len(), 1);
+
+            result
+        }).unwrap();
+    }
+
+    #[test]
+    fn tag_outermost_pairs() {
+        let runs = Cell::new(0);
+        let input = "aa";
+
+        let pairs = state(input, |state, pos| {
+            state.tag_node("lhs", move |state| {
+                a(pos, state, &runs).and_then(|pos| a(pos, state, &runs))
+            })
+        }).unwrap();
+
+        assert_eq!(
+            format!("{}", pairs),
+            "[#lhs = a(0, 1, [b(0, 1)]), #lhs = a(1, 2, [b(1, 2)])]"
+        );
+        assert_eq!(pairs.clone().find_tagged("lhs").count(), 2);
+        assert_eq!(pairs.find_tagged("rhs").count(), 0);
+    }
+
+    #[test]
+    fn tag_memoized() {
+        let runs = Cell::new(0);
+        let input = "a";
+
+        let pairs = state(input, |state, pos| {
+            state.memoize(true, |state| {
+                state
+                    .sequence(|state| {
+                        state
+                            .tag_node("lhs", |state| a(pos.clone(), state, &runs))
+                            .and_then(|pos| pos.match_string("b"))
+                    })
+                    .or_else(|_| a(pos, state, &runs))
+            })
+        }).unwrap();
+
+        assert_eq!(runs.get(), 1);
+        assert_eq!(format!("{}", pairs), "[a(0, 1, [b(0, 1)])]");
+    }
 }
@@ -13,7 +13,10 @@ use std::hash::{Hash, Hasher};
 use std::ops::Range;
 use std::ptr;
 
+use encoding::ColumnEncoding;
+use line_index::LineIndex;
 use span;
+use unicode;
 
 /// A `struct` containing a position that is tied to a `&str` which provides useful methods to
 /// manually parse it. This leads to an API largely based on the standard `Result`.
@@ -26,6 +29,11 @@ pub unsafe fn new(input: &str, pos: usize) -> Position {
     Position { input, pos }
 }
 
+#[inline]
+pub fn input<'i>(pos: &Position<'i>) -> &'i str {
+    pos.input
+}
+
 impl<'i> Position<'i> {
     /// Creates starting `Position` from an `&str`.
     ///
@@ -143,6 +151,81 @@ impl<'i> Position<'i> {
         line_col
     }
 
+    /// Returns the line - and column number pair of the current `Position`, with the column counted
+    /// in the units of `encoding`. When looking up many `Position`s of the same input, build a
+    /// [`LineIndex`](struct.LineIndex.html) once and use [`line_col_in`](#method.line_col_in)
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{ColumnEncoding, Position};
+    /// let input = "\n𝄞a";
+    /// let start = Position::from_start(input);
+    /// let pos = start.match_string("\n𝄞").unwrap();
+    ///
+    /// assert_eq!(pos.line_col_with(ColumnEncoding::Bytes), (2, 5));
+    /// assert_eq!(pos.line_col_with(ColumnEncoding::Chars), (2, 2));
+    /// assert_eq!(pos.line_col_with(ColumnEncoding::Utf16), (2, 3));
+    /// ```
+    #[inline]
+    pub fn line_col_with(&self, encoding: ColumnEncoding) -> (usize, usize) {
+        if self.pos > self.input.len() {
+            panic!("position out of bounds");
+        }
+
+        // Lines after the `Position` are never looked up, so only the input before it is indexed.
+        LineIndex::new(&self.input[..self.pos])
+            .with_encoding(encoding)
+            .line_col(self.pos)
+    }
+
+    /// Returns the line - and column number pair of the current `Position`, looked up in an
+    /// already built `index` of its input. The column is counted in the units of the `index`'s
+    /// encoding. Unlike [`line_col`](#method.line_col), this does not scan the input up to the
+    /// `Position`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `index` was built from an input shorter than the `Position`'s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{LineIndex, Position};
+    /// let input = "\na";
+    /// let index = LineIndex::new(input);
+    /// let pos = Position::from_start(input).match_string("\na").unwrap();
+    ///
+    /// assert_eq!(pos.line_col_in(&index), (2, 2));
+    /// ```
+    #[inline]
+    pub fn line_col_in(&self, index: &LineIndex) -> (usize, usize) {
+        index.line_col(self.pos)
+    }
+
+    /// Returns the actual line of the current `Position`, looked up in an already built `index`
+    /// of its input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `index` was built from an input shorter than the `Position`'s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{LineIndex, Position};
+    /// let input = "\na";
+    /// let index = LineIndex::new(input);
+    /// let pos = Position::from_start(input).match_string("\na").unwrap();
+    ///
+    /// assert_eq!(pos.line_of_in(&index), "a");
+    /// ```
+    #[inline]
+    pub fn line_of_in(&self, index: &LineIndex<'i>) -> &'i str {
+        index.line_of(self.pos)
+    }
+
     /// Returns the actual line of the current `Position`.
     ///
     /// # Examples
@@ -331,6 +414,9 @@ impl<'i> Position<'i> {
     /// Case-insensitively matches `string` from the `Position` and returns `Ok` with the new
     /// `Position` if a match was made or `Err` with the current `Position` otherwise.
     ///
+    /// Both `string` and the input are compared by their Unicode full case folding, so the
+    /// matched input can be longer or shorter than `string`, e.g. `"STRASSE"` matches `"straße"`.
+    ///
     /// # Examples
     ///
     /// ```
@@ -340,28 +426,38 @@ impl<'i> Position<'i> {
     ///
     /// assert_eq!(start.clone().match_insensitive("AB").unwrap().pos(), 2);
     /// assert_eq!(start.clone().match_insensitive("AC"), Err(start));
+    ///
+    /// let input = "STRASSE";
+    /// let start = Position::from_start(input);
+    ///
+    /// assert_eq!(start.clone().match_insensitive("straße").unwrap().pos(), 7);
     /// ```
     #[inline]
-    pub fn match_insensitive(mut self, string: &'i str) -> Result<Position<'i>, Position<'i>> {
-        // Matching is safe since, even if the string does not fall on UTF-8 borders, that
-        // particular slice is only used for comparison which will be handled correctly.
-
-        let matched = unsafe {
-            let slice = self.input.slice_unchecked(self.pos, self.input.len());
-
-            if slice.is_char_boundary(string.len()) {
-                let slice = slice.slice_unchecked(0, string.len());
-                slice.eq_ignore_ascii_case(string)
+    pub fn match_insensitive(mut self, string: &str) -> Result<Position<'i>, Position<'i>> {
+        let len = {
+            let slice = &self.input[self.pos..];
+
+            // ASCII only ever folds to ASCII of the same length.
+            if string.is_ascii()
+                && slice.is_char_boundary(string.len())
+                && slice[..string.len()].is_ascii()
+            {
+                if slice[..string.len()].eq_ignore_ascii_case(string) {
+                    Some(string.len())
+                } else {
+                    None
+                }
             } else {
-                false
+                insensitive_len(slice, string)
             }
         };
 
-        if matched {
-            self.pos += string.len();
-            Ok(self)
-        } else {
-            Err(self)
+        match len {
+            Some(len) => {
+                self.pos += len;
+                Ok(self)
+            }
+            None => Err(self)
         }
     }
 
@@ -404,6 +500,52 @@ impl<'i> Position<'i> {
         }
     }
 
+    /// Case-insensitively matches `char` `range` from the `Position` and returns `Ok` with the
+    /// new `Position` if a match was made or `Err` with the current `Position` otherwise. A `char`
+    /// matches if it has the same Unicode case folding as any `char` in `range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Position;
+    /// let input = "Bé";
+    /// let start = Position::from_start(input);
+    ///
+    /// assert_eq!(start.clone().match_range_insensitive('a'..'z').unwrap().pos(), 1);
+    /// assert_eq!(start.clone().match_range_insensitive('0'..'9'), Err(start));
+    /// ```
+    #[inline]
+    pub fn match_range_insensitive(self, range: Range<char>) -> Result<Position<'i>, Position<'i>> {
+        self.match_char_by(|c| unicode::in_range_insensitive(c, range.start, range.end))
+    }
+
+    /// Matches the next `char` if `f` returns `true` for it and returns `Ok` with the new
+    /// `Position` if a match was made or `Err` with the current `Position` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Position;
+    /// let input = "a1";
+    /// let start = Position::from_start(input);
+    ///
+    /// assert_eq!(start.clone().match_char_by(|c| c.is_alphabetic()).unwrap().pos(), 1);
+    /// assert_eq!(start.clone().match_char_by(|c| c.is_numeric()), Err(start));
+    /// ```
+    #[inline]
+    pub fn match_char_by<F>(mut self, f: F) -> Result<Position<'i>, Position<'i>>
+    where
+        F: FnOnce(char) -> bool
+    {
+        let len = match self.input[self.pos..].chars().next() {
+            Some(c) if f(c) => c.len_utf8(),
+            _ => return Err(self)
+        };
+
+        self.pos += len;
+        Ok(self)
+    }
+
     /// Starts a sequence of transformations provided by `f` from the `Position`. It returns the
     /// same `Result` returned by `f` in the case of an `Ok` or `Err` with the current `Position`
     /// otherwise.
@@ -643,6 +785,33 @@ impl<'i> Hash for Position<'i> {
     }
 }
 
+// Returns the length of the start of `input` whose case folding is the one of `string`, folding
+// one `char` of `input` at a time so that folds spanning several `char`s must match in full.
+fn insensitive_len(input: &str, string: &str) -> Option<usize> {
+    let mut expected = string.chars().flat_map(unicode::fold).peekable();
+    let mut len = 0;
+
+    for c in input.chars() {
+        if expected.peek().is_none() {
+            break;
+        }
+
+        for folded in unicode::fold(c) {
+            if expected.next() != Some(folded) {
+                return None;
+            }
+        }
+
+        len += c.len_utf8();
+    }
+
+    if expected.peek().is_none() {
+        Some(len)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -678,6 +847,17 @@ mod tests {
         assert_eq!(unsafe { new(input, 11) }.line_col(), (3, 3));
     }
 
+    #[test]
+    fn line_col_with_matches_line_col() {
+        let input = "a\rb\nc\r\nd嗨";
+
+        for pos in &[0, 1, 2, 3, 4, 5, 6, 7, 8, 11] {
+            let position = unsafe { new(input, *pos) };
+
+            assert_eq!(position.line_col_with(ColumnEncoding::Chars), position.line_col());
+        }
+    }
+
     #[test]
     fn line_of() {
         let input = "a\rb\nc\r\nd嗨";
@@ -752,6 +932,16 @@ mod tests {
         assert!(unsafe { new(input, 0) }.match_range('a'..'嗨').is_ok());
     }
 
+    #[test]
+    fn match_char_by() {
+        let input = "嗨b";
+
+        assert_eq!(unsafe { new(input, 0) }.match_char_by(|c| c == '嗨').unwrap().pos(), 3);
+        assert!(unsafe { new(input, 3) }.match_char_by(|c| c == 'b').is_ok());
+        assert!(!unsafe { new(input, 3) }.match_char_by(|c| c == 'a').is_ok());
+        assert!(!unsafe { new(input, 4) }.match_char_by(|_| true).is_ok());
+    }
+
     #[test]
     fn match_insensitive() {
         let input = "AsdASdF";
@@ -759,4 +949,28 @@ mod tests {
         assert!(unsafe { new(input, 0) }.match_insensitive("asd").is_ok());
         assert!(unsafe { new(input, 3) }.match_insensitive("asdf").is_ok());
     }
+
+    #[test]
+    fn match_insensitive_unicode() {
+        let input = "STRASSE ΣΊΣΥΦΟΣ \u{212a}";
+
+        assert_eq!(unsafe { new(input, 0) }.match_insensitive("straße").unwrap().pos(), 7);
+        assert_eq!(unsafe { new(input, 8) }.match_insensitive("σίσυφος").unwrap().pos(), 22);
+        assert_eq!(unsafe { new(input, 23) }.match_insensitive("k").unwrap().pos(), 26);
+        assert!(unsafe { new(input, 0) }.match_insensitive("STRASSEX").is_err());
+        assert!(unsafe { new("ß", 0) }.match_insensitive("s").is_err());
+        assert!(unsafe { new("ß", 0) }.match_insensitive("SS").is_ok());
+        assert!(unsafe { new("é", 0) }.match_insensitive("É").is_ok());
+    }
+
+    #[test]
+    fn match_range_insensitive() {
+        assert!(unsafe { new("B", 0) }.match_range_insensitive('a'..'c').is_ok());
+        assert!(unsafe { new("b", 0) }.match_range_insensitive('A'..'C').is_ok());
+        assert!(unsafe { new("ς", 0) }.match_range_insensitive('Σ'..'Σ').is_ok());
+        assert!(unsafe { new("\u{212a}", 0) }.match_range_insensitive('k'..'k').is_ok());
+        assert!(unsafe { new("k", 0) }.match_range_insensitive('\u{212a}'..'\u{212a}').is_ok());
+        assert!(unsafe { new("ẞ", 0) }.match_range_insensitive('ß'..'ß').is_ok());
+        assert!(unsafe { new("d", 0) }.match_range_insensitive('A'..'C').is_err());
+    }
 }
@@ -0,0 +1,780 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use diagnostic::Diagnostic;
+use encoding::ColumnEncoding;
+use error::{self, Error};
+use line_index::LineIndex;
+use position::{self, Position};
+use span::Span;
+
+/// A `struct` which renders `Error`s the way `Display` does, with the annotated lines of the
+/// input followed by the error's message.
+///
+/// Errors with a `Span` are underlined on every line they cover. Lines can be surrounded by
+/// [`context_lines`](#method.with_context_lines) lines of input before and after them, and
+/// lines wider than [`max_width`](#method.with_max_width) columns are trimmed around the
+/// error, with `...` marking the parts that were left out.
+///
+/// [`Diagnostic`](struct.Diagnostic.html)s are rendered in the same layout by
+/// [`render_diagnostic`](#method.render_diagnostic).
+///
+/// # Examples
+///
+/// ```
+/// # use pest::{Error, Position, Renderer};
+/// # #[allow(non_camel_case_types)]
+/// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+/// # enum Rule {
+/// #     a
+/// # }
+/// let input = "a = 1\nb = [\n  2,\n]\nc = 3";
+/// let start = Position::from_start(input).skip(10).unwrap();
+/// let end = start.clone().skip(8).unwrap();
+/// let error: Error<Rule> = Error::CustomErrorSpan {
+///     message: "unexpected array".to_owned(),
+///     span: start.span(&end)
+/// };
+///
+/// assert_eq!(
+///     Renderer::new().with_context_lines(1).render(&error),
+///     [
+///         " --> 2:5",
+///         "  |",
+///         "1 | a = 1",
+///         "2 | b = [",
+///         "  |     ^",
+///         "3 |   2,",
+///         "  | ----",
+///         "4 | ]",
+///         "  | ^",
+///         "5 | c = 3",
+///         "  |",
+///         "  = unexpected array"
+///     ].join("\n")
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Renderer {
+    context_lines: usize,
+    max_width: usize
+}
+
+impl Renderer {
+    /// Creates a `Renderer` without context lines which trims lines wider than 100 columns. This
+    /// is the `Renderer` used by `Display`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{Error, Position, Renderer};
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// # enum Rule {
+    /// #     a
+    /// # }
+    /// let error: Error<Rule> = Error::CustomErrorPos {
+    ///     message: "unexpected a".to_owned(),
+    ///     pos: Position::from_start("a")
+    /// };
+    ///
+    /// assert_eq!(Renderer::new().render(&error), format!("{}", error));
+    /// ```
+    pub fn new() -> Renderer {
+        Renderer::default()
+    }
+
+    /// Sets the number of lines of input shown before and after the annotated lines, consuming
+    /// the `Renderer`.
+    #[inline]
+    pub fn with_context_lines(mut self, context_lines: usize) -> Renderer {
+        self.context_lines = context_lines;
+        self
+    }
+
+    /// Sets the width in columns above which lines are trimmed, consuming the `Renderer`. A
+    /// width of `0` never trims lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{Error, Position, Renderer};
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// # enum Rule {
+    /// #     a
+    /// # }
+    /// let input = "0123456789abcdefghij";
+    /// let error: Error<Rule> = Error::CustomErrorPos {
+    ///     message: "unexpected f".to_owned(),
+    ///     pos: Position::from_start(input).skip(15).unwrap()
+    /// };
+    ///
+    /// assert_eq!(
+    ///     Renderer::new().with_max_width(8).render(&error),
+    ///     [
+    ///         " --> 1:16",
+    ///         "  |",
+    ///         "1 | ...bcdefghi...",
+    ///         "  |        ^---",
+    ///         "  |",
+    ///         "  = unexpected f"
+    ///     ].join("\n")
+    /// );
+    /// ```
+    #[inline]
+    pub fn with_max_width(mut self, max_width: usize) -> Renderer {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Renders an `error`, counting lines and columns in the input it was found in.
+    pub fn render<'i, R: fmt::Debug>(&self, error: &Error<'i, R>) -> String {
+        let index = LineIndex::new(position::input(&start(error)));
+
+        self.render_with(error, &index)
+    }
+
+    /// Renders an `error` like [`render`](#method.render), but looks up lines in an already
+    /// built `LineIndex` of the input, just like
+    /// [`Error::format_with`](enum.Error.html#method.format_with).
+    ///
+    /// The reported column is counted in the `LineIndex`'s `ColumnEncoding`. With
+    /// `ColumnEncoding::Display`, underlines and trimming are also measured in display width,
+    /// while they are measured in `char`s otherwise.
+    pub fn render_with<'i, R: fmt::Debug>(
+        &self,
+        error: &Error<'i, R>,
+        index: &LineIndex<'i>
+    ) -> String {
+        self.render_annotated(error, None, None, &[], &[], index)
+    }
+
+    /// Renders a `diagnostic` like its `Error`, with its labeled spans underlined alongside the
+    /// `Error`'s, its path reported in front of the line and column, and its code and footers
+    /// reported around the message. Lines between distant
+    /// annotations are left out and replaced by `...`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{Diagnostic, Error, Position, Renderer};
+    /// # #[allow(non_camel_case_types)]
+    /// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    /// # enum Rule {
+    /// #     a
+    /// # }
+    /// let input = "[a]\nb = 1";
+    /// let start = Position::from_start(input);
+    /// let error: Error<Rule> = Error::CustomErrorPos {
+    ///     message: "expected table".to_owned(),
+    ///     pos: start.clone().skip(4).unwrap()
+    /// };
+    /// let diagnostic = Diagnostic::new(error)
+    ///     .with_label(start.clone().span(&start.skip(3).unwrap()), "inside of this table")
+    ///     .with_note("keys are defined after their table");
+    ///
+    /// assert_eq!(
+    ///     Renderer::new().render_diagnostic(&diagnostic),
+    ///     [
+    ///         " --> 2:1",
+    ///         "  |",
+    ///         "1 | [a]",
+    ///         "  | --- inside of this table",
+    ///         "2 | b = 1",
+    ///         "  | ^---",
+    ///         "  |",
+    ///         "  = expected table",
+    ///         "  = note: keys are defined after their table"
+    ///     ].join("\n")
+    /// );
+    /// ```
+    pub fn render_diagnostic<'i, R: fmt::Debug>(&self, diagnostic: &Diagnostic<'i, R>) -> String {
+        let index = LineIndex::new(position::input(&start(diagnostic.error())));
+
+        self.render_diagnostic_with(diagnostic, &index)
+    }
+
+    /// Renders a `diagnostic` like [`render_diagnostic`](#method.render_diagnostic), but looks
+    /// up lines in an already built `LineIndex` of the input, just like
+    /// [`render_with`](#method.render_with).
+    pub fn render_diagnostic_with<'i, R: fmt::Debug>(
+        &self,
+        diagnostic: &Diagnostic<'i, R>,
+        index: &LineIndex<'i>
+    ) -> String {
+        let footers: Vec<_> = diagnostic
+            .notes()
+            .iter()
+            .map(|note| format!("note: {}", note))
+            .chain(diagnostic.helps().iter().map(|help| format!("help: {}", help)))
+            .collect();
+
+        self.render_annotated(
+            diagnostic.error(),
+            diagnostic.path(),
+            diagnostic.code(),
+            diagnostic.labels(),
+            &footers,
+            index
+        )
+    }
+
+    fn render_annotated<'i, R: fmt::Debug>(
+        &self,
+        error: &Error<'i, R>,
+        path: Option<&str>,
+        code: Option<&str>,
+        labels: &[(Span<'i>, String)],
+        footers: &[String],
+        index: &LineIndex<'i>
+    ) -> String {
+        let (span, mark) = match *error {
+            Error::CustomErrorSpan { ref span, .. } => (span.clone(), Mark::Span),
+            _ => {
+                let pos = start(error);
+                (pos.span(&pos), Mark::Pos)
+            }
+        };
+        let encoding = match index.encoding() {
+            encoding @ ColumnEncoding::Display { .. } => encoding,
+            _ => ColumnEncoding::Chars
+        };
+
+        let mut annotations = vec![annotation(&span, mark, None, index)];
+        annotations.extend(
+            labels
+                .iter()
+                .map(|label| annotation(&label.0, Mark::Label, Some(&label.1), index))
+        );
+
+        let (first, col) = span.start_line_col_in(index);
+
+        let mut numbers = BTreeSet::new();
+        for annotation in &annotations {
+            let from = annotation.first.saturating_sub(self.context_lines).max(1);
+            let to = (annotation.last + self.context_lines).min(index.line_count());
+
+            numbers.extend(from..to + 1);
+        }
+
+        let spacing = " ".repeat(format!("{}", numbers.iter().last().unwrap()).len());
+
+        let mut result = match path {
+            Some(path) => format!("{}--> {}:{}:{}\n", spacing, path, first, col),
+            None => format!("{}--> {}:{}\n", spacing, first, col)
+        };
+        result.push_str(&format!("{} |\n", spacing));
+
+        let mut previous = None;
+        for number in numbers {
+            if let Some(previous) = previous {
+                if number > previous + 1 {
+                    result.push_str("...\n");
+                }
+            }
+            previous = Some(number);
+
+            let line = index.line(number).unwrap();
+            let line_start = index.line_start(number).unwrap();
+            let covering: Vec<_> = annotations
+                .iter()
+                .filter(|annotation| annotation.first <= number && number <= annotation.last)
+                .collect();
+
+            let underlines: Vec<_> = covering
+                .iter()
+                .map(|annotation| {
+                    let start = if number == annotation.first {
+                        annotation.start - line_start
+                    } else {
+                        0
+                    };
+                    let end = if number == annotation.last {
+                        annotation.end - line_start
+                    } else {
+                        line.len()
+                    };
+
+                    underline(
+                        line,
+                        start.min(line.len()),
+                        end.min(line.len()),
+                        (annotation.mark, number == annotation.first, number == annotation.last),
+                        encoding
+                    )
+                })
+                .collect();
+
+            // Lines are trimmed around the first annotation starting on them, or around the
+            // error's start otherwise.
+            let anchor = annotations
+                .iter()
+                .find(|annotation| annotation.first == number)
+                .unwrap_or(&annotations[0]);
+            let anchor_line = index.line(anchor.first).unwrap();
+            let anchor_start = index.line_start(anchor.first).unwrap();
+            let anchor = encoding.width(
+                &anchor_line[..(anchor.start - anchor_start).min(anchor_line.len())]
+            );
+
+            let (line, underlines) = self.trim(line, &underlines, anchor, encoding);
+
+            result.push_str(&format!("{:>2$} | {}\n", number, line, spacing.len()));
+
+            for (annotation, underline) in covering.iter().zip(underlines) {
+                let underline = match annotation.label {
+                    Some(label) if number == annotation.last => {
+                        format!("{} {}", underline, label)
+                    }
+                    _ => underline
+                };
+
+                if !underline.trim().is_empty() {
+                    result.push_str(&format!("{} | {}\n", spacing, underline));
+                }
+            }
+        }
+
+        result.push_str(&format!("{} |\n", spacing));
+
+        match code {
+            Some(code) => {
+                result.push_str(&format!("{} = error[{}]: {}", spacing, code, message(error)))
+            }
+            None => result.push_str(&format!("{} = {}", spacing, message(error)))
+        }
+
+        for note in notes(error, |pos| pos.line_col_in(index)) {
+            result.push_str(&format!("\n{} = {}", spacing, note));
+        }
+
+        for footer in footers {
+            result.push_str(&format!("\n{} = {}", spacing, footer));
+        }
+
+        result
+    }
+
+    // Trims a `line` wider than `max_width` to a window which starts `max_width / 2` columns
+    // before the `anchor` column, if possible. The `underlines` are trimmed to the same window.
+    fn trim(
+        &self,
+        line: &str,
+        underlines: &[String],
+        anchor: usize,
+        encoding: ColumnEncoding
+    ) -> (String, Vec<String>) {
+        let width = encoding.width(line);
+
+        if self.max_width == 0 || width <= self.max_width {
+            return (line.to_owned(), underlines.to_vec());
+        }
+
+        let left = anchor
+            .saturating_sub(self.max_width / 2)
+            .min(width - self.max_width);
+        let (start, end, from) = columns(line, left, left + self.max_width, encoding);
+        let to = from + encoding.width(&line[start..end]);
+
+        let mut trimmed = String::new();
+
+        if start > 0 {
+            trimmed.push_str("...");
+        }
+
+        trimmed.push_str(&line[start..end]);
+
+        if end < line.len() {
+            trimmed.push_str("...");
+        }
+
+        let underlines = underlines
+            .iter()
+            .map(|underline| {
+                let mut trimmed_underline = String::new();
+
+                if start > 0 {
+                    trimmed_underline.push_str("   ");
+                }
+
+                trimmed_underline.extend(underline.chars().skip(from).take(to - from));
+                trimmed_underline.trim_end().to_owned()
+            })
+            .collect();
+
+        (trimmed, underlines)
+    }
+}
+
+impl Default for Renderer {
+    fn default() -> Renderer {
+        Renderer {
+            context_lines: 0,
+            max_width: 100
+        }
+    }
+}
+
+fn start<'i, R>(error: &Error<'i, R>) -> Position<'i> {
+    match *error {
+        Error::ParsingError { ref pos, .. } | Error::CustomErrorPos { ref pos, .. } => pos.clone(),
+        Error::CustomErrorSpan { ref span, .. } => span.start_pos()
+    }
+}
+
+fn message<'i, R: fmt::Debug>(error: &Error<'i, R>) -> String {
+    match *error {
+        Error::ParsingError {
+            ref positives,
+            ref negatives,
+            ref literals,
+            ..
+        } => error::parsing_error_message(positives, negatives, literals, |r| format!("{:?}", r)),
+        Error::CustomErrorPos { ref message, .. } | Error::CustomErrorSpan { ref message, .. } => {
+            message.to_owned()
+        }
+    }
+}
+
+// Notes on the rules that were being parsed, innermost first.
+fn notes<'i, R: fmt::Debug, F>(error: &Error<'i, R>, line_col: F) -> Vec<String>
+where
+    F: Fn(&Position<'i>) -> (usize, usize)
+{
+    match *error {
+        Error::ParsingError { ref rule_stack, .. } => rule_stack
+            .iter()
+            .rev()
+            .map(|entry| {
+                let (line, col) = line_col(&entry.1);
+
+                format!("while parsing `{:?}` starting at {}:{}", entry.0, line, col)
+            })
+            .collect(),
+        _ => vec![]
+    }
+}
+
+// How an annotation is underlined.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Mark {
+    Pos,
+    Span,
+    Label
+}
+
+// A part of the input to underline, covering the lines `first..=last`.
+struct Annotation<'a> {
+    start: usize,
+    end: usize,
+    first: usize,
+    last: usize,
+    mark: Mark,
+    label: Option<&'a str>
+}
+
+fn annotation<'a>(
+    span: &Span,
+    mark: Mark,
+    label: Option<&'a str>,
+    index: &LineIndex
+) -> Annotation<'a> {
+    let first = span.start_line_col_in(index).0;
+    let mut last = span.end_line_col_in(index).0;
+
+    // A span ending right after a line ending does not cover the next line.
+    if last > first && index.line_start(last) == Some(span.end()) {
+        last -= 1;
+    }
+
+    Annotation {
+        start: span.start(),
+        end: span.end(),
+        first,
+        last,
+        mark,
+        label
+    }
+}
+
+// Underlines the bytes `start..end` of a `line`. Positions are marked with `^---`, while spans
+// are marked from their first `^` to their last `^`, across lines when `is_first` or `is_last`
+// are not set. Labeled spans are only marked with `-`.
+fn underline(
+    line: &str,
+    start: usize,
+    end: usize,
+    (mark, is_first, is_last): (Mark, bool, bool),
+    encoding: ColumnEncoding
+) -> String {
+    let mut underline = " ".repeat(encoding.width(&line[..start]));
+    let width = encoding.width(&line[start..end]);
+
+    match mark {
+        Mark::Pos => underline.push_str("^---"),
+        Mark::Label if is_first || is_last => underline.push_str(&"-".repeat(width.max(1))),
+        Mark::Label => underline.push_str(&"-".repeat(width)),
+        Mark::Span if is_first && is_last => {
+            if width > 1 {
+                underline.push('^');
+                underline.push_str(&"-".repeat(width - 2));
+                underline.push('^');
+            } else {
+                underline.push('^');
+            }
+        }
+        Mark::Span if is_first => {
+            underline.push('^');
+            underline.push_str(&"-".repeat(width.saturating_sub(1)));
+        }
+        Mark::Span if is_last => {
+            underline.push_str(&"-".repeat(width.saturating_sub(1)));
+            underline.push('^');
+        }
+        Mark::Span => underline.push_str(&"-".repeat(width))
+    }
+
+    underline
+}
+
+// Returns the bytes `start..end` of the `line` which fit between the columns `from` and `to`,
+// along with the column where `start` is.
+fn columns(line: &str, from: usize, to: usize, encoding: ColumnEncoding) -> (usize, usize, usize) {
+    let mut col = 0;
+    let mut start = None;
+
+    for (i, c) in line.char_indices() {
+        let width = match encoding {
+            ColumnEncoding::Display { tab_width } if c == '\t' && tab_width > 0 => {
+                tab_width - col % tab_width
+            }
+            _ => encoding.width(&line[i..i + c.len_utf8()])
+        };
+
+        if start.is_none() && col >= from {
+            start = Some((i, col));
+        }
+
+        if col + width > to {
+            let (start, start_col) = start.unwrap_or((i, col));
+
+            return (start, i, start_col);
+        }
+
+        col += width;
+    }
+
+    let (start, start_col) = start.unwrap_or((line.len(), col));
+
+    (start, line.len(), start_col)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::position;
+
+    fn span_error<'i>(input: &'i str, start: usize, end: usize) -> Error<'i, ()> {
+        let start = unsafe { position::new(input, start) };
+        let end = unsafe { position::new(input, end) };
+
+        Error::CustomErrorSpan {
+            message: "error".to_owned(),
+            span: start.span(&end)
+        }
+    }
+
+    #[test]
+    fn span_ending_with_line() {
+        let error = span_error("ab\ncd\n", 3, 6);
+
+        assert_eq!(
+            Renderer::new().render(&error),
+            vec![" --> 2:1", "  |", "2 | cd", "  | ^^", "  |", "  = error"].join("\n")
+        );
+    }
+
+    #[test]
+    fn span_past_line() {
+        let error = span_error("ab\r\ncd", 1, 4);
+
+        assert_eq!(
+            Renderer::new().render(&error),
+            vec![" --> 1:2", "  |", "1 | ab", "  |  ^", "  |", "  = error"].join("\n")
+        );
+    }
+
+    #[test]
+    fn multi_line_span_over_empty_line() {
+        let error = span_error("ab\n\ncd", 1, 5);
+
+        assert_eq!(
+            Renderer::new().render(&error),
+            vec![
+                " --> 1:2",
+                "  |",
+                "1 | ab",
+                "  |  ^",
+                "2 | ",
+                "3 | cd",
+                "  | ^",
+                "  |",
+                "  = error",
+            ].join("\n")
+        );
+    }
+
+    #[test]
+    fn gutter_width() {
+        let input = "a\n".repeat(9) + "b\nc";
+        let error = span_error(&input, 18, 19);
+
+        assert_eq!(
+            Renderer::new().with_context_lines(1).render(&error),
+            vec![
+                "  --> 10:1",
+                "   |",
+                " 9 | a",
+                "10 | b",
+                "   | ^",
+                "11 | c",
+                "   |",
+                "   = error",
+            ].join("\n")
+        );
+    }
+
+    #[test]
+    fn context_at_input_bounds() {
+        let error = span_error("ab", 0, 1);
+
+        assert_eq!(
+            Renderer::new().with_context_lines(3).render(&error),
+            vec![" --> 1:1", "  |", "1 | ab", "  | ^", "  |", "  = error"].join("\n")
+        );
+    }
+
+    #[test]
+    fn trim_start_of_line() {
+        let error = span_error("0123456789", 1, 3);
+
+        assert_eq!(
+            Renderer::new().with_max_width(4).render(&error),
+            vec![" --> 1:2", "  |", "1 | 0123...", "  |  ^^", "  |", "  = error"].join("\n")
+        );
+    }
+
+    #[test]
+    fn trim_end_of_line() {
+        let error = span_error("0123456789", 8, 10);
+
+        assert_eq!(
+            Renderer::new().with_max_width(4).render(&error),
+            vec![" --> 1:9", "  |", "1 | ...6789", "  |      ^^", "  |", "  = error"].join("\n")
+        );
+    }
+
+    #[test]
+    fn trim_wide_chars() {
+        let input = "嗨嗨嗨嗨ab";
+        let index = LineIndex::new(input).with_encoding(ColumnEncoding::Display { tab_width: 4 });
+        let error = span_error(input, 12, 13);
+
+        assert_eq!(
+            Renderer::new().with_max_width(5).render_with(&error, &index),
+            vec![" --> 1:9", "  |", "1 | ...嗨ab", "  |      ^", "  |", "  = error"].join("\n")
+        );
+    }
+
+    fn label<'i>(input: &'i str, start: usize, end: usize) -> Span<'i> {
+        let start = unsafe { position::new(input, start) };
+        let end = unsafe { position::new(input, end) };
+
+        start.span(&end)
+    }
+
+    #[test]
+    fn diagnostic_label_on_error_line() {
+        let input = "a = [1, \"b\"]";
+        let diagnostic = Diagnostic::new(span_error(input, 8, 11))
+            .with_code("E002")
+            .with_label(label(input, 4, 12), "in this array");
+
+        assert_eq!(
+            Renderer::new().render_diagnostic(&diagnostic),
+            vec![
+                " --> 1:9",
+                "  |",
+                "1 | a = [1, \"b\"]",
+                "  |         ^-^",
+                "  |     -------- in this array",
+                "  |",
+                "  = error[E002]: error",
+            ].join("\n")
+        );
+    }
+
+    #[test]
+    fn diagnostic_multi_line_label() {
+        let input = "[\n  1\n]\nx";
+        let diagnostic = Diagnostic::new(span_error(input, 8, 9))
+            .with_label(label(input, 0, 7), "array")
+            .with_note("first")
+            .with_help("second")
+            .with_note("third");
+
+        assert_eq!(
+            Renderer::new().render_diagnostic(&diagnostic),
+            vec![
+                " --> 4:1",
+                "  |",
+                "1 | [",
+                "  | -",
+                "2 |   1",
+                "  | ---",
+                "3 | ]",
+                "  | - array",
+                "4 | x",
+                "  | ^",
+                "  |",
+                "  = error",
+                "  = note: first",
+                "  = note: third",
+                "  = help: second",
+            ].join("\n")
+        );
+    }
+
+    #[test]
+    fn diagnostic_context_joins_lines() {
+        let input = "a\nb\nc\nd";
+        let diagnostic =
+            Diagnostic::new(span_error(input, 6, 7)).with_label(label(input, 0, 1), "a");
+
+        assert_eq!(
+            Renderer::new().with_context_lines(1).render_diagnostic(&diagnostic),
+            vec![
+                " --> 4:1",
+                "  |",
+                "1 | a",
+                "  | - a",
+                "2 | b",
+                "3 | c",
+                "4 | d",
+                "  | ^",
+                "  |",
+                "  = error",
+            ].join("\n")
+        );
+    }
+}
@@ -0,0 +1,232 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use RuleType;
+use diagnostic::Diagnostic;
+use error::Error;
+use line_index::LineIndex;
+use position::{self, Position};
+use span::Span;
+
+/// A `struct` which identifies a file added to a [`SourceMap`](struct.SourceMap.html).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FileId(usize);
+
+/// A `struct` which maps files to the inputs they were read into. Useful when parsing many
+/// files, in order to find out which file a `Position`, `Span` or `Error` came from and to
+/// report errors as `--> path:line:col`.
+///
+/// Files are told apart by the `&str` they were added with rather than its contents, so a
+/// `Position` is only found in a file if it points into that very `&str`. Every file keeps a
+/// `LineIndex` of its input, so formatting many errors does not rescan the files.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::{Error, Position, SourceMap};
+/// # #[allow(non_camel_case_types)]
+/// # #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+/// # enum Rule {
+/// #     a
+/// # }
+/// let main = "[server]\nport = 80".to_owned();
+/// let extra = "[client]\nport = \"80\"".to_owned();
+///
+/// let mut sources = SourceMap::new();
+/// let main_id = sources.add("main.toml", &main);
+/// let extra_id = sources.add("conf.d/extra.toml", &extra);
+///
+/// let pos = Position::from_start(&extra).skip(16).unwrap();
+/// assert_eq!(sources.file_of(&pos), Some(extra_id));
+/// assert_eq!(sources.file_of(&Position::from_start(&main)), Some(main_id));
+///
+/// let error: Error<Rule> = Error::CustomErrorPos {
+///     message: "expected integer".to_owned(),
+///     pos
+/// };
+///
+/// assert_eq!(
+///     sources.format_error(&error),
+///     [
+///         " --> conf.d/extra.toml:2:8",
+///         "  |",
+///         "2 | port = \"80\"",
+///         "  |        ^---",
+///         "  |",
+///         "  = expected integer"
+///     ].join("\n")
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap<'i> {
+    files: Vec<SourceFile<'i>>
+}
+
+#[derive(Clone, Debug)]
+struct SourceFile<'i> {
+    path: String,
+    index: LineIndex<'i>
+}
+
+impl<'i> SourceMap<'i> {
+    /// Creates an empty `SourceMap`.
+    pub fn new() -> SourceMap<'i> {
+        SourceMap::default()
+    }
+
+    /// Adds the `input` read from the file at `path` and returns its `FileId`.
+    pub fn add(&mut self, path: &str, input: &'i str) -> FileId {
+        self.add_indexed(path, LineIndex::new(input))
+    }
+
+    /// Adds a file at `path` whose input is already indexed, e.g. with another
+    /// [`ColumnEncoding`](enum.ColumnEncoding.html), and returns its `FileId`.
+    pub fn add_indexed(&mut self, path: &str, index: LineIndex<'i>) -> FileId {
+        self.files.push(SourceFile {
+            path: path.to_owned(),
+            index
+        });
+
+        FileId(self.files.len() - 1)
+    }
+
+    /// Returns the path of the file `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was returned by another `SourceMap`.
+    pub fn path(&self, id: FileId) -> &str {
+        &self.files[id.0].path
+    }
+
+    /// Returns the input of the file `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was returned by another `SourceMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::SourceMap;
+    /// let mut sources = SourceMap::new();
+    /// let id = sources.add("a.toml", "a = 1");
+    ///
+    /// assert_eq!(sources.path(id), "a.toml");
+    /// assert_eq!(sources.input(id), "a = 1");
+    /// ```
+    pub fn input(&self, id: FileId) -> &'i str {
+        self.files[id.0].index.input()
+    }
+
+    /// Returns the `LineIndex` of the file `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was returned by another `SourceMap`.
+    pub fn line_index(&self, id: FileId) -> &LineIndex<'i> {
+        &self.files[id.0].index
+    }
+
+    /// Returns the file which `pos` points into, if it was added.
+    pub fn file_of(&self, pos: &Position<'i>) -> Option<FileId> {
+        let input = position::input(pos);
+
+        self.files
+            .iter()
+            .position(|file| {
+                let other = file.index.input();
+
+                input.as_ptr() == other.as_ptr() && input.len() == other.len()
+            })
+            .map(FileId)
+    }
+
+    /// Returns the file which `span` points into, if it was added.
+    pub fn file_of_span(&self, span: &Span<'i>) -> Option<FileId> {
+        self.file_of(&span.start_pos())
+    }
+
+    /// Formats an `error` like its `Display` implementation, with the path of the file it was
+    /// found in, if it was added.
+    pub fn format_error<R: RuleType>(&self, error: &Error<'i, R>) -> String {
+        self.format_diagnostic(&Diagnostic::new(error.clone()))
+    }
+
+    /// Formats a `diagnostic` like its `Display` implementation, with the path of the file its
+    /// `Error` was found in, if it was added and the `diagnostic` does not have a path already.
+    pub fn format_diagnostic<R: RuleType>(&self, diagnostic: &Diagnostic<'i, R>) -> String {
+        let pos = match *diagnostic.error() {
+            Error::ParsingError { ref pos, .. } | Error::CustomErrorPos { ref pos, .. } => {
+                pos.clone()
+            }
+            Error::CustomErrorSpan { ref span, .. } => span.start_pos()
+        };
+
+        match self.file_of(&pos) {
+            Some(id) if diagnostic.path().is_none() => diagnostic
+                .clone()
+                .with_path(self.path(id))
+                .format_with(self.line_index(id)),
+            Some(id) => diagnostic.format_with(self.line_index(id)),
+            None => format!("{}", diagnostic)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_with_same_contents() {
+        let a = "a".to_owned();
+        let b = "a".to_owned();
+
+        let mut sources = SourceMap::new();
+        let a_id = sources.add("a", &a);
+        let b_id = sources.add("b", &b);
+
+        assert_eq!(sources.file_of(&Position::from_start(&a)), Some(a_id));
+        assert_eq!(sources.file_of(&Position::from_start(&b)), Some(b_id));
+        assert_eq!(sources.file_of(&Position::from_start(&a[..0])), None);
+    }
+
+    #[test]
+    fn format_unknown_file() {
+        let mut sources = SourceMap::new();
+        sources.add("a", "a");
+
+        let error: Error<()> = Error::CustomErrorPos {
+            message: "error".to_owned(),
+            pos: Position::from_start("b")
+        };
+
+        assert_eq!(sources.format_error(&error), format!("{}", error));
+    }
+
+    #[test]
+    fn format_diagnostic_with_path() {
+        let input = "a";
+        let mut sources = SourceMap::new();
+        sources.add("a", input);
+
+        let error: Error<()> = Error::CustomErrorPos {
+            message: "error".to_owned(),
+            pos: Position::from_start(input)
+        };
+
+        assert!(sources.format_error(&error).starts_with(" --> a:1:1\n"));
+        assert!(
+            sources
+                .format_diagnostic(&error.with_path("b"))
+                .starts_with(" --> b:1:1\n")
+        );
+    }
+}
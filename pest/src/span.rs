@@ -12,6 +12,8 @@ use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::ptr;
 
+use encoding::ColumnEncoding;
+use line_index::LineIndex;
 use position;
 
 /// A `struct` of a span over a `&str`. It is created from either
@@ -125,6 +127,36 @@ impl<'i> Span<'i> {
         (pos1, pos2)
     }
 
+    /// Returns the line - and column number pair of the start of the `Span`, looked up in an
+    /// already built `index` of its input, just like
+    /// [`Position::line_col_in`](struct.Position.html#method.line_col_in).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{LineIndex, Position};
+    /// let input = "a\nbc";
+    /// let index = LineIndex::new(input);
+    /// let start = Position::from_start(input).skip(1).unwrap();
+    /// let end = start.clone().match_string("\nb").unwrap();
+    /// let span = start.span(&end);
+    ///
+    /// assert_eq!(span.start_line_col_in(&index), (1, 2));
+    /// assert_eq!(span.end_line_col_in(&index), (2, 2));
+    /// ```
+    #[inline]
+    pub fn start_line_col_in(&self, index: &LineIndex) -> (usize, usize) {
+        index.line_col(self.start)
+    }
+
+    /// Returns the line - and column number pair of the end of the `Span`, looked up in an
+    /// already built `index` of its input, just like
+    /// [`Position::line_col_in`](struct.Position.html#method.line_col_in).
+    #[inline]
+    pub fn end_line_col_in(&self, index: &LineIndex) -> (usize, usize) {
+        index.line_col(self.end)
+    }
+
     /// Captures a slice from the `&str` defined by the `Span`.
     ///
     /// # Examples
@@ -142,6 +174,25 @@ impl<'i> Span<'i> {
     pub fn as_str(&self) -> &'i str {
         unsafe { self.input.slice_unchecked(self.start, self.end) }
     }
+
+    /// Returns the width of the `Span` in the units of `encoding`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{ColumnEncoding, Position};
+    /// let input = "a嗨";
+    /// let start = Position::from_start(input);
+    /// let end = start.clone().match_string("a嗨").unwrap();
+    /// let span = start.span(&end);
+    ///
+    /// assert_eq!(span.width(ColumnEncoding::Chars), 2);
+    /// assert_eq!(span.width(ColumnEncoding::Display { tab_width: 4 }), 3);
+    /// ```
+    #[inline]
+    pub fn width(&self, encoding: ColumnEncoding) -> usize {
+        encoding.width(self.as_str())
+    }
 }
 
 impl<'i> fmt::Debug for Span<'i> {
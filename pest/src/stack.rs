@@ -0,0 +1,299 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+/// A `struct` of a stack which can be snapshotted and restored to a snapshot. It is used by
+/// [`ParserState`](struct.ParserState.html) so that `push`es and `pop`s performed by failed
+/// sequences, rules, and lookaheads are undone on backtracking.
+///
+/// Operations are only logged while a snapshot is active, so a stack that is never snapshotted
+/// behaves just like a `Vec`.
+#[derive(Debug)]
+pub struct Stack<T: Clone> {
+    cache: Vec<T>,
+    ops: Vec<StackOp<T>>,
+    snapshots: Vec<usize>
+}
+
+#[derive(Debug)]
+enum StackOp<T> {
+    Push,
+    Pop(T)
+}
+
+impl<T: Clone> Stack<T> {
+    /// Creates a new, empty `Stack`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Stack;
+    /// let stack: Stack<&str> = Stack::new();
+    ///
+    /// assert!(stack.is_empty());
+    /// ```
+    pub fn new() -> Stack<T> {
+        Stack {
+            cache: vec![],
+            ops: vec![],
+            snapshots: vec![]
+        }
+    }
+
+    /// Returns `true` if the `Stack` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Stack;
+    /// let mut stack = Stack::new();
+    /// stack.push("a");
+    ///
+    /// assert!(!stack.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Returns the number of elements in the `Stack`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Stack;
+    /// let mut stack = Stack::new();
+    /// stack.push("a");
+    /// stack.push("b");
+    ///
+    /// assert_eq!(stack.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Returns the elements of the `Stack` as a slice, from the bottom to the top.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Stack;
+    /// let mut stack = Stack::new();
+    /// stack.push("a");
+    /// stack.push("b");
+    ///
+    /// assert_eq!(stack.as_slice(), &["a", "b"]);
+    /// ```
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.cache
+    }
+
+    /// Returns the top of the `Stack`, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Stack;
+    /// let mut stack = Stack::new();
+    /// stack.push("a");
+    ///
+    /// assert_eq!(stack.peek(), Some(&"a"));
+    /// ```
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.cache.last()
+    }
+
+    /// Pushes `elem` onto the `Stack`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Stack;
+    /// let mut stack = Stack::new();
+    /// stack.push("a");
+    ///
+    /// assert_eq!(stack.peek(), Some(&"a"));
+    /// ```
+    #[inline]
+    pub fn push(&mut self, elem: T) {
+        if !self.snapshots.is_empty() {
+            self.ops.push(StackOp::Push);
+        }
+
+        self.cache.push(elem);
+    }
+
+    /// Pops the top of the `Stack`, or returns `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Stack;
+    /// let mut stack = Stack::new();
+    /// stack.push("a");
+    ///
+    /// assert_eq!(stack.pop(), Some("a"));
+    /// assert_eq!(stack.pop(), None);
+    /// ```
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        let popped = self.cache.pop();
+
+        if !self.snapshots.is_empty() {
+            if let Some(ref elem) = popped {
+                self.ops.push(StackOp::Pop(elem.clone()));
+            }
+        }
+
+        popped
+    }
+
+    /// Takes a snapshot of the `Stack` which can later be restored with
+    /// [`restore`](#method.restore) or dropped with [`clear_snapshot`](#method.clear_snapshot).
+    /// Snapshots nest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Stack;
+    /// let mut stack = Stack::new();
+    /// stack.push("a");
+    ///
+    /// stack.snapshot();
+    /// stack.pop();
+    /// stack.push("b");
+    /// stack.restore();
+    ///
+    /// assert_eq!(stack.peek(), Some(&"a"));
+    /// assert_eq!(stack.len(), 1);
+    /// ```
+    #[inline]
+    pub fn snapshot(&mut self) {
+        self.snapshots.push(self.ops.len());
+    }
+
+    /// Drops the last snapshot, keeping every change made since it was taken.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Stack;
+    /// let mut stack = Stack::new();
+    ///
+    /// stack.snapshot();
+    /// stack.push("a");
+    /// stack.clear_snapshot();
+    ///
+    /// assert_eq!(stack.peek(), Some(&"a"));
+    /// ```
+    #[inline]
+    pub fn clear_snapshot(&mut self) {
+        self.snapshots.pop();
+
+        // Changes only need to be kept around for as long as an outer snapshot may restore them.
+        if self.snapshots.is_empty() {
+            self.ops.clear();
+        }
+    }
+
+    /// Restores the `Stack` to the last snapshot, undoing every change made since it was taken,
+    /// and drops the snapshot. Does nothing if there is no snapshot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Stack;
+    /// let mut stack = Stack::new();
+    ///
+    /// stack.snapshot();
+    /// stack.push("a");
+    /// stack.restore();
+    ///
+    /// assert!(stack.is_empty());
+    /// ```
+    #[inline]
+    pub fn restore(&mut self) {
+        let index = match self.snapshots.pop() {
+            Some(index) => index,
+            None => return
+        };
+
+        while self.ops.len() > index {
+            match self.ops.pop().unwrap() {
+                StackOp::Push => {
+                    self.cache.pop();
+                }
+                StackOp::Pop(elem) => self.cache.push(elem)
+            }
+        }
+    }
+}
+
+impl<T: Clone> Default for Stack<T> {
+    fn default() -> Stack<T> {
+        Stack::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_nested() {
+        let mut stack = Stack::new();
+        stack.push(1);
+
+        stack.snapshot();
+        stack.push(2);
+
+        stack.snapshot();
+        stack.pop();
+        stack.pop();
+        stack.push(3);
+        stack.restore();
+
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.peek(), Some(&2));
+
+        stack.restore();
+
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.peek(), Some(&1));
+    }
+
+    #[test]
+    fn restore_after_cleared_inner_snapshot() {
+        let mut stack = Stack::new();
+
+        stack.snapshot();
+        stack.push(1);
+
+        stack.snapshot();
+        stack.push(2);
+        stack.clear_snapshot();
+
+        assert_eq!(stack.len(), 2);
+
+        stack.restore();
+
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn no_ops_logged_without_snapshot() {
+        let mut stack = Stack::new();
+        stack.push(1);
+        stack.pop();
+
+        assert!(stack.ops.is_empty());
+    }
+}
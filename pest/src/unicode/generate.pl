@@ -0,0 +1,153 @@
+#!/usr/bin/env perl
+
+# pest. The Elegant Parser
+# Copyright (c) 2018 Dragoș Tiselice
+#
+# Licensed under the Apache License, Version 2.0
+# <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+# license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+# option. All files in the project carrying such notice may not be copied,
+# modified, or distributed except according to those terms.
+
+# Generates `tables.rs` from the Unicode Character Database shipped with Perl. Every table is
+# taken from the same version of the database, which has to be `$VERSION`; Perl 5.36 ships it.
+#
+#     perl pest/src/unicode/generate.pl > pest/src/unicode/tables.rs
+
+use strict;
+use utf8;
+use warnings;
+use feature qw(fc unicode_strings);
+
+use Unicode::UCD qw(prop_invlist);
+
+my $VERSION = '14.0.0';
+
+die "expected Unicode $VERSION, found " . Unicode::UCD::UnicodeVersion() . "\n"
+    unless Unicode::UCD::UnicodeVersion() eq $VERSION;
+
+binmode STDOUT, ':encoding(UTF-8)';
+
+my @CATEGORIES = (
+    [Lu => 'UppercaseLetter'], [Ll => 'LowercaseLetter'], [Lt => 'TitlecaseLetter'],
+    [Lm => 'ModifierLetter'], [Lo => 'OtherLetter'],
+    [Mn => 'NonspacingMark'], [Mc => 'SpacingMark'], [Me => 'EnclosingMark'],
+    [Nd => 'DecimalNumber'], [Nl => 'LetterNumber'], [No => 'OtherNumber'],
+    [Pc => 'ConnectorPunctuation'], [Pd => 'DashPunctuation'], [Ps => 'OpenPunctuation'],
+    [Pe => 'ClosePunctuation'], [Pi => 'InitialPunctuation'], [Pf => 'FinalPunctuation'],
+    [Po => 'OtherPunctuation'],
+    [Sm => 'MathSymbol'], [Sc => 'CurrencySymbol'], [Sk => 'ModifierSymbol'],
+    [So => 'OtherSymbol'],
+    [Zs => 'SpaceSeparator'], [Zl => 'LineSeparator'], [Zp => 'ParagraphSeparator'],
+    [Cc => 'Control'], [Cf => 'Format'], [Co => 'PrivateUse']
+);
+
+my @PROPERTIES = (
+    [ALPHABETIC => 'Alphabetic'], [WHITE_SPACE => 'White_Space'], [LOWERCASE => 'Lowercase'],
+    [UPPERCASE => 'Uppercase'], [XID_START => 'XID_Start'], [XID_CONTINUE => 'XID_Continue']
+);
+
+# Turns the inversion list of `$property` into inclusive `[start, end]` ranges.
+sub ranges {
+    my ($property) = @_;
+    my @list = prop_invlist($property);
+    my @ranges;
+
+    while (@list) {
+        my $start = shift @list;
+        my $end = @list ? shift(@list) - 1 : 0x10ffff;
+
+        push @ranges, [$start, $end];
+    }
+
+    return @ranges;
+}
+
+# Lays out `@items` on lines of at most 100 columns.
+sub emit {
+    my @items = @_;
+    my @lines;
+    my $line = '    ';
+
+    for my $item (@items) {
+        if (length($line) + length($item) + 1 > 100 && $line =~ /\S/) {
+            $line =~ s/\s+$//;
+            push @lines, $line;
+            $line = '    ';
+        }
+
+        $line .= "$item ";
+    }
+
+    $line =~ s/\s+$//;
+    push @lines, $line if $line =~ /\S/;
+
+    return join("\n", @lines) . "\n";
+}
+
+sub escape {
+    my ($string) = @_;
+
+    return join '', map { ord($_) < 0x80 ? $_ : sprintf('\\u{%x}', ord($_)) } split //, $string;
+}
+
+my @categories;
+for my $category (@CATEGORIES) {
+    my ($short, $name) = @$category;
+
+    push @categories, map { [@$_, $name] } ranges("gc=$short");
+}
+@categories = sort { $a->[0] <=> $b->[0] } @categories;
+
+my @folds;
+for my $code (0 .. 0x10ffff) {
+    next if $code >= 0xd800 && $code <= 0xdfff;
+
+    my $folded = fc(chr($code));
+    push @folds, [$code, $folded] if $folded ne chr($code);
+}
+
+print <<"EOF";
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+// Generated by `generate.pl` from the Unicode Character Database, version $VERSION. Do not edit
+// it by hand.
+//
+// Every table is sorted by code point. Code points that are missing from `GENERAL_CATEGORIES` are
+// either unassigned or surrogates, while those missing from `CASE_FOLDING` fold to themselves.
+// `CASE_FOLDING` holds the full case folding, i.e. the mappings of status C and F, and
+// `CASE_UNFOLDING` holds the same mappings sorted by folding instead.
+
+use super::GeneralCategory;
+use super::GeneralCategory::*;
+
+EOF
+
+print "pub static GENERAL_CATEGORIES: &[(u32, u32, GeneralCategory)] = &[\n";
+print emit(map { sprintf('(0x%x, 0x%x, %s),', @$_) } @categories);
+print "];\n";
+
+for my $property (@PROPERTIES) {
+    my ($name, $ucd) = @$property;
+
+    print "\npub static $name: &[(u32, u32)] = &[\n";
+    print emit(map { sprintf('(0x%x, 0x%x),', @$_) } ranges($ucd));
+    print "];\n";
+}
+
+print "\npub static CASE_FOLDING: &[(u32, &str)] = &[\n";
+print emit(map { sprintf('(0x%x, "%s"),', $_->[0], escape($_->[1])) } @folds);
+print "];\n";
+
+my @unfolds = sort { $a->[1] cmp $b->[1] || $a->[0] <=> $b->[0] } @folds;
+
+print "\npub static CASE_UNFOLDING: &[(&str, u32)] = &[\n";
+print emit(map { sprintf('("%s", 0x%x),', escape($_->[1]), $_->[0]) } @unfolds);
+print "];\n";
@@ -0,0 +1,326 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Predicates on `char`s for Unicode general categories and properties, which back the
+//! predefined rules of the same names. They are meant to be used with
+//! [`Position::match_char_by`](../struct.Position.html#method.match_char_by).
+//!
+//! # Examples
+//!
+//! ```
+//! # use pest::Position;
+//! # use pest::unicode;
+//! let start = Position::from_start("ü1");
+//!
+//! assert!(unicode::LETTER('ü'));
+//! assert_eq!(start.clone().match_char_by(unicode::LETTER).unwrap().pos(), 2);
+//! assert_eq!(start.clone().match_char_by(unicode::NUMBER), Err(start));
+//! ```
+
+#![allow(non_snake_case)]
+
+use std::cmp::Ordering;
+use std::str::Chars;
+
+mod tables;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum GeneralCategory {
+    UppercaseLetter,
+    LowercaseLetter,
+    TitlecaseLetter,
+    ModifierLetter,
+    OtherLetter,
+    NonspacingMark,
+    SpacingMark,
+    EnclosingMark,
+    DecimalNumber,
+    LetterNumber,
+    OtherNumber,
+    ConnectorPunctuation,
+    DashPunctuation,
+    OpenPunctuation,
+    ClosePunctuation,
+    InitialPunctuation,
+    FinalPunctuation,
+    OtherPunctuation,
+    MathSymbol,
+    CurrencySymbol,
+    ModifierSymbol,
+    OtherSymbol,
+    SpaceSeparator,
+    LineSeparator,
+    ParagraphSeparator,
+    Control,
+    Format,
+    PrivateUse,
+    Unassigned
+}
+
+fn general_category(c: char) -> GeneralCategory {
+    let c = c as u32;
+
+    tables::GENERAL_CATEGORIES
+        .binary_search_by(|&(start, end, _)| compare(start, end, c))
+        .map(|i| tables::GENERAL_CATEGORIES[i].2)
+        .unwrap_or(GeneralCategory::Unassigned)
+}
+
+fn in_table(table: &[(u32, u32)], c: char) -> bool {
+    let c = c as u32;
+
+    table
+        .binary_search_by(|&(start, end)| compare(start, end, c))
+        .is_ok()
+}
+
+fn compare(start: u32, end: u32, c: u32) -> Ordering {
+    if end < c {
+        Ordering::Less
+    } else if start > c {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// An `Iterator` over the full case folding of a `char`, e.g. `"ss"` for `'ß'`.
+#[derive(Clone, Debug)]
+pub(crate) enum Fold {
+    Char(Option<char>),
+    Str(Chars<'static>)
+}
+
+impl Iterator for Fold {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match *self {
+            Fold::Char(ref mut c) => c.take(),
+            Fold::Str(ref mut chars) => chars.next()
+        }
+    }
+}
+
+pub(crate) fn fold(c: char) -> Fold {
+    match folding(c) {
+        Some(string) => Fold::Str(string.chars()),
+        None => Fold::Char(Some(c))
+    }
+}
+
+fn folding(c: char) -> Option<&'static str> {
+    tables::CASE_FOLDING
+        .binary_search_by_key(&(c as u32), |&(code, _)| code)
+        .ok()
+        .map(|i| tables::CASE_FOLDING[i].1)
+}
+
+/// Returns whether `c` has the same case folding as any `char` in `start..=end`.
+pub(crate) fn in_range_insensitive(c: char, start: char, end: char) -> bool {
+    let in_range = |c: char| start <= c && c <= end;
+
+    if in_range(c) {
+        return true;
+    }
+
+    // Foldings never fold any further, so a single `char` that `c` folds to is a match as well.
+    let mut folded = fold(c);
+    if let (Some(folded), None) = (folded.next(), folded.next()) {
+        if in_range(folded) {
+            return true;
+        }
+    }
+
+    // Never returning `Equal` finds the first `char` with the same folding as `c`, if any.
+    let first = tables::CASE_UNFOLDING
+        .binary_search_by(|&(string, _)| string.chars().cmp(fold(c)).then(Ordering::Greater))
+        .unwrap_err();
+
+    tables::CASE_UNFOLDING[first..]
+        .iter()
+        .take_while(|&&(string, _)| fold(c).eq(string.chars()))
+        .filter_map(|&(_, code)| ::std::char::from_u32(code))
+        .any(in_range)
+}
+
+macro_rules! categories {
+    ( $( $name:ident => [ $( $category:ident ),* ] ),* ) => {
+        $(
+            /// Returns whether `c` is in the general category of the same name.
+            #[inline]
+            pub fn $name(c: char) -> bool {
+                [ $( GeneralCategory::$category ),* ].contains(&general_category(c))
+            }
+        )*
+    };
+}
+
+macro_rules! properties {
+    ( $( $name:ident => $predicate:expr ),* ) => {
+        $(
+            /// Returns whether `c` has the property of the same name.
+            #[inline]
+            pub fn $name(c: char) -> bool {
+                $predicate(c)
+            }
+        )*
+    };
+}
+
+macro_rules! by_name {
+    ( $( $name:ident ),* ) => {
+        /// Returns the predicate called `name`, e.g. `"LETTER"`, if there is one.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// # use pest::unicode;
+        /// assert!(unicode::by_name("XID_START").unwrap()('a'));
+        /// assert!(unicode::by_name("digit").is_none());
+        /// ```
+        pub fn by_name(name: &str) -> Option<fn(char) -> bool> {
+            match name {
+                $( stringify!($name) => Some($name), )*
+                _ => None
+            }
+        }
+    };
+}
+
+categories! {
+    LETTER => [UppercaseLetter, LowercaseLetter, TitlecaseLetter, ModifierLetter, OtherLetter],
+    CASED_LETTER => [UppercaseLetter, LowercaseLetter, TitlecaseLetter],
+    UPPERCASE_LETTER => [UppercaseLetter],
+    LOWERCASE_LETTER => [LowercaseLetter],
+    TITLECASE_LETTER => [TitlecaseLetter],
+    MODIFIER_LETTER => [ModifierLetter],
+    OTHER_LETTER => [OtherLetter],
+
+    MARK => [NonspacingMark, SpacingMark, EnclosingMark],
+    NONSPACING_MARK => [NonspacingMark],
+    SPACING_MARK => [SpacingMark],
+    ENCLOSING_MARK => [EnclosingMark],
+
+    NUMBER => [DecimalNumber, LetterNumber, OtherNumber],
+    DECIMAL_NUMBER => [DecimalNumber],
+    LETTER_NUMBER => [LetterNumber],
+    OTHER_NUMBER => [OtherNumber],
+
+    PUNCTUATION => [
+        ConnectorPunctuation,
+        DashPunctuation,
+        OpenPunctuation,
+        ClosePunctuation,
+        InitialPunctuation,
+        FinalPunctuation,
+        OtherPunctuation
+    ],
+    CONNECTOR_PUNCTUATION => [ConnectorPunctuation],
+    DASH_PUNCTUATION => [DashPunctuation],
+    OPEN_PUNCTUATION => [OpenPunctuation],
+    CLOSE_PUNCTUATION => [ClosePunctuation],
+    INITIAL_PUNCTUATION => [InitialPunctuation],
+    FINAL_PUNCTUATION => [FinalPunctuation],
+    OTHER_PUNCTUATION => [OtherPunctuation],
+
+    SYMBOL => [MathSymbol, CurrencySymbol, ModifierSymbol, OtherSymbol],
+    MATH_SYMBOL => [MathSymbol],
+    CURRENCY_SYMBOL => [CurrencySymbol],
+    MODIFIER_SYMBOL => [ModifierSymbol],
+    OTHER_SYMBOL => [OtherSymbol],
+
+    SEPARATOR => [SpaceSeparator, LineSeparator, ParagraphSeparator],
+    SPACE_SEPARATOR => [SpaceSeparator],
+    LINE_SEPARATOR => [LineSeparator],
+    PARAGRAPH_SEPARATOR => [ParagraphSeparator],
+
+    OTHER => [Control, Format, PrivateUse, Unassigned],
+    CONTROL => [Control],
+    FORMAT => [Format],
+    PRIVATE_USE => [PrivateUse],
+    UNASSIGNED => [Unassigned]
+}
+
+properties! {
+    ALPHABETIC => |c| in_table(tables::ALPHABETIC, c),
+    WHITE_SPACE => |c| in_table(tables::WHITE_SPACE, c),
+    LOWERCASE => |c| in_table(tables::LOWERCASE, c),
+    UPPERCASE => |c| in_table(tables::UPPERCASE, c),
+    XID_START => |c| in_table(tables::XID_START, c),
+    XID_CONTINUE => |c| in_table(tables::XID_CONTINUE, c)
+}
+
+by_name! {
+    LETTER, CASED_LETTER, UPPERCASE_LETTER, LOWERCASE_LETTER, TITLECASE_LETTER, MODIFIER_LETTER,
+    OTHER_LETTER, MARK, NONSPACING_MARK, SPACING_MARK, ENCLOSING_MARK, NUMBER, DECIMAL_NUMBER,
+    LETTER_NUMBER, OTHER_NUMBER, PUNCTUATION, CONNECTOR_PUNCTUATION, DASH_PUNCTUATION,
+    OPEN_PUNCTUATION, CLOSE_PUNCTUATION, INITIAL_PUNCTUATION, FINAL_PUNCTUATION,
+    OTHER_PUNCTUATION, SYMBOL, MATH_SYMBOL, CURRENCY_SYMBOL, MODIFIER_SYMBOL, OTHER_SYMBOL,
+    SEPARATOR, SPACE_SEPARATOR, LINE_SEPARATOR, PARAGRAPH_SEPARATOR, OTHER, CONTROL, FORMAT,
+    PRIVATE_USE, UNASSIGNED, ALPHABETIC, WHITE_SPACE, LOWERCASE, UPPERCASE, XID_START,
+    XID_CONTINUE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories() {
+        assert!(UPPERCASE_LETTER('Á'));
+        assert!(LOWERCASE_LETTER('ß'));
+        assert!(OTHER_LETTER('中'));
+        assert!(DECIMAL_NUMBER('٣'));
+        assert!(CURRENCY_SYMBOL('€'));
+        assert!(SPACE_SEPARATOR('\u{a0}'));
+        assert!(CONTROL('\n'));
+        assert!(PRIVATE_USE('\u{e000}'));
+        assert!(UNASSIGNED('\u{10ffff}'));
+        assert!(!LETTER('1'));
+    }
+
+    #[test]
+    fn table_bounds() {
+        assert!(CONTROL('\0'));
+        assert!(OTHER('\u{10ffff}'));
+        assert!(!XID_START('0'));
+        assert!(XID_CONTINUE('0'));
+        assert!(!XID_START('_'));
+        assert!(XID_CONTINUE('_'));
+    }
+
+    #[test]
+    fn case_unfolding_sorted() {
+        assert!(
+            tables::CASE_UNFOLDING
+                .windows(2)
+                .all(|pair| pair[0].0.chars().cmp(pair[1].0.chars()) != Ordering::Greater)
+        );
+    }
+
+    #[test]
+    fn range_insensitive() {
+        assert!(in_range_insensitive('k', '\u{212a}', '\u{212a}'));
+        assert!(in_range_insensitive('\u{212a}', 'K', 'K'));
+        assert!(in_range_insensitive('\u{1e9e}', '\u{df}', '\u{df}'));
+        assert!(!in_range_insensitive('s', '\u{df}', '\u{df}'));
+    }
+
+    #[test]
+    fn properties() {
+        assert!(ALPHABETIC('\u{345}'));
+        assert!(!ALPHABETIC('1'));
+        assert!(WHITE_SPACE('\u{85}'));
+        assert!(!WHITE_SPACE('\u{180e}'));
+        assert!(LOWERCASE('\u{aa}'));
+        assert!(UPPERCASE('\u{2160}'));
+        assert!(!UPPERCASE('a'));
+    }
+}
@@ -21,24 +21,111 @@
 //! relative to `src` and is specified between the `derive` attribute and empty `struct` that
 //! `Parser` will be derived on.
 //!
-//! Because of a limitation in procedural macros, there is no way for Cargo to know that a module
-//! needs to be recompiled based on the file that the procedural macro is opening. This leads to the
-//! case where modifying a `.pest` file without touching the file where the `derive` is does not
-//! recompile it if it already has a working binary in the cache. To avoid this issue, the grammar
-//! file can be included in a dummy `const` definition while debugging.
+//! Every grammar file, as well as every file it imports, is included in the generated code with
+//! `include_str!`, so that Cargo recompiles the `derive` whenever one of them changes.
 //!
 //! ```ignore
-//! #[cfg(debug_assertions)]
-//! const _GRAMMAR: &'static str = include_str!("path/to/my_grammar.pest"); // relative to this file
-//!
 //! #[derive(Parser)]
 //! #[grammar = "path/to/my_grammar.pest"] // relative to src
 //! struct MyParser;
 //! ```
 //!
+//! ## Inline grammars
+//!
+//! Small grammars can also be written directly in a `grammar_inline` attribute instead. Errors in
+//! such a grammar are reported at `grammar_inline:line:column`, counted from the start of the
+//! attribute's string.
+//!
+//! ```ignore
+//! #[derive(Parser)]
+//! #[grammar_inline = "number = @{ '0'..'9'+ }"]
+//! struct MyParser;
+//! ```
+//!
+//! ## Multiple grammars
+//!
+//! Several `grammar` and `grammar_inline` attributes can be provided. Their grammars are merged in
+//! order into a single `Rule` `enum`, and defining the same rule in more than one of them is an
+//! error.
+//!
+//! ```ignore
+//! #[derive(Parser)]
+//! #[grammar = "lexical.pest"]
+//! #[grammar = "my_language.pest"]
+//! struct MyParser;
+//! ```
+//!
+//! ## Memoization
+//!
+//! With the `memoize` attribute, the derived parser caches the result of every rule at every
+//! position it is tried at, so that alternatives starting with the same rules do not parse them
+//! over again. This trades memory for avoiding exponential backtracking and does not change what
+//! is parsed nor the errors that are reported.
+//!
+//! ```ignore
+//! #[derive(Parser)]
+//! #[grammar = "my_language.pest"]
+//! #[memoize]
+//! struct MyParser;
+//! ```
+//!
+//! ## Rule stacks
+//!
+//! With the `track_rule_stack` attribute, the derived parser keeps track of the rules it is
+//! running, so that its errors also report the rules that the failed attempts were made inside of
+//! together with where they started, in their `rule_stack`.
+//!
+//! ```ignore
+//! #[derive(Parser)]
+//! #[grammar = "my_language.pest"]
+//! #[track_rule_stack]
+//! struct MyParser;
+//! ```
+//!
+//! ## Error recovery
+//!
+//! A `recover` attribute lets a rule recover from its errors: when it fails, the error is recorded
+//! and the input is skipped up to the next occurrence of the `sync` string, where parsing resumes.
+//! The skipped input is covered by a pair of the `error` rule, which has to be defined in the
+//! grammar. Every recorded error is returned by `Parser::parse_recovering`, while `Parser::parse`
+//! fails with the first one.
+//!
+//! ```ignore
+//! #[derive(Parser)]
+//! #[grammar = "my_language.pest"]
+//! #[recover(rule = "statement", error = "error", sync = ";")]
+//! struct MyParser;
+//! ```
+//!
 //! ## Grammar
 //!
-//! A grammar is a series of rules separated by whitespace, possibly containing comments.
+//! A grammar is a series of rules and imports separated by whitespace, possibly containing
+//! comments.
+//!
+//! ### Imports
+//!
+//! A grammar can import the rules of another file with its path relative to the importing file.
+//! Each file is only imported once, no matter how many grammars import it.
+//!
+//! ```ignore
+//! import "lexical.pest"
+//! ```
+//!
+//! ### Overriding rules
+//!
+//! A grammar can extend another one by importing it and redefining some of its rules with
+//! `override`. The overriding rule replaces the original one everywhere it is called, so a
+//! dialect only needs to spell out the rules in which it differs. Overriding a rule which is not
+//! defined is an error, and when a rule is overridden more than once, the last override wins.
+//!
+//! ```ignore
+//! import "sql.pest"
+//!
+//! override identifier = @{ "`" ~ (!"`" ~ any)* ~ "`" | ident_start ~ ident_char* }
+//! ```
+//!
+//! Deriving `Parser` for the dialect generates its own `Rule` `enum`, so the base grammar and its
+//! dialects should be derived in separate modules.
 //!
 //! ### Comments
 //!
@@ -132,12 +219,16 @@
 //!
 //! 1. Terminals
 //!
-//!     | Terminal   | Usage                                                          |
-//!     |------------|----------------------------------------------------------------|
-//!     | `"a"`      | matches the exact string `"a"`                                 |
-//!     | `^"a"`     | matches the exact string `"a"` case insensitively (ASCII only) |
-//!     | `'a'..'z'` | matches one character between `'a'` and `'z'`                  |
-//!     | `a`        | matches rule `a`                                               |
+//!     | Terminal    | Usage                                                            |
+//!     |-------------|------------------------------------------------------------------|
+//!     | `"a"`       | matches the exact string `"a"`                                   |
+//!     | `^"a"`      | matches the exact string `"a"` case insensitively                |
+//!     | `'a'..'z'`  | matches one character between `'a'` and `'z'`                    |
+//!     | `^'a'..'z'` | matches one character between `'a'` and `'z'` case insensitively |
+//!     | `a`         | matches rule `a`                                                 |
+//!
+//! Case insensitive terminals compare the Unicode full case foldings of the characters, so
+//! `^"straße"` also matches `"STRASSE"`.
 //!
 //! Strings and characters follow
 //! [Rust's escape mechanisms](https://doc.rust-lang.org/reference/tokens.html#byte-escapes), while
@@ -161,9 +252,54 @@
 //!     | `&e`         | matches `e` without making progress                        |
 //!     | `!e`         | matches if `e` doesn't match without making progress       |
 //!     | `push(e)`    | matches `e` and pushes it's captured string down the stack |
+//!     | `peek[m..n]` | matches the slice `m..n` of the stack from bottom to top   |
 //!
 //!     where `e`, `e1`, and `e2` are expressions.
 //!
+//! #### Parameterized rules
+//!
+//! Rules can take expressions as parameters, which makes it possible to name patterns that
+//! repeat throughout a grammar:
+//!
+//! ```ignore
+//! list(item, separator) = _{ item ~ (separator ~ item)* }
+//!
+//! array = { "[" ~ list(value, ",")? ~ "]" }
+//! object = { "{" ~ list(pair, ",")? ~ "}" }
+//! ```
+//!
+//! Every call is expanded in place by substituting its arguments for the parameters, so
+//! parameterized rules have to be silent and cannot call themselves, not even through other
+//! parameterized rules. They must always be called with as many arguments as they have
+//! parameters.
+//!
+//! #### Node tags
+//!
+//! A term can be tagged with `#tag = e`. Every pair produced by the outermost rules matched by `e`
+//! is then tagged with `tag`, which can be read back with
+//! [`Pair::as_node_tag`](../pest/iterators/struct.Pair.html#method.as_node_tag) or searched for
+//! with [`Pairs::find_tagged`](../pest/iterators/struct.Pairs.html#method.find_tagged):
+//!
+//! ```ignore
+//! expr = { #lhs = term ~ op ~ #rhs = term }
+//! ```
+//!
+//! Tags do not change what is matched and terms that produce no pairs are left untagged.
+//!
+//! #### Doc comments
+//!
+//! Rules can be documented with `///` comments, which end up on their variants of the generated
+//! `Rule` `enum`, while `//!` comments document the `enum` itself:
+//!
+//! ```ignore
+//! //! Grammar of arithmetic expressions.
+//!
+//! /// A decimal integer like `42`.
+//! number = @{ '0'..'9'+ }
+//! ```
+//!
+//! Any other comment starts with `//` and is ignored, including those starting with `////`.
+//!
 //! ## Special rules
 //!
 //! Special rules can be called within the grammar. They are:
@@ -175,10 +311,28 @@
 //! * `eoi` - (end-of-input) matches only when a `Parser` has reached its end
 //! * `pop` - pops a string from the stack and matches it
 //! * `peek` - peeks a string from the stack and matches it
+//! * `drop` - pops a string from the stack without matching it
+//! * `pop_all` - pops the entire stack and matches it from top to bottom
+//! * `peek_all` - peeks the entire stack and matches it from top to bottom
 //!
 //! `whitespace` and `comment` should be defined manually if needed. All other rules cannot be
 //! overridden.
 //!
+//! ## Character classes
+//!
+//! Common classes of characters are predefined as well and cannot be overridden either:
+//!
+//! * `ASCII_DIGIT`, `ASCII_NONZERO_DIGIT`, `ASCII_BIN_DIGIT`, `ASCII_OCT_DIGIT`, and
+//!   `ASCII_HEX_DIGIT` match one digit of the respective base
+//! * `ASCII_ALPHA_LOWER`, `ASCII_ALPHA_UPPER`, `ASCII_ALPHA`, and `ASCII_ALPHANUMERIC` match one
+//!   ASCII letter or digit
+//! * `ASCII` matches one ASCII character
+//! * `NEWLINE` matches `"\n"`, `"\r\n"`, or `"\r"`
+//! * Unicode general categories like `LETTER`, `UPPERCASE_LETTER`, or `DECIMAL_NUMBER` and
+//!   properties like `ALPHABETIC`, `WHITE_SPACE`, or `XID_START` match one character in the
+//!   category or with the property; see [`pest::unicode`](../pest/unicode/index.html) for all of
+//!   them
+//!
 //! ## `whitespace` and `comment`
 //!
 //! When defined, these rules get matched automatically in sequences (`~`) and repetitions
@@ -222,19 +376,29 @@
 //! the match worked. With the stack from above, if `pop` matches `"a"`, the stack will be mutated
 //! to `["b"]`.
 //!
+//! `drop` removes the string at the top of the stack without matching anything, failing if the
+//! stack is empty. `peek_all` and `pop_all` match every string in the stack, starting from the
+//! top, which comes in handy for closing nested delimiters.
+//!
+//! `peek[m..n]` matches a slice of the stack, this time from bottom to top, without changing it.
+//! Both `m` and `n` are optional and count from the bottom of the stack, or from its top when they
+//! are negative. With the stack `["a", "b", "c"]`, `peek[1..]` matches `"bc"` and `peek[..-1]`
+//! matches `"ab"`, which is useful for indentation-based languages. An out-of-bounds slice fails.
+//!
+//! Changes to the stack are undone on backtracking: `push`es and `pop`s made by a sequence, rule,
+//! or choice alternative that ends up failing, as well as those made inside of predicates, do not
+//! affect the rest of the parse.
+//!
 //! ## `Rule`
 //!
 //! All rules defined or used in the grammar populate a generated `enum` called `Rule`. This
 //! implements `pest`'s `RuleType` and can be used throughout the API.
 
 #![doc(html_root_url = "https://docs.rs/pest_derive")]
-#![recursion_limit = "256"]
 
-#[cfg(test)]
-#[macro_use]
-extern crate pest;
-#[cfg(not(test))]
 extern crate pest;
+extern crate pest_generator;
+extern crate pest_meta;
 
 extern crate proc_macro;
 #[macro_use]
@@ -242,126 +406,237 @@ extern crate quote;
 extern crate syn;
 
 use std::env;
-use std::fs::File;
-use std::io::{self, Read};
 use std::path::Path;
 
-use pest::Parser;
+use pest::Error;
+use pest_generator::{GrammarFiles, Options, Recovery};
+use pest_meta::parser::GrammarRule;
 use proc_macro::TokenStream;
-use quote::Ident;
-use syn::{Attribute, Lit, MetaItem};
-
-mod ast;
-mod generator;
-mod optimizer;
-mod parser;
-mod validator;
+use quote::{Ident, Tokens};
+use syn::{Attribute, Lit, MetaItem, NestedMetaItem};
 
-use parser::{GrammarParser, GrammarRule};
-
-#[proc_macro_derive(Parser, attributes(grammar))]
+#[proc_macro_derive(
+    Parser,
+    attributes(grammar, grammar_inline, memoize, recover, track_rule_stack)
+)]
 pub fn derive_parser(input: TokenStream) -> TokenStream {
     let source = input.to_string();
 
-    let (name, path) = parse_derive(source);
+    let (name, grammars, options) = parse_derive(source);
 
     let root = env::var("CARGO_MANIFEST_DIR").unwrap_or(".".into());
-    let path = Path::new(&root).join("src/").join(&path);
-    let file_name = match path.file_name() {
-        Some(file_name) => file_name,
-        None => panic!("grammar attribute should point to a file")
-    };
+    let src = Path::new(&root).join("src/");
+    let mut files = GrammarFiles::new();
 
-    let data = match read_file(&path) {
-        Ok(data) => data,
-        Err(error) => panic!("error opening {:?}: {}", file_name, error)
-    };
+    for grammar in grammars {
+        match grammar {
+            GrammarSource::File(path) => {
+                let path = src.join(&path);
+                let file_name = match path.file_name() {
+                    Some(file_name) => file_name.to_owned(),
+                    None => panic!("grammar attribute should point to a file")
+                };
 
-    let input = &data;
-    let pairs = match GrammarParser::parse(GrammarRule::grammar_rules, input) {
-        Ok(pairs) => pairs,
-        Err(error) => panic!(
-            "error parsing {:?}\n\n{}",
-            file_name,
-            error.renamed_rules(|rule| match *rule {
-                GrammarRule::grammar_rule => "rule".to_owned(),
-                GrammarRule::eoi => "end-of-input".to_owned(),
-                GrammarRule::assignment_operator => "`=`".to_owned(),
-                GrammarRule::silent_modifier => "`_`".to_owned(),
-                GrammarRule::atomic_modifier => "`@`".to_owned(),
-                GrammarRule::compound_atomic_modifier => "`$`".to_owned(),
-                GrammarRule::non_atomic_modifier => "`!`".to_owned(),
-                GrammarRule::opening_brace => "`{`".to_owned(),
-                GrammarRule::closing_brace => "`}`".to_owned(),
-                GrammarRule::opening_paren => "`(`".to_owned(),
-                GrammarRule::positive_predicate_operator => "`&`".to_owned(),
-                GrammarRule::negative_predicate_operator => "`!`".to_owned(),
-                GrammarRule::sequence_operator => "`&`".to_owned(),
-                GrammarRule::choice_operator => "`|`".to_owned(),
-                GrammarRule::optional_operator => "`?`".to_owned(),
-                GrammarRule::repeat_operator => "`*`".to_owned(),
-                GrammarRule::repeat_once_operator => "`+`".to_owned(),
-                GrammarRule::comma => "`,`".to_owned(),
-                GrammarRule::closing_paren => "`)`".to_owned(),
-                GrammarRule::quote => "`\"`".to_owned(),
-                GrammarRule::insensitive_string => "`^`".to_owned(),
-                GrammarRule::range_operator => "`..`".to_owned(),
-                GrammarRule::single_quote => "`'`".to_owned(),
-                other_rule => format!("{:?}", other_rule)
-            })
-        )
+                if let Err(error) = files.add_file(&path) {
+                    panic!("error opening {:?}: {}", file_name, error);
+                }
+            }
+            GrammarSource::Inline(data) => {
+                if let Err(error) = files.add_source("grammar_inline", &data, &src) {
+                    panic!("error opening grammar_inline imports: {}", error);
+                }
+            }
+        }
+    }
+
+    let (defaults, rules) = match pest_meta::parse_and_optimize(files.source()) {
+        Ok(result) => result,
+        Err(errors) => return compile_errors(&files, &errors).as_ref().parse().unwrap()
     };
 
-    let (ast, defaults) = parser::consume_rules(pairs);
-    let optimized = optimizer::optimize(ast);
-    let generated = generator::generate(name, optimized, defaults);
+    for recovery in options.recoveries() {
+        for name in &[&recovery.rule, &recovery.error] {
+            if !rules.iter().any(|rule| rule.name == **name) {
+                panic!("recover attribute names undefined rule {}", name);
+            }
+        }
+    }
+    let generated = pest_generator::generate(name, rules, defaults, files.doc(), &options);
+    let dependencies = dependencies(&files);
+
+    let generated = quote! {
+        #dependencies
+        #generated
+    };
 
     generated.as_ref().parse().unwrap()
 }
 
-fn read_file<P: AsRef<Path>>(path: P) -> io::Result<String> {
-    let mut file = File::open(path.as_ref())?;
-    let mut string = String::new();
-    file.read_to_string(&mut string)?;
-    Ok(string)
+// Includes every file that was read, which makes Cargo recompile the derive when any of them
+// changes.
+fn dependencies(files: &GrammarFiles) -> Tokens {
+    let paths = files.paths().iter().map(|path| path.to_string_lossy());
+
+    quote! {
+        #( const _: &str = include_str!(#paths); )*
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum GrammarSource {
+    File(String),
+    Inline(String)
 }
 
-fn parse_derive(source: String) -> (Ident, String) {
+fn compile_errors(files: &GrammarFiles, errors: &[Error<GrammarRule>]) -> Tokens {
+    let errors = errors.iter().map(|error| files.format_error(error));
+
+    quote! {
+        #( compile_error!(#errors); )*
+    }
+}
+
+fn parse_derive(source: String) -> (Ident, Vec<GrammarSource>, Options) {
     let ast = syn::parse_derive_input(&source).unwrap();
     let name = Ident::new(ast.ident.as_ref());
 
-    let grammar: Vec<_> = ast.attrs
+    let grammars: Vec<_> = ast.attrs
         .iter()
         .filter(|attr| match attr.value {
-            MetaItem::NameValue(ref ident, _) => format!("{}", ident) == "grammar",
+            MetaItem::NameValue(ref ident, _) => {
+                format!("{}", ident) == "grammar" || format!("{}", ident) == "grammar_inline"
+            }
             _ => false
         })
+        .map(get_grammar)
         .collect();
 
-    let filename = match grammar.len() {
-        0 => panic!("a grammar file needs to be provided with the #[grammar(\"...\")] attribute"),
-        1 => get_filename(grammar[0]),
-        _ => panic!("only 1 grammar file can be provided")
+    if grammars.is_empty() {
+        panic!(
+            "a grammar needs to be provided with the #[grammar = \"...\"] or \
+             #[grammar_inline = \"...\"] attribute"
+        );
+    }
+
+    let has_word = |word: &str| {
+        ast.attrs.iter().any(|attr| match attr.value {
+            MetaItem::Word(ref ident) => format!("{}", ident) == word,
+            _ => false
+        })
     };
+    let memoize = has_word("memoize");
+    let track_rule_stack = has_word("track_rule_stack");
+
+    let options = ast.attrs
+        .iter()
+        .filter_map(|attr| match attr.value {
+            MetaItem::List(ref ident, ref items) if format!("{}", ident) == "recover" => {
+                Some(get_recovery(items))
+            }
+            _ => None
+        })
+        .fold(
+            Options::new()
+                .with_memoize(memoize)
+                .with_track_rule_stack(track_rule_stack),
+            |options, recovery| options.with_recovery(recovery)
+        );
 
-    (name, filename)
+    (name, grammars, options)
 }
 
-fn get_filename(attr: &Attribute) -> String {
-    if let MetaItem::NameValue(_, ref lit) = attr.value {
+fn get_grammar(attr: &Attribute) -> GrammarSource {
+    if let MetaItem::NameValue(ref ident, ref lit) = attr.value {
         if let &Lit::Str(ref string, _) = lit {
-            string.clone()
+            if format!("{}", ident) == "grammar" {
+                GrammarSource::File(string.clone())
+            } else {
+                GrammarSource::Inline(string.clone())
+            }
         } else {
-            panic!("grammar attribute must be a string")
+            panic!("{} attribute must be a string", ident)
         }
     } else {
         unreachable!();
     }
 }
 
+fn get_recovery(items: &[NestedMetaItem]) -> Recovery {
+    let get = |key: &str| {
+        items
+            .iter()
+            .filter_map(|item| match *item {
+                NestedMetaItem::MetaItem(MetaItem::NameValue(ref ident, Lit::Str(ref string, _)))
+                    if format!("{}", ident) == key =>
+                {
+                    Some(string.clone())
+                }
+                _ => None
+            })
+            .next()
+            .unwrap_or_else(|| panic!("recover attribute needs a {} string", key))
+    };
+
+    Recovery {
+        rule: get("rule"),
+        error: get("error"),
+        sync: get("sync")
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::parse_derive;
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Write;
+
+    use pest_generator::{GrammarFiles, Recovery};
+
+    use super::{compile_errors, dependencies, parse_derive, GrammarSource};
+
+    #[test]
+    fn compile_errors_all() {
+        let mut files = GrammarFiles::new();
+        files.add_source("src/grammar.pest", "a = { b ~ c }", "src").unwrap();
+
+        let errors = pest_meta::parse_and_optimize(files.source()).unwrap_err();
+        let tokens = compile_errors(&files, &errors);
+
+        assert_eq!(
+            tokens.as_ref(),
+            concat!(
+                r#"compile_error ! ( "src/grammar.pest:1:7: rule b is undefined\n  |\n"#,
+                r#"1 | a = { b ~ c }\n  |       ^\n  |" ) ; "#,
+                r#"compile_error ! ( "src/grammar.pest:1:11: rule c is undefined\n  |\n"#,
+                r#"1 | a = { b ~ c }\n  |           ^\n  |" ) ;"#
+            )
+        );
+    }
+
+    #[test]
+    fn dependencies_of_imports() {
+        let dir = env::temp_dir();
+        let path = dir.join("pest_derive_dependencies.pest");
+        File::create(&path)
+            .unwrap()
+            .write_all(b"a = { \"a\" }")
+            .unwrap();
+
+        let mut files = GrammarFiles::new();
+        files
+            .add_source("grammar_inline", "import \"pest_derive_dependencies.pest\"", &dir)
+            .unwrap();
+
+        let path = fs::canonicalize(&path).unwrap();
+        let path = path.to_str().unwrap();
+
+        assert_eq!(
+            dependencies(&files),
+            quote! {
+                const _: &str = include_str!(#path);
+            }
+        );
+    }
 
     #[test]
     fn derive_ok() {
@@ -370,13 +645,24 @@ mod tests {
             #[grammar = \"myfile.pest\"]
             pub struct MyParser<'a, T>;
         ";
-        let (_, filename) = parse_derive(definition.to_owned());
+        let (_, grammars, _) = parse_derive(definition.to_owned());
+
+        assert_eq!(grammars, vec![GrammarSource::File("myfile.pest".to_owned())]);
+    }
+
+    #[test]
+    fn derive_inline_ok() {
+        let definition = "
+            #[other_attr]
+            #[grammar_inline = \"a = { \\\"a\\\" }\"]
+            pub struct MyParser<'a, T>;
+        ";
+        let (_, grammars, _) = parse_derive(definition.to_owned());
 
-        assert_eq!(filename, "myfile.pest");
+        assert_eq!(grammars, vec![GrammarSource::Inline("a = { \"a\" }".to_owned())]);
     }
 
     #[test]
-    #[should_panic(expected = "only 1 grammar file can be provided")]
     fn derive_multiple_grammars() {
         let definition = "
             #[other_attr]
@@ -384,6 +670,89 @@ mod tests {
             #[grammar = \"myfile2.pest\"]
             pub struct MyParser<'a, T>;
         ";
+        let (_, grammars, _) = parse_derive(definition.to_owned());
+
+        assert_eq!(
+            grammars,
+            vec![
+                GrammarSource::File("myfile1.pest".to_owned()),
+                GrammarSource::File("myfile2.pest".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn derive_memoize() {
+        let definition = "
+            #[grammar = \"myfile.pest\"]
+            #[memoize]
+            pub struct MyParser<'a, T>;
+        ";
+        let (_, _, options) = parse_derive(definition.to_owned());
+
+        assert!(options.memoize());
+
+        let definition = "
+            #[grammar = \"myfile.pest\"]
+            pub struct MyParser<'a, T>;
+        ";
+        let (_, _, options) = parse_derive(definition.to_owned());
+
+        assert!(!options.memoize());
+    }
+
+    #[test]
+    fn derive_track_rule_stack() {
+        let definition = "
+            #[grammar = \"myfile.pest\"]
+            #[track_rule_stack]
+            pub struct MyParser<'a, T>;
+        ";
+        let (_, _, options) = parse_derive(definition.to_owned());
+
+        assert!(options.track_rule_stack());
+        assert!(!options.memoize());
+    }
+
+    #[test]
+    fn derive_recover() {
+        let definition = "
+            #[grammar = \"myfile.pest\"]
+            #[recover(rule = \"statement\", error = \"error\", sync = \";\")]
+            pub struct MyParser<'a, T>;
+        ";
+        let (_, _, options) = parse_derive(definition.to_owned());
+
+        assert_eq!(
+            options.recoveries(),
+            &[
+                Recovery {
+                    rule: "statement".to_owned(),
+                    error: "error".to_owned(),
+                    sync: ";".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "recover attribute needs a sync string")]
+    fn derive_recover_without_sync() {
+        let definition = "
+            #[grammar = \"myfile.pest\"]
+            #[recover(rule = \"statement\", error = \"error\")]
+            pub struct MyParser<'a, T>;
+        ";
+        parse_derive(definition.to_owned());
+    }
+
+    #[test]
+    #[should_panic(expected = "a grammar needs to be provided")]
+    fn derive_no_grammar() {
+        let definition = "
+            #[other_attr]
+            pub struct MyParser<'a, T>;
+        ";
         parse_derive(definition.to_owned());
     }
 
@@ -397,4 +766,32 @@ mod tests {
         ";
         parse_derive(definition.to_owned());
     }
+
+    #[test]
+    fn derive_file_and_inline_grammars() {
+        let definition = "
+            #[grammar = \"myfile.pest\"]
+            #[grammar_inline = \"a = { \\\"a\\\" }\"]
+            pub struct MyParser<'a, T>;
+        ";
+        let (_, grammars, _) = parse_derive(definition.to_owned());
+
+        assert_eq!(
+            grammars,
+            vec![
+                GrammarSource::File("myfile.pest".to_owned()),
+                GrammarSource::Inline("a = { \"a\" }".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "grammar_inline attribute must be a string")]
+    fn derive_inline_wrong_arg() {
+        let definition = "
+            #[grammar_inline = 1]
+            pub struct MyParser<'a, T>;
+        ";
+        parse_derive(definition.to_owned());
+    }
 }
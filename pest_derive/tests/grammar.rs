@@ -40,6 +40,18 @@ fn insensitive() {
     };
 }
 
+#[test]
+fn insensitive_unicode() {
+    parses_to! {
+        parser: GrammarParser,
+        input: "STRASSE",
+        rule: Rule::insensitive_unicode,
+        tokens: [
+            insensitive_unicode(0, 7)
+        ]
+    };
+}
+
 #[test]
 fn range() {
     parses_to! {
@@ -52,6 +64,18 @@ fn range() {
     };
 }
 
+#[test]
+fn insensitive_range() {
+    parses_to! {
+        parser: GrammarParser,
+        input: "B",
+        rule: Rule::insensitive_range,
+        tokens: [
+            insensitive_range(0, 1)
+        ]
+    };
+}
+
 #[test]
 fn ident() {
     parses_to! {
@@ -707,3 +731,117 @@ fn pop_fail() {
         ]
     };
 }
+
+#[test]
+fn peek_all() {
+    parses_to! {
+        parser: GrammarParser,
+        input: "0110",
+        rule: Rule::peek_all_,
+        tokens: [
+            peek_all_(0, 4, [
+                range(0, 1),
+                range(1, 2)
+            ])
+        ]
+    };
+}
+
+#[test]
+fn pop_all() {
+    parses_to! {
+        parser: GrammarParser,
+        input: "0110",
+        rule: Rule::pop_all_,
+        tokens: [
+            pop_all_(0, 4, [
+                range(0, 1),
+                range(1, 2)
+            ])
+        ]
+    };
+}
+
+#[test]
+fn drop() {
+    parses_to! {
+        parser: GrammarParser,
+        input: "010",
+        rule: Rule::drop_,
+        tokens: [
+            drop_(0, 3, [
+                range(0, 1),
+                range(1, 2)
+            ])
+        ]
+    };
+}
+
+#[test]
+fn peek_slice_23() {
+    parses_to! {
+        parser: GrammarParser,
+        input: "0123412",
+        rule: Rule::peek_slice_23,
+        tokens: [
+            peek_slice_23(0, 7, [
+                range(0, 1),
+                range(1, 2),
+                range(2, 3),
+                range(3, 4),
+                range(4, 5)
+            ])
+        ]
+    };
+}
+
+#[test]
+fn push_backtrack() {
+    parses_to! {
+        parser: GrammarParser,
+        input: "010",
+        rule: Rule::push_backtrack,
+        tokens: [
+            push_backtrack(0, 3, [
+                range(0, 1),
+                range(1, 2)
+            ])
+        ]
+    };
+}
+
+#[test]
+fn ascii_digits() {
+    parses_to! {
+        parser: GrammarParser,
+        input: "6 90",
+        rule: Rule::ascii_digits,
+        tokens: [
+            ascii_digits(0, 4)
+        ]
+    };
+}
+
+#[test]
+fn newline() {
+    parses_to! {
+        parser: GrammarParser,
+        input: "\n\r\n\r",
+        rule: Rule::newline,
+        tokens: [
+            newline(0, 4)
+        ]
+    };
+}
+
+#[test]
+fn unicode() {
+    parses_to! {
+        parser: GrammarParser,
+        input: "নামে",
+        rule: Rule::unicode,
+        tokens: [
+            unicode(0, 12)
+        ]
+    };
+}
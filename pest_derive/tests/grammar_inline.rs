@@ -0,0 +1,43 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+#[macro_use]
+extern crate pest;
+#[macro_use]
+extern crate pest_derive;
+
+use pest::Parser;
+
+#[derive(Parser)]
+#[grammar_inline = "
+number = @{ '0'..'9'+ }
+list = { number ~ (\",\" ~ number)* }
+whitespace = _{ \" \" }
+"]
+struct ListParser;
+
+#[test]
+fn list() {
+    parses_to! {
+        parser: ListParser,
+        input: "1, 23",
+        rule: Rule::list,
+        tokens: [
+            list(0, 5, [
+                number(0, 1),
+                number(3, 5)
+            ])
+        ]
+    };
+}
+
+#[test]
+fn list_error() {
+    assert!(ListParser::parse(Rule::list, ",").is_err());
+}
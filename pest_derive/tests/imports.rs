@@ -0,0 +1,97 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+#[macro_use]
+extern crate pest;
+#[macro_use]
+extern crate pest_derive;
+
+mod imported {
+    #[derive(Parser)]
+    #[grammar = "../tests/imports.pest"]
+    pub struct ImportParser;
+}
+
+mod dialect {
+    #[derive(Parser)]
+    #[grammar = "../tests/dialect.pest"]
+    pub struct DialectParser;
+}
+
+mod merged {
+    #[derive(Parser)]
+    #[grammar = "../tests/lexical.pest"]
+    #[grammar_inline = "list = { number ~ (\",\" ~ number)* }"]
+    pub struct MergedParser;
+}
+
+#[test]
+fn import() {
+    use imported::{ImportParser, Rule};
+
+    parses_to! {
+        parser: ImportParser,
+        input: "a_b = 12",
+        rule: Rule::assignment,
+        tokens: [
+            assignment(0, 8, [
+                identifier(0, 3),
+                number(6, 8)
+            ])
+        ]
+    };
+}
+
+#[test]
+fn override_rule() {
+    use dialect::{DialectParser, Rule};
+
+    parses_to! {
+        parser: DialectParser,
+        input: "a = -12",
+        rule: Rule::assignment,
+        tokens: [
+            assignment(0, 7, [
+                identifier(0, 1),
+                number(4, 7)
+            ])
+        ]
+    };
+}
+
+#[test]
+fn base_rule() {
+    use imported::{ImportParser, Rule};
+
+    fails_with! {
+        parser: ImportParser,
+        input: "a = -12",
+        rule: Rule::assignment,
+        positives: vec![Rule::number],
+        negatives: vec![],
+        pos: 4
+    };
+}
+
+#[test]
+fn merged() {
+    use merged::{MergedParser, Rule};
+
+    parses_to! {
+        parser: MergedParser,
+        input: "1, 23",
+        rule: Rule::list,
+        tokens: [
+            list(0, 5, [
+                number(0, 1),
+                number(3, 5)
+            ])
+        ]
+    };
+}
@@ -0,0 +1,61 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+extern crate pest;
+#[macro_use]
+extern crate pest_derive;
+
+mod memoized {
+    #[derive(Parser)]
+    #[grammar_inline = "
+        choice = { b | c }
+        values = { a ~ \"1\" | a ~ \"2\" }
+        a = { \"x\" }
+        b = { a ~ \"b\" }
+        c = { a ~ \"c\" }
+    "]
+    #[memoize]
+    pub struct MemoizedParser;
+}
+
+mod plain {
+    #[derive(Parser)]
+    #[grammar_inline = "
+        choice = { b | c }
+        values = { a ~ \"1\" | a ~ \"2\" }
+        a = { \"x\" }
+        b = { a ~ \"b\" }
+        c = { a ~ \"c\" }
+    "]
+    pub struct PlainParser;
+}
+
+use pest::Parser;
+
+use memoized::MemoizedParser;
+use plain::PlainParser;
+
+#[test]
+fn same_pairs() {
+    let memoized = MemoizedParser::parse(memoized::Rule::values, "x2").unwrap();
+    let plain = PlainParser::parse(plain::Rule::values, "x2").unwrap();
+
+    assert_eq!(format!("{}", memoized), format!("{}", plain));
+    assert_eq!(format!("{}", memoized), "[values(0, 2, [a(0, 1)])]");
+}
+
+#[test]
+fn same_errors() {
+    for input in &["y", "x", "xd"] {
+        let memoized = MemoizedParser::parse(memoized::Rule::choice, input).unwrap_err();
+        let plain = PlainParser::parse(plain::Rule::choice, input).unwrap_err();
+
+        assert_eq!(format!("{}", memoized), format!("{}", plain));
+    }
+}
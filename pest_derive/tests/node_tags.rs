@@ -0,0 +1,69 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+#[macro_use]
+extern crate pest;
+#[macro_use]
+extern crate pest_derive;
+
+use pest::Parser;
+
+#[derive(Parser)]
+#[grammar_inline = "
+number = @{ '0'..'9'+ }
+op = { \"+\" | \"-\" }
+term = _{ number | \"(\" ~ expr ~ \")\" }
+expr = { #lhs = term ~ (op ~ #rhs = term)* }
+pair = ${ #key = number ~ \":\" ~ #value = number }
+whitespace = _{ \" \" }
+"]
+struct TagParser;
+
+#[test]
+fn tags() {
+    parses_to! {
+        parser: TagParser,
+        input: "1 + (2 - 3)",
+        rule: Rule::expr,
+        tokens: [
+            expr(0, 11, [
+                number(0, 1, #lhs),
+                op(2, 3),
+                expr(5, 10, #rhs, [
+                    number(5, 6, #lhs),
+                    op(7, 8),
+                    number(9, 10, #rhs)
+                ])
+            ])
+        ]
+    };
+}
+
+#[test]
+fn tags_compound_atomic() {
+    parses_to! {
+        parser: TagParser,
+        input: "1:23",
+        rule: Rule::pair,
+        tokens: [
+            pair(0, 4, [
+                number(0, 1, #key),
+                number(2, 4, #value)
+            ])
+        ]
+    };
+}
+
+#[test]
+fn find_tagged() {
+    let pairs = TagParser::parse(Rule::expr, "1 + 2 - 3").unwrap();
+    let rhs: Vec<_> = pairs.find_tagged("rhs").map(|pair| pair.as_str()).collect();
+
+    assert_eq!(rhs, vec!["2", "3"]);
+}
@@ -0,0 +1,50 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+#[macro_use]
+extern crate pest;
+#[macro_use]
+extern crate pest_derive;
+
+use pest::Parser;
+
+#[derive(Parser)]
+#[grammar_inline = "
+list(item, separator) = _{ item ~ (separator ~ item)* }
+delimited(open, item, close) = _{ open ~ list(item, \",\")? ~ close }
+
+number = @{ '0'..'9'+ }
+array = { delimited(\"[\", value, \"]\") }
+value = _{ number | array }
+whitespace = _{ \" \" }
+"]
+struct ArrayParser;
+
+#[test]
+fn array() {
+    parses_to! {
+        parser: ArrayParser,
+        input: "[1, [], [23]]",
+        rule: Rule::array,
+        tokens: [
+            array(0, 13, [
+                number(1, 2),
+                array(4, 6),
+                array(8, 12, [
+                    number(9, 11)
+                ])
+            ])
+        ]
+    };
+}
+
+#[test]
+fn array_error() {
+    assert!(ArrayParser::parse(Rule::array, "[1,]").is_err());
+}
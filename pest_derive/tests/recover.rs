@@ -0,0 +1,55 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+extern crate pest;
+#[macro_use]
+extern crate pest_derive;
+
+use pest::Parser;
+
+#[derive(Parser)]
+#[grammar_inline = "
+    statements = { soi ~ (statement ~ \";\")* ~ eoi }
+    statement = { \"a\" }
+    error = { (!\";\" ~ any)* }
+"]
+#[recover(rule = "statement", error = "error", sync = ";")]
+struct RecoveringParser;
+
+#[test]
+fn recovered_errors() {
+    let (pairs, errors) = RecoveringParser::parse_recovering(Rule::statements, "a;bb;a;c;");
+
+    assert_eq!(
+        format!("{}", pairs),
+        "[statements(0, 9, [statement(0, 1), error(2, 4), statement(5, 6), error(7, 8)])]"
+    );
+    assert_eq!(
+        errors.iter().map(|error| format!("{}", error)).collect::<Vec<_>>(),
+        vec![
+            " --> 1:3\n  |\n1 | a;bb;a;c;\n  |   ^---\n  |\n  = expected statement",
+            " --> 1:8\n  |\n1 | a;bb;a;c;\n  |        ^---\n  |\n  = expected statement",
+        ]
+    );
+}
+
+#[test]
+fn parse_fails_with_first_error() {
+    let error = RecoveringParser::parse(Rule::statements, "b;c;").unwrap_err();
+
+    assert!(format!("{}", error).starts_with(" --> 1:1\n"));
+}
+
+#[test]
+fn no_errors() {
+    let (pairs, errors) = RecoveringParser::parse_recovering(Rule::statements, "a;a;");
+
+    assert_eq!(format!("{}", pairs), "[statements(0, 4, [statement(0, 1), statement(2, 3)])]");
+    assert!(errors.is_empty());
+}
@@ -12,6 +12,8 @@ extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
+use pest::{Error, Parser};
+
 #[derive(Parser)]
 #[grammar = "../tests/reporting.pest"]
 struct ReportingParser;
@@ -123,3 +125,23 @@ fn mixed_progress() {
         pos: 1
     };
 }
+
+#[test]
+fn literals() {
+    let error = ReportingParser::parse(Rule::literals, "a}").unwrap_err();
+
+    match error {
+        Error::ParsingError {
+            ref positives,
+            ref literals,
+            ref pos,
+            ..
+        } => {
+            assert_eq!(*positives, vec![]);
+            assert_eq!(*literals, vec!["`,`", "`]`"]);
+            assert_eq!(pos.pos(), 1);
+        }
+        _ => unreachable!()
+    };
+    assert!(format!("{}", error).ends_with("= expected `,` or `]`"));
+}
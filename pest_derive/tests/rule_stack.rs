@@ -0,0 +1,48 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+extern crate pest;
+#[macro_use]
+extern crate pest_derive;
+
+use pest::{Error, Parser};
+
+#[derive(Parser)]
+#[grammar_inline = "
+    array = { \"[\" ~ value ~ (\",\" ~ value)* ~ \"]\" }
+    value = { array | number }
+    number = @{ '0'..'9'+ }
+"]
+#[track_rule_stack]
+struct TrackingParser;
+
+#[test]
+fn rule_stack() {
+    let error = TrackingParser::parse(Rule::array, "[1,[2,]]").unwrap_err();
+
+    match error {
+        Error::ParsingError {
+            positives,
+            rule_stack,
+            pos,
+            ..
+        } => {
+            assert_eq!(positives, vec![Rule::array, Rule::value, Rule::number]);
+            assert_eq!(pos.pos(), 6);
+            assert_eq!(
+                rule_stack
+                    .iter()
+                    .map(|&(rule, ref pos)| (rule, pos.pos()))
+                    .collect::<Vec<_>>(),
+                vec![(Rule::array, 0), (Rule::value, 3), (Rule::array, 3)]
+            );
+        }
+        _ => unreachable!()
+    };
+}
@@ -0,0 +1,541 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use pest::{Error, LineIndex, Parser, Position, Renderer};
+use pest_meta::parser::{self, GrammarParser, GrammarRule};
+use pest_meta::validator;
+
+/// A `struct` which concatenates grammars and the files they `import` into a single source that
+/// can be passed to [`parse_and_optimize`](../pest_meta/fn.parse_and_optimize.html). It
+/// remembers where every grammar starts so that errors are reported in the right file.
+///
+/// Imported files are added before the grammar importing them, and every file is only added
+/// once, so a shared grammar can be imported by many others.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate pest_generator;
+/// # extern crate pest_meta;
+/// # use pest_generator::GrammarFiles;
+/// # fn main() {
+/// let mut files = GrammarFiles::new();
+/// files.add_source("a.pest", "a = { b }", ".").unwrap();
+/// files.add_source("b.pest", "b = { \"b\" } b = { c }", ".").unwrap();
+///
+/// let errors = pest_meta::parse_and_optimize(files.source()).unwrap_err();
+///
+/// assert!(files.format_error(&errors[0]).starts_with("b.pest:1:13: rule b already defined"));
+/// assert!(files.format_error(&errors[0]).ends_with("= note: first defined at b.pest:1:1"));
+/// assert!(files.format_error(&errors[1]).starts_with("b.pest:1:19: rule c is undefined"));
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct GrammarFiles {
+    source: String,
+    files: Vec<GrammarFile>,
+    added: HashSet<PathBuf>,
+    paths: Vec<PathBuf>
+}
+
+#[derive(Debug)]
+struct GrammarFile {
+    name: PathBuf,
+    start: usize
+}
+
+impl GrammarFiles {
+    /// Creates an empty `GrammarFiles`.
+    pub fn new() -> GrammarFiles {
+        GrammarFiles::default()
+    }
+
+    /// Reads the grammar at `path` and adds it after the files it imports, which are looked up
+    /// relative to its directory. Does nothing if the file was already added.
+    ///
+    /// # Errors
+    ///
+    /// Returns the `io::Error` of reading the file or any of the files it imports, prefixed with
+    /// the path of the file that could not be read.
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let canonical = fs::canonicalize(path).map_err(|error| with_path(path, error))?;
+
+        if !self.added.insert(canonical.clone()) {
+            return Ok(());
+        }
+
+        self.paths.push(canonical);
+
+        let mut data = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut data))
+            .map_err(|error| with_path(path, error))?;
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        self.add_source(path, &data, dir)
+    }
+
+    /// Adds the grammar `source` after the files it imports, which are looked up relative to
+    /// `dir`. Errors in `source` are reported in `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the `io::Error` of reading any of the imported files.
+    pub fn add_source<P: AsRef<Path>, Q: AsRef<Path>>(
+        &mut self,
+        name: P,
+        source: &str,
+        dir: Q
+    ) -> io::Result<()> {
+        for import in imports(source) {
+            self.add_file(dir.as_ref().join(import))?;
+        }
+
+        if !self.source.is_empty() {
+            self.source.push('\n');
+        }
+
+        self.files.push(GrammarFile {
+            name: name.as_ref().to_owned(),
+            start: self.source.len()
+        });
+        self.source.push_str(source);
+
+        Ok(())
+    }
+
+    /// Returns the canonical paths of the files that were read, i.e. those added with
+    /// [`add_file`](#method.add_file) and the files they import, in the order they were read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate pest_generator;
+    /// # use std::env;
+    /// # use std::fs::{self, File};
+    /// # use std::io::Write;
+    /// # use pest_generator::GrammarFiles;
+    /// # fn main() {
+    /// let dir = env::temp_dir();
+    /// let path = dir.join("pest_generator_paths_doc.pest");
+    /// File::create(&path).unwrap().write_all(b"a = { \"a\" }").unwrap();
+    ///
+    /// let mut files = GrammarFiles::new();
+    /// let source = "import \"pest_generator_paths_doc.pest\"";
+    /// files.add_source("grammar_inline", source, &dir).unwrap();
+    ///
+    /// assert_eq!(files.paths(), &[fs::canonicalize(&path).unwrap()]);
+    /// # }
+    /// ```
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Returns the concatenated source of all the grammars.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the lines of the `//!` doc comments of all the grammars, in the order of
+    /// [`source`](#method.source), or nothing if the grammars do not parse.
+    pub fn doc(&self) -> Vec<String> {
+        match GrammarParser::parse(GrammarRule::grammar_rules, &self.source) {
+            Ok(pairs) => parser::consume_grammar_doc(pairs),
+            Err(_) => vec![]
+        }
+    }
+
+    /// Formats an `error` found in [`source`](#method.source) like
+    /// [`format_error`](fn.format_error.html), using the name of the grammar it was found in and
+    /// counting lines from the start of that grammar. Errors about a rule which is already
+    /// defined end with a note on where it was first defined.
+    pub fn format_error(&self, error: &Error<GrammarRule>) -> String {
+        let pos = position(error);
+        let (file, text) = self.file_at(pos);
+        let end = file.start + text.len();
+        let at = |pos: usize| {
+            let offset = pos.min(end).saturating_sub(file.start);
+
+            Position::from_start(text)
+                .skip(text[..offset].chars().count())
+                .unwrap()
+        };
+        let notes: Vec<_> = self.first_definition(error)
+            .map(|pos| {
+                let (file, (line, col)) = self.line_col(pos);
+
+                format!("note: first defined at {}:{}:{}", file.name.display(), line, col)
+            })
+            .into_iter()
+            .collect();
+
+        // The error is moved into the grammar's own text, so that lines are counted from its
+        // start.
+        let error = match error.clone().renamed_rules(parser::rename_meta_rule) {
+            Error::CustomErrorSpan { message, span } => Error::CustomErrorSpan {
+                message,
+                span: at(span.start()).span(&at(span.end()))
+            },
+            error => Error::CustomErrorPos {
+                message: message(&error),
+                pos: at(pos)
+            }
+        };
+        let index = LineIndex::new(text);
+        let (line, col) = index.line_col(position(&error));
+
+        format_rendered(
+            &file.name,
+            (line, col),
+            &message(&error),
+            &Renderer::new().render_with(&error, &index),
+            &notes
+        )
+    }
+
+    // Returns the grammar containing the byte offset `pos` of the source, along with its text.
+    fn file_at(&self, pos: usize) -> (&GrammarFile, &str) {
+        let index = self.files
+            .iter()
+            .rposition(|file| file.start <= pos)
+            .expect("error outside of the grammar files");
+        let file = &self.files[index];
+
+        // Grammars are separated by a new line, which belongs to neither of them.
+        let end = match self.files.get(index + 1) {
+            Some(next) => next.start - 1,
+            None => self.source.len()
+        };
+
+        (file, &self.source[file.start..end])
+    }
+
+    fn line_col(&self, pos: usize) -> (&GrammarFile, (usize, usize)) {
+        let (file, text) = self.file_at(pos);
+
+        (file, LineIndex::new(text).line_col(pos - file.start))
+    }
+
+    // Returns the byte offset of the first definition of the rule `error` reports as already
+    // defined, if it was found in the source.
+    fn first_definition(&self, error: &Error<GrammarRule>) -> Option<usize> {
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, &self.source).ok()?;
+
+        validator::first_definition(pairs, error).map(|span| span.start())
+    }
+}
+
+/// Formats an `error` found in the grammar at `path`. The first line reads
+/// `path:line:col: message`, which editors and terminals can link to, and is followed by the
+/// annotated line of the grammar.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate pest_generator;
+/// # extern crate pest_meta;
+/// # fn main() {
+/// let errors = pest_meta::parse_and_optimize("a = { b }").unwrap_err();
+///
+/// assert_eq!(
+///     pest_generator::format_error("src/grammar.pest", &errors[0]),
+///     [
+///         "src/grammar.pest:1:7: rule b is undefined",
+///         "  |",
+///         "1 | a = { b }",
+///         "  |       ^",
+///         "  |"
+///     ].join("\n")
+/// );
+/// # }
+/// ```
+pub fn format_error<P: AsRef<Path>>(path: P, error: &Error<GrammarRule>) -> String {
+    let error = error.clone().renamed_rules(parser::rename_meta_rule);
+    let line_col = match error {
+        Error::ParsingError { ref pos, .. } | Error::CustomErrorPos { ref pos, .. } => {
+            pos.line_col()
+        }
+        Error::CustomErrorSpan { ref span, .. } => span.start_pos().line_col()
+    };
+
+    format_rendered(
+        path.as_ref(),
+        line_col,
+        &message(&error),
+        &Renderer::new().render(&error),
+        &[]
+    )
+}
+
+// Replaces the `-->` line and the message at the end of an error `rendered` by `Renderer` with a
+// `path:line:col: message` line in front of its annotated lines, which are followed by `notes`.
+fn format_rendered(
+    path: &Path,
+    (line, col): (usize, usize),
+    message: &str,
+    rendered: &str,
+    notes: &[String]
+) -> String {
+    let lines: Vec<_> = rendered.lines().collect();
+    let end = lines.iter().rposition(|line| line.trim() == "|").unwrap();
+    let spacing = lines[end].trim_end_matches('|');
+
+    let mut result = format!(
+        "{}:{}:{}: {}\n{}",
+        path.display(),
+        line,
+        col,
+        message,
+        lines[1..end + 1].join("\n")
+    );
+
+    for note in notes {
+        result.push_str(&format!("\n{}= {}", spacing, note));
+    }
+
+    result
+}
+
+// Returns the message of an `error` whose rules were renamed, which makes it a custom error.
+fn message(error: &Error<GrammarRule>) -> String {
+    match *error {
+        Error::CustomErrorPos { ref message, .. } | Error::CustomErrorSpan { ref message, .. } => {
+            message.to_owned()
+        }
+        Error::ParsingError { .. } => unreachable!()
+    }
+}
+
+fn position(error: &Error<GrammarRule>) -> usize {
+    match *error {
+        Error::ParsingError { ref pos, .. } | Error::CustomErrorPos { ref pos, .. } => pos.pos(),
+        Error::CustomErrorSpan { ref span, .. } => span.start()
+    }
+}
+
+fn imports(source: &str) -> Vec<String> {
+    // Grammars which do not parse are added without following their imports. The syntax error
+    // is reported once all of the grammars are parsed together.
+    match GrammarParser::parse(GrammarRule::grammar_rules, source) {
+        Ok(pairs) => pairs
+            .filter(|pair| pair.as_rule() == GrammarRule::import)
+            .map(|pair| {
+                let string = pair.into_inner().next().unwrap().as_str();
+
+                string[1..string.len() - 1].to_owned()
+            })
+            .collect(),
+        Err(_) => vec![]
+    }
+}
+
+fn with_path(path: &Path, error: io::Error) -> io::Error {
+    io::Error::new(error.kind(), format!("{}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    fn write_grammars(dir: &str, grammars: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join(dir);
+        fs::create_dir_all(dir.join("lexical")).unwrap();
+
+        for &(name, grammar) in grammars {
+            fs::write(dir.join(name), grammar).unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn imports_once() {
+        let dir = write_grammars(
+            "pest_generator_imports_once",
+            &[
+                ("a.pest", "import \"b.pest\"\nimport \"lexical/c.pest\"\na = { b ~ c }"),
+                ("b.pest", "import \"lexical/c.pest\"\nb = { c }"),
+                ("lexical/c.pest", "c = { \"c\" }")
+            ]
+        );
+        let mut files = GrammarFiles::new();
+        files.add_file(dir.join("a.pest")).unwrap();
+
+        assert_eq!(
+            files.source(),
+            "c = { \"c\" }\nimport \"lexical/c.pest\"\nb = { c }\nimport \"b.pest\"\n\
+             import \"lexical/c.pest\"\na = { b ~ c }"
+        );
+        assert!(pest_meta::parse_and_optimize(files.source()).is_ok());
+    }
+
+    #[test]
+    fn imports_cycle() {
+        let dir = write_grammars(
+            "pest_generator_imports_cycle",
+            &[
+                ("a.pest", "import \"b.pest\"\na = { b }"),
+                ("b.pest", "import \"a.pest\"\nb = { \"b\" ~ a? }")
+            ]
+        );
+        let mut files = GrammarFiles::new();
+        files.add_file(dir.join("a.pest")).unwrap();
+
+        assert_eq!(files.files.len(), 2);
+        assert!(pest_meta::parse_and_optimize(files.source()).is_ok());
+    }
+
+    #[test]
+    fn import_missing() {
+        let dir = write_grammars(
+            "pest_generator_import_missing",
+            &[("a.pest", "import \"missing.pest\"\na = { b }")]
+        );
+        let mut files = GrammarFiles::new();
+        let error = files.add_file(dir.join("a.pest")).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(format!("{}", error).starts_with(&format!(
+            "{}:",
+            dir.join("missing.pest").display()
+        )));
+    }
+
+    #[test]
+    fn doc_of_files() {
+        let mut files = GrammarFiles::new();
+        files.add_source("a.pest", "//! Grammar a.\n/// Rule a.\na = { \"a\" }", ".").unwrap();
+        files.add_source("b.pest", "//! Grammar b.\nb = { \"b\" }", ".").unwrap();
+
+        assert_eq!(files.doc(), vec![" Grammar a.", " Grammar b."]);
+    }
+
+    #[test]
+    fn format_errors_in_files() {
+        let mut files = GrammarFiles::new();
+        files.add_source("a.pest", "a = { b }\n\n", ".").unwrap();
+        files.add_source("b.pest", "b = { \"b\" }\n\nc = { d }", ".").unwrap();
+
+        let errors = pest_meta::parse_and_optimize(files.source()).unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            files.format_error(&errors[0]),
+            [
+                "b.pest:3:7: rule d is undefined",
+                "  |",
+                "3 | c = { d }",
+                "  |       ^",
+                "  |"
+            ].join("\n")
+        );
+    }
+
+    #[test]
+    fn format_already_defined_in_other_file() {
+        let mut files = GrammarFiles::new();
+        files.add_source("lexical.pest", "\nident = { \"a\" }", ".").unwrap();
+        files.add_source("sql.pest", "a = { ident }\n\nident = { \"b\" }", ".").unwrap();
+
+        let errors = pest_meta::parse_and_optimize(files.source()).unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            files.format_error(&errors[0]),
+            [
+                "sql.pest:3:1: rule ident already defined",
+                "  |",
+                "3 | ident = { \"b\" }",
+                "  | ^---^",
+                "  |",
+                "  = note: first defined at lexical.pest:2:1"
+            ].join("\n")
+        );
+    }
+
+    #[test]
+    fn format_errors_line_width() {
+        let mut files = GrammarFiles::new();
+        files.add_source("a.pest", &"\n".repeat(10), ".").unwrap();
+        files.add_source("b.pest", "b = { c }", ".").unwrap();
+
+        let errors = pest_meta::parse_and_optimize(files.source()).unwrap_err();
+
+        assert_eq!(
+            files.format_error(&errors[0]),
+            [
+                "b.pest:1:7: rule c is undefined",
+                "  |",
+                "1 | b = { c }",
+                "  |       ^",
+                "  |"
+            ].join("\n")
+        );
+    }
+
+    #[test]
+    fn format_errors_after_multi_byte_chars() {
+        let mut files = GrammarFiles::new();
+        files.add_source("a.pest", "a = { \"ä\" }", ".").unwrap();
+        files.add_source("b.pest", "b = { \"ö\" ~ c }", ".").unwrap();
+
+        let errors = pest_meta::parse_and_optimize(files.source()).unwrap_err();
+
+        assert_eq!(
+            files.format_error(&errors[0]),
+            [
+                "b.pest:1:13: rule c is undefined",
+                "  |",
+                "1 | b = { \"ö\" ~ c }",
+                "  |             ^",
+                "  |"
+            ].join("\n")
+        );
+    }
+
+    #[test]
+    fn format_multi_line_span() {
+        use pest::Position;
+
+        let mut files = GrammarFiles::new();
+        files.add_source("a.pest", &"\n".repeat(10), ".").unwrap();
+        files.add_source("b.pest", "b = {\n  \"b\"\n}", ".").unwrap();
+
+        let start = Position::from_start(files.source()).skip(15).unwrap();
+        let end = start.clone().skip(9).unwrap();
+        let error = Error::CustomErrorSpan {
+            message: "rule b is empty".to_owned(),
+            span: start.span(&end)
+        };
+
+        assert_eq!(
+            files.format_error(&error),
+            [
+                "b.pest:1:5: rule b is empty",
+                "  |",
+                "1 | b = {",
+                "  |     ^",
+                "2 |   \"b\"",
+                "  | -----",
+                "3 | }",
+                "  | ^",
+                "  |"
+            ].join("\n")
+        );
+    }
+}
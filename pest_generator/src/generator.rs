@@ -11,9 +11,115 @@ use std::collections::HashMap;
 
 use quote::{Ident, Tokens};
 
-use ast::*;
+use pest::unicode;
+use pest_meta::ast::*;
+
+/// A `struct` which configures the `Parser` implementation generated by
+/// [`generate`](fn.generate.html). `pest_derive` sets it from attributes like `#[memoize]`.
+///
+/// # Examples
+///
+/// ```
+/// # use pest_generator::Options;
+/// let options = Options::new().with_memoize(true);
+///
+/// assert!(options.memoize());
+/// assert!(!Options::new().memoize());
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Options {
+    memoize: bool,
+    track_rule_stack: bool,
+    recoveries: Vec<Recovery>
+}
+
+/// A `struct` which describes how a rule recovers from its errors with
+/// [`ParserState::recover`](../pest/struct.ParserState.html#method.recover).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Recovery {
+    /// Name of the rule which recovers from its errors
+    pub rule: String,
+    /// Name of the rule whose pairs cover the input skipped after an error
+    pub error: String,
+    /// String at which parsing resumes after an error
+    pub sync: String
+}
+
+impl Options {
+    /// Creates `Options` with every option turned off.
+    pub fn new() -> Options {
+        Options::default()
+    }
 
-pub fn generate(name: Ident, rules: Vec<Rule>, defaults: Vec<Ident>) -> Tokens {
+    /// Sets whether the generated `Parser` memoizes its rules with
+    /// [`ParserState::memoize`](../pest/struct.ParserState.html#method.memoize), consuming the
+    /// `Options`. This avoids parsing the same rule at the same position more than once when
+    /// alternatives start with the same rules, at the cost of storing every result.
+    pub fn with_memoize(mut self, memoize: bool) -> Options {
+        self.memoize = memoize;
+        self
+    }
+
+    /// Returns whether the generated `Parser` memoizes its rules.
+    pub fn memoize(&self) -> bool {
+        self.memoize
+    }
+
+    /// Sets whether the generated `Parser` tracks the stack of rules being run with
+    /// [`ParserState::track_rule_stack`](../pest/struct.ParserState.html#method.track_rule_stack),
+    /// consuming the `Options`. Its errors then report the rules that the failed attempts were
+    /// made inside of.
+    pub fn with_track_rule_stack(mut self, track_rule_stack: bool) -> Options {
+        self.track_rule_stack = track_rule_stack;
+        self
+    }
+
+    /// Returns whether the generated `Parser` tracks the stack of rules being run.
+    pub fn track_rule_stack(&self) -> bool {
+        self.track_rule_stack
+    }
+
+    /// Adds a `recovery`, consuming the `Options`. When its rule fails, the error is recorded and
+    /// the input is skipped up to the next occurrence of its `sync` string, which is covered by a
+    /// pair of its `error` rule. Recorded errors are returned by
+    /// [`Parser::parse_recovering`](../pest/trait.Parser.html#method.parse_recovering).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest_generator::{Options, Recovery};
+    /// let recovery = Recovery {
+    ///     rule: "statement".to_owned(),
+    ///     error: "error".to_owned(),
+    ///     sync: ";".to_owned()
+    /// };
+    /// let options = Options::new().with_recovery(recovery.clone());
+    ///
+    /// assert_eq!(options.recoveries(), &[recovery]);
+    /// ```
+    pub fn with_recovery(mut self, recovery: Recovery) -> Options {
+        self.recoveries.push(recovery);
+        self
+    }
+
+    /// Returns the `Recovery`s of the generated `Parser`'s rules.
+    pub fn recoveries(&self) -> &[Recovery] {
+        &self.recoveries
+    }
+}
+
+/// Generates the `Rule` `enum` and the `Parser` implementation for the `struct` called `name`
+/// from optimized `rules`. `defaults` are the names of the predefined rules the grammar calls,
+/// as returned by [`parse_and_optimize`](../pest_meta/fn.parse_and_optimize.html), and `doc` are
+/// the lines of the grammar's `//!` doc comments, which document the `Rule` `enum`. The
+/// `Parser` is configured by `options`.
+pub fn generate(
+    name: Ident,
+    rules: Vec<Rule>,
+    defaults: Vec<&str>,
+    doc: Vec<String>,
+    options: &Options
+) -> Tokens {
     let mut predefined = HashMap::new();
     predefined.insert(
         "any",
@@ -59,8 +165,19 @@ pub fn generate(name: Ident, rules: Vec<Rule>, defaults: Vec<Ident>) -> Tokens {
                 pos: ::pest::Position<'i>,
                 state: &mut ::pest::ParserState<'i, Rule>
             ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
-                let string = state.stack.last().expect("peek was called on empty stack").as_str();
-                pos.match_string(string)
+                state.stack_peek(pos)
+            }
+        }
+    );
+    predefined.insert(
+        "peek_all",
+        quote! {
+            #[inline]
+            fn peek_all<'i>(
+                pos: ::pest::Position<'i>,
+                state: &mut ::pest::ParserState<'i, Rule>
+            ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
+                state.stack_peek_all(pos)
             }
         }
     );
@@ -72,54 +189,165 @@ pub fn generate(name: Ident, rules: Vec<Rule>, defaults: Vec<Ident>) -> Tokens {
                 pos: ::pest::Position<'i>,
                 state: &mut ::pest::ParserState<'i, Rule>
             ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
-                let pos = {
-                    let string = state.stack.last()
-                                      .expect("pop was called on empty stack").as_str();
-
-                    pos.match_string(string)
-                };
-
-                if pos.is_ok() {
-                    state.stack.pop().unwrap();
-                }
-
-                pos
+                state.stack_pop(pos)
             }
         }
     );
+    predefined.insert(
+        "pop_all",
+        quote! {
+            #[inline]
+            fn pop_all<'i>(
+                pos: ::pest::Position<'i>,
+                state: &mut ::pest::ParserState<'i, Rule>
+            ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
+                state.stack_pop_all(pos)
+            }
+        }
+    );
+    predefined.insert(
+        "drop",
+        quote! {
+            #[inline]
+            fn drop<'i>(
+                pos: ::pest::Position<'i>,
+                state: &mut ::pest::ParserState<'i, Rule>
+            ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
+                state.stack_drop(pos)
+            }
+        }
+    );
+
+    let character_classes = vec![
+        ("ASCII_DIGIT", quote! { pos.match_range('0'..'9') }),
+        ("ASCII_NONZERO_DIGIT", quote! { pos.match_range('1'..'9') }),
+        ("ASCII_BIN_DIGIT", quote! { pos.match_range('0'..'1') }),
+        ("ASCII_OCT_DIGIT", quote! { pos.match_range('0'..'7') }),
+        (
+            "ASCII_HEX_DIGIT",
+            quote! {
+                pos.match_range('0'..'9')
+                    .or_else(|pos| pos.match_range('a'..'f'))
+                    .or_else(|pos| pos.match_range('A'..'F'))
+            }
+        ),
+        ("ASCII_ALPHA_LOWER", quote! { pos.match_range('a'..'z') }),
+        ("ASCII_ALPHA_UPPER", quote! { pos.match_range('A'..'Z') }),
+        (
+            "ASCII_ALPHA",
+            quote! {
+                pos.match_range('a'..'z').or_else(|pos| pos.match_range('A'..'Z'))
+            }
+        ),
+        (
+            "ASCII_ALPHANUMERIC",
+            quote! {
+                pos.match_range('a'..'z')
+                    .or_else(|pos| pos.match_range('A'..'Z'))
+                    .or_else(|pos| pos.match_range('0'..'9'))
+            }
+        ),
+        ("ASCII", quote! { pos.match_range('\x00'..'\x7f') }),
+        (
+            "NEWLINE",
+            quote! {
+                pos.match_string("\n")
+                    .or_else(|pos| pos.match_string("\r\n"))
+                    .or_else(|pos| pos.match_string("\r"))
+            }
+        ),
+    ];
+    for (name, body) in character_classes {
+        predefined.insert(name, generate_character_class(name, body));
+    }
 
-    let rule_enum = generate_enum(&rules);
+    let rule_enum = generate_enum(&rules, &doc);
     let patterns = generate_patterns(&rules);
+    let parse = generate_parse(patterns, options);
     let skip = generate_skip(&rules);
 
-    let mut rules: Vec<_> = rules.into_iter().map(|rule| generate_rule(rule)).collect();
+    let mut rules: Vec<_> = rules
+        .into_iter()
+        .map(|rule| {
+            let recovery = options
+                .recoveries
+                .iter()
+                .find(|recovery| recovery.rule == rule.name);
+
+            generate_rule(rule, recovery)
+        })
+        .collect();
     rules.extend(
         defaults
             .into_iter()
-            .map(|name| predefined.get(name.as_ref()).unwrap().clone())
+            .map(|name| match predefined.get(name) {
+                Some(tokens) => tokens.clone(),
+                None => generate_unicode_class(name)
+            })
     );
 
-    let parser_impl = quote! {
-        impl ::pest::Parser<Rule> for #name {
-            fn parse<'i>(
-                rule: Rule,
-                input: &'i str
-            ) -> ::std::result::Result<
-                ::pest::iterators::Pairs<'i, Rule>,
-                ::pest::Error<'i, Rule>
-            > {
-                mod rules {
-                    use super::Rule;
-
-                    #( #rules )*
-                    #skip
+    // Parsers which recover from errors parse with `parse_recovering`, while `parse` returns
+    // their first error just like `pest::state`.
+    let parser_impl = if options.recoveries.is_empty() {
+        quote! {
+            impl ::pest::Parser<Rule> for #name {
+                fn parse<'i>(
+                    rule: Rule,
+                    input: &'i str
+                ) -> ::std::result::Result<
+                    ::pest::iterators::Pairs<'i, Rule>,
+                    ::pest::Error<'i, Rule>
+                > {
+                    mod rules {
+                        use super::Rule;
+
+                        #( #rules )*
+                        #skip
+                    }
+
+                    ::pest::state(input, move |mut state, pos| {
+                        #parse
+                    })
                 }
+            }
+        }
+    } else {
+        quote! {
+            impl ::pest::Parser<Rule> for #name {
+                fn parse<'i>(
+                    rule: Rule,
+                    input: &'i str
+                ) -> ::std::result::Result<
+                    ::pest::iterators::Pairs<'i, Rule>,
+                    ::pest::Error<'i, Rule>
+                > {
+                    let (pairs, mut errors) = Self::parse_recovering(rule, input);
 
-                ::pest::state(input, move |mut state, pos| {
-                    match rule {
-                        #patterns
+                    if errors.is_empty() {
+                        ::std::result::Result::Ok(pairs)
+                    } else {
+                        ::std::result::Result::Err(errors.remove(0))
                     }
-                })
+                }
+
+                fn parse_recovering<'i>(
+                    rule: Rule,
+                    input: &'i str
+                ) -> (
+                    ::pest::iterators::Pairs<'i, Rule>,
+                    ::std::vec::Vec<::pest::Error<'i, Rule>>
+                ) {
+                    mod rules {
+                        use super::Rule;
+
+                        #( #rules )*
+                        #skip
+                    }
+
+                    ::pest::recovering_state(input, move |mut state, pos| {
+                        #parse
+                    })
+                }
             }
         }
     };
@@ -130,10 +358,45 @@ pub fn generate(name: Ident, rules: Vec<Rule>, defaults: Vec<Ident>) -> Tokens {
     }
 }
 
-fn generate_enum(rules: &Vec<Rule>) -> Tokens {
-    let rules = rules.iter().map(|rule| &rule.name);
+fn generate_character_class(name: &str, body: Tokens) -> Tokens {
+    let name = Ident::new(name);
+
+    quote! {
+        #[inline]
+        #[allow(non_snake_case)]
+        fn #name<'i>(
+            pos: ::pest::Position<'i>,
+            _: &mut ::pest::ParserState<'i, Rule>
+        ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
+            #body
+        }
+    }
+}
+
+// The Unicode classes are the predicates of `pest::unicode`, all of which are named after the
+// predefined rules they implement.
+fn generate_unicode_class(name: &str) -> Tokens {
+    assert!(unicode::by_name(name).is_some(), "undefined predefined rule {}", name);
+
+    let predicate = Ident::new(name);
+
+    generate_character_class(name, quote! { pos.match_char_by(::pest::unicode::#predicate) })
+}
+
+fn generate_enum(rules: &Vec<Rule>, doc: &[String]) -> Tokens {
+    let rules = rules.iter().map(|rule| {
+        let name = Ident::new(rule.name.as_str());
+        let doc = rule.doc.iter().map(|line| line.as_str());
+
+        quote! {
+            #( #[doc = #doc] )*
+            #name
+        }
+    });
+    let doc = doc.iter().map(|line| line.as_str());
 
     quote! {
+        #( #[doc = #doc] )*
         #[allow(dead_code, non_camel_case_types)]
         #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
         pub enum Rule {
@@ -146,7 +409,7 @@ fn generate_patterns(rules: &Vec<Rule>) -> Tokens {
     let mut tokens = Tokens::new();
 
     let rules = rules.iter().map(|rule| {
-        let rule = &rule.name;
+        let rule = Ident::new(rule.name.as_str());
         quote! {
             Rule::#rule => rules::#rule(pos, &mut state)
         }
@@ -157,8 +420,36 @@ fn generate_patterns(rules: &Vec<Rule>) -> Tokens {
     tokens
 }
 
-fn generate_rule(rule: Rule) -> Tokens {
-    let name = rule.name;
+fn generate_parse(patterns: Tokens, options: &Options) -> Tokens {
+    let parse = quote! {
+        match rule {
+            #patterns
+        }
+    };
+
+    let parse = if options.track_rule_stack {
+        quote! {
+            state.track_rule_stack(true, move |mut state| {
+                #parse
+            })
+        }
+    } else {
+        parse
+    };
+
+    if options.memoize {
+        quote! {
+            state.memoize(true, move |mut state| {
+                #parse
+            })
+        }
+    } else {
+        parse
+    }
+}
+
+fn generate_rule(rule: Rule, recovery: Option<&Recovery>) -> Tokens {
+    let name = Ident::new(rule.name);
     let expr = if { rule.ty == RuleType::Atomic || rule.ty == RuleType::CompoundAtomic } {
         generate_expr_atomic(rule.expr)
     } else {
@@ -175,77 +466,67 @@ fn generate_rule(rule: Rule) -> Tokens {
         }
     };
 
-    match rule.ty {
+    let body = match rule.ty {
         RuleType::Normal => quote! {
-            #[inline]
-            #[allow(unused_variables)]
-            pub fn #name<'i>(
-                pos: ::pest::Position<'i>,
-                state: &mut ::pest::ParserState<'i, Rule>
-            ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
-                state.rule(Rule::#name, pos, #[inline(always)] |state, pos| {
-                    #expr
-                })
-            }
+            state.rule(Rule::#name, pos, #[inline(always)] |state, pos| {
+                #expr
+            })
         },
         RuleType::Silent => quote! {
-            #[inline]
-            #[allow(unused_variables)]
-            pub fn #name<'i>(
-                pos: ::pest::Position<'i>,
-                state: &mut ::pest::ParserState<'i, Rule>
-            ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
-                #expr
-            }
+            #expr
         },
         RuleType::Atomic => quote! {
-            #[inline]
-            #[allow(unused_variables)]
-            pub fn #name<'i>(
-                pos: ::pest::Position<'i>,
-                state: &mut ::pest::ParserState<'i, Rule>
-            ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
-                state.rule(Rule::#name, pos, #[inline(always)] |state, pos| {
-                    state.atomic(::pest::Atomicity::Atomic, #[inline(always)] move |state| {
-                        #expr
-                    })
+            state.rule(Rule::#name, pos, #[inline(always)] |state, pos| {
+                state.atomic(::pest::Atomicity::Atomic, #[inline(always)] move |state| {
+                    #expr
                 })
-            }
+            })
         },
         RuleType::CompoundAtomic => quote! {
-            #[inline]
-            #[allow(unused_variables)]
-            pub fn #name<'i>(
-                pos: ::pest::Position<'i>,
-                state: &mut ::pest::ParserState<'i, Rule>
-            ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
-                state.atomic(::pest::Atomicity::CompoundAtomic, #[inline(always)] move |state| {
-                    state.rule(Rule::#name, pos, #[inline(always)] |state, pos| {
-                        #expr
-                    })
+            state.atomic(::pest::Atomicity::CompoundAtomic, #[inline(always)] move |state| {
+                state.rule(Rule::#name, pos, #[inline(always)] |state, pos| {
+                    #expr
                 })
-            }
+            })
         },
         RuleType::NonAtomic => quote! {
-            #[inline]
-            #[allow(unused_variables)]
-            pub fn #name<'i>(
-                pos: ::pest::Position<'i>,
-                state: &mut ::pest::ParserState<'i, Rule>
-            ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
-                state.atomic(::pest::Atomicity::NonAtomic, #[inline(always)] move |state| {
-                    state.rule(Rule::#name, pos, #[inline(always)] |state, pos| {
-                        #expr
-                    })
+            state.atomic(::pest::Atomicity::NonAtomic, #[inline(always)] move |state| {
+                state.rule(Rule::#name, pos, #[inline(always)] |state, pos| {
+                    #expr
                 })
+            })
+        }
+    };
+
+    let body = match recovery {
+        Some(recovery) => {
+            let error = Ident::new(recovery.error.as_str());
+            let sync = recovery.sync.as_str();
+
+            quote! {
+                state.recover(Rule::#error, pos, #[inline(always)] |state, pos| {
+                    #body
+                }, |pos| pos.match_string(#sync))
             }
         }
+        None => body
+    };
+
+    quote! {
+        #[inline]
+        #[allow(unused_variables)]
+        pub fn #name<'i>(
+            pos: ::pest::Position<'i>,
+            state: &mut ::pest::ParserState<'i, Rule>
+        ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
+            #body
+        }
     }
 }
 
 fn generate_skip(rules: &Vec<Rule>) -> Tokens {
-    let whitespace = rules.iter().any(|rule| rule.name.as_ref() == "whitespace");
-    let comment = rules.iter().any(|rule| rule.name.as_ref() == "comment");
+    let whitespace = rules.iter().any(|rule| rule.name == "whitespace");
+    let comment = rules.iter().any(|rule| rule.name == "comment");
 
     match (whitespace, comment) {
         (false, false) => quote! {
@@ -325,14 +606,27 @@ fn generate_skip(rules: &Vec<Rule>) -> Tokens {
     }
 }
 
+fn generate_peek_slice(start: i32, end: Option<i32>) -> Tokens {
+    let end = match end {
+        Some(end) => quote! { ::std::option::Option::Some(#end) },
+        None => quote! { ::std::option::Option::None }
+    };
+
+    quote! {
+        state.stack_peek_slice(pos, #start, #end)
+    }
+}
+
 fn generate_expr(expr: Expr) -> Tokens {
     match expr {
         Expr::Str(string) => {
             let mut tokens = quote! {
-                pos.match_string
+                state.match_string
             };
 
             tokens.append("(");
+            tokens.append("pos");
+            tokens.append(",");
             tokens.append(format!("\"{}\"", string));
             tokens.append(")");
 
@@ -340,10 +634,12 @@ fn generate_expr(expr: Expr) -> Tokens {
         }
         Expr::Insens(string) => {
             let mut tokens = quote! {
-                pos.match_insensitive
+                state.match_insensitive
             };
 
             tokens.append("(");
+            tokens.append("pos");
+            tokens.append(",");
             tokens.append(format!("\"{}\"", string));
             tokens.append(")");
 
@@ -351,10 +647,12 @@ fn generate_expr(expr: Expr) -> Tokens {
         }
         Expr::Range(start, end) => {
             let mut tokens = quote! {
-                pos.match_range
+                state.match_range
             };
 
             tokens.append("(");
+            tokens.append("pos");
+            tokens.append(",");
             tokens.append(start);
             tokens.append("..");
             tokens.append(end);
@@ -362,9 +660,29 @@ fn generate_expr(expr: Expr) -> Tokens {
 
             tokens
         }
-        Expr::Ident(ident) => quote! {
-            self::#ident(pos, state)
-        },
+        Expr::InsensRange(start, end) => {
+            let mut tokens = quote! {
+                state.match_range_insensitive
+            };
+
+            tokens.append("(");
+            tokens.append("pos");
+            tokens.append(",");
+            tokens.append(start);
+            tokens.append("..");
+            tokens.append(end);
+            tokens.append(")");
+
+            tokens
+        }
+        Expr::Ident(ident) => {
+            let ident = Ident::new(ident);
+
+            quote! {
+                self::#ident(pos, state)
+            }
+        }
+        Expr::PeekSlice(start, end) => generate_peek_slice(start, end),
         Expr::PosPred(expr) => {
             let expr = generate_expr(*expr);
 
@@ -496,6 +814,16 @@ fn generate_expr(expr: Expr) -> Tokens {
                 }
             }
         }
+        Expr::NodeTag(expr, tag) => {
+            let expr = generate_expr(*expr);
+            let tag = tag.as_str();
+
+            quote! {
+                state.tag_node(#tag, #[inline(always)] move |state| {
+                    #expr
+                })
+            }
+        }
         _ => unreachable!()
     }
 }
@@ -504,10 +832,12 @@ fn generate_expr_atomic(expr: Expr) -> Tokens {
     match expr {
         Expr::Str(string) => {
             let mut tokens = quote! {
-                pos.match_string
+                state.match_string
             };
 
             tokens.append("(");
+            tokens.append("pos");
+            tokens.append(",");
             tokens.append(format!("\"{}\"", string));
             tokens.append(")");
 
@@ -515,10 +845,12 @@ fn generate_expr_atomic(expr: Expr) -> Tokens {
         }
         Expr::Insens(string) => {
             let mut tokens = quote! {
-                pos.match_insensitive
+                state.match_insensitive
             };
 
             tokens.append("(");
+            tokens.append("pos");
+            tokens.append(",");
             tokens.append(format!("\"{}\"", string));
             tokens.append(")");
 
@@ -526,10 +858,12 @@ fn generate_expr_atomic(expr: Expr) -> Tokens {
         }
         Expr::Range(start, end) => {
             let mut tokens = quote! {
-                pos.match_range
+                state.match_range
             };
 
             tokens.append("(");
+            tokens.append("pos");
+            tokens.append(",");
             tokens.append(start);
             tokens.append("..");
             tokens.append(end);
@@ -537,9 +871,29 @@ fn generate_expr_atomic(expr: Expr) -> Tokens {
 
             tokens
         }
-        Expr::Ident(ident) => quote! {
-            self::#ident(pos, state)
-        },
+        Expr::InsensRange(start, end) => {
+            let mut tokens = quote! {
+                state.match_range_insensitive
+            };
+
+            tokens.append("(");
+            tokens.append("pos");
+            tokens.append(",");
+            tokens.append(start);
+            tokens.append("..");
+            tokens.append(end);
+            tokens.append(")");
+
+            tokens
+        }
+        Expr::Ident(ident) => {
+            let ident = Ident::new(ident);
+
+            quote! {
+                self::#ident(pos, state)
+            }
+        }
+        Expr::PeekSlice(start, end) => generate_peek_slice(start, end),
         Expr::PosPred(expr) => {
             let expr = generate_expr_atomic(*expr);
 
@@ -655,6 +1009,16 @@ fn generate_expr_atomic(expr: Expr) -> Tokens {
                 }
             }
         }
+        Expr::NodeTag(expr, tag) => {
+            let expr = generate_expr_atomic(*expr);
+            let tag = tag.as_str();
+
+            quote! {
+                state.tag_node(#tag, #[inline(always)] move |state| {
+                    #expr
+                })
+            }
+        }
         _ => unreachable!()
     }
 }
@@ -667,18 +1031,45 @@ mod tests {
     fn rule_enum_simple() {
         let rules = vec![
             Rule {
-                name: Ident::new("f"),
+                name: "f".to_owned(),
+                ty: RuleType::Normal,
+                doc: vec![],
+                expr: Expr::Ident("g".to_owned())
+            },
+        ];
+
+        assert_eq!(
+            generate_enum(&rules, &[]),
+            quote! {
+                #[allow(dead_code, non_camel_case_types)]
+                #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+                pub enum Rule {
+                    f
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn rule_enum_doc() {
+        let rules = vec![
+            Rule {
+                name: "f".to_owned(),
                 ty: RuleType::Normal,
-                expr: Expr::Ident(Ident::new("g"))
+                doc: vec![" An `f`.".to_owned(), "".to_owned()],
+                expr: Expr::Ident("g".to_owned())
             },
         ];
 
         assert_eq!(
-            generate_enum(&rules),
+            generate_enum(&rules, &[" Rules of the grammar.".to_owned()]),
             quote! {
+                #[doc = " Rules of the grammar."]
                 #[allow(dead_code, non_camel_case_types)]
                 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
                 pub enum Rule {
+                    #[doc = " An `f`."]
+                    #[doc = ""]
                     f
                 }
             }
@@ -703,18 +1094,18 @@ mod tests {
             quote! {
                 state.sequence(#[inline(always)] move |state| {
                     pos.sequence(#[inline(always)] |pos| {
-                        pos.match_string("a").and_then(#[inline(always)] |pos| {
+                        state.match_string(pos, "a").and_then(#[inline(always)] |pos| {
                             self::skip(pos, state)
                         }).and_then(#[inline(always)] |pos| {
-                            pos.match_string("b")
+                            state.match_string(pos, "b")
                         }).and_then(#[inline(always)] |pos| {
                             self::skip(pos, state)
                         }).and_then(#[inline(always)] |pos| {
-                            pos.match_string("c")
+                            state.match_string(pos, "c")
                         }).and_then(#[inline(always)] |pos| {
                             self::skip(pos, state)
                         }).and_then(#[inline(always)] |pos| {
-                            pos.match_string("d")
+                            state.match_string(pos, "d")
                         })
                     })
                 })
@@ -740,12 +1131,12 @@ mod tests {
             quote! {
                 state.sequence(#[inline(always)] move |state| {
                     pos.sequence(#[inline(always)] |pos| {
-                        pos.match_string("a").and_then(#[inline(always)] |pos| {
-                            pos.match_string("b")
+                        state.match_string(pos, "a").and_then(#[inline(always)] |pos| {
+                            state.match_string(pos, "b")
                         }).and_then(#[inline(always)] |pos| {
-                            pos.match_string("c")
+                            state.match_string(pos, "c")
                         }).and_then(#[inline(always)] |pos| {
-                            pos.match_string("d")
+                            state.match_string(pos, "d")
                         })
                     })
                 })
@@ -769,12 +1160,12 @@ mod tests {
         assert_eq!(
             generate_expr(expr),
             quote! {
-                pos.match_string("a").or_else(#[inline(always)] |pos| {
-                    pos.match_string("b")
+                state.match_string(pos, "a").or_else(#[inline(always)] |pos| {
+                    state.match_string(pos, "b")
                 }).or_else(#[inline(always)] |pos| {
-                    pos.match_string("c")
+                    state.match_string(pos, "c")
                 }).or_else(#[inline(always)] |pos| {
-                    pos.match_string("d")
+                    state.match_string(pos, "d")
                 })
             }
         );
@@ -796,12 +1187,12 @@ mod tests {
         assert_eq!(
             generate_expr_atomic(expr),
             quote! {
-                pos.match_string("a").or_else(#[inline(always)] |pos| {
-                    pos.match_string("b")
+                state.match_string(pos, "a").or_else(#[inline(always)] |pos| {
+                    state.match_string(pos, "b")
                 }).or_else(#[inline(always)] |pos| {
-                    pos.match_string("c")
+                    state.match_string(pos, "c")
                 }).or_else(#[inline(always)] |pos| {
-                    pos.match_string("d")
+                    state.match_string(pos, "d")
                 })
             }
         );
@@ -810,7 +1201,7 @@ mod tests {
     #[test]
     fn expr_complex() {
         let expr = Expr::Choice(
-            Box::new(Expr::Ident(Ident::new("a"))),
+            Box::new(Expr::Ident("a".to_owned())),
             Box::new(Expr::Seq(
                 Box::new(Expr::Range("'a'".to_owned(), "'b'".to_owned())),
                 Box::new(Expr::Seq(
@@ -832,7 +1223,7 @@ mod tests {
                 pos.sequence(#[inline(always)] |pos| {
                     self::skip(pos, state).and_then(
                         #[inline(always)] |pos| {
-                            pos.match_insensitive("b")
+                            state.match_insensitive(pos, "b")
                         }
                     )
                 })
@@ -843,8 +1234,8 @@ mod tests {
                 state.sequence(#[inline(always)] move |state| {
                     pos.sequence(#[inline(always)] |pos| {
                         self::skip(pos, state).and_then(#[inline(always)] |pos| {
-                            pos.match_string("c").or_else(#[inline(always)] |pos| {
-                                pos.match_string("d")
+                            state.match_string(pos, "c").or_else(#[inline(always)] |pos| {
+                                state.match_string(pos, "d")
                             })
                          })
                     })
@@ -857,7 +1248,7 @@ mod tests {
                 self::a(pos, state).or_else(#[inline(always)] |pos| {
                     state.sequence(#[inline(always)] move |state| {
                         pos.sequence(#[inline(always)] |pos| {
-                            pos.match_range( 'a' .. 'b' ).and_then(#[inline(always)] |pos| {
+                            state.match_range(pos,  'a' .. 'b' ).and_then(#[inline(always)] |pos| {
                                 self::skip(pos, state)
                             }).and_then(#[inline(always)] |pos| {
                                 state.lookahead(false, #[inline(always)] move |state| {
@@ -865,7 +1256,7 @@ mod tests {
                                         state.sequence(#[inline(always)] move |state| {
                                             pos.sequence(#[inline(always)] |pos| {
                                                 pos.optional(#[inline(always)] |pos| {
-                                                    pos.match_insensitive("b")
+                                                    state.match_insensitive(pos, "b")
                                                 }).and_then(#[inline(always)] |pos| {
                                                     pos.repeat(#[inline(always)] |pos| {
                                                         #sequence
@@ -884,9 +1275,9 @@ mod tests {
                                             state.sequence(#[inline(always)] move |state| {
                                                 pos.sequence(#[inline(always)] |pos| {
                                                     pos.optional(#[inline(always)] |pos| {
-                                                        pos.match_string("c").or_else(
+                                                        state.match_string(pos, "c").or_else(
                                                             #[inline(always)] |pos| {
-                                                                pos.match_string("d")
+                                                                state.match_string(pos, "d")
                                                             }
                                                         )
                                                     }).and_then(#[inline(always)] |pos| {
@@ -908,7 +1299,7 @@ mod tests {
     #[test]
     fn expr_complex_atomic() {
         let expr = Expr::Choice(
-            Box::new(Expr::Ident(Ident::new("a"))),
+            Box::new(Expr::Ident("a".to_owned())),
             Box::new(Expr::Seq(
                 Box::new(Expr::Range("'a'".to_owned(), "'b'".to_owned())),
                 Box::new(Expr::Seq(
@@ -931,11 +1322,11 @@ mod tests {
                 self::a(pos, state).or_else(#[inline(always)] |pos| {
                     state.sequence(#[inline(always)] move |state| {
                         pos.sequence(#[inline(always)] |pos| {
-                            pos.match_range('a'..'b').and_then(#[inline(always)] |pos| {
+                            state.match_range(pos, 'a'..'b').and_then(#[inline(always)] |pos| {
                                 state.lookahead(false, #[inline(always)] move |state| {
                                     pos.lookahead(false, #[inline(always)] |pos| {
                                         pos.repeat(#[inline(always)] |pos| {
-                                            pos.match_insensitive("b")
+                                            state.match_insensitive(pos, "b")
                                         })
                                     })
                                 })
@@ -944,9 +1335,9 @@ mod tests {
                                     pos.lookahead(true, #[inline(always)] |pos| {
                                         pos.optional(#[inline(always)] |pos| {
                                             pos.repeat(#[inline(always)] |pos| {
-                                                pos.match_string("c")
+                                                state.match_string(pos, "c")
                                                    .or_else(#[inline(always)] |pos| {
-                                                    pos.match_string("d")
+                                                    state.match_string(pos, "d")
                                                 })
                                             })
                                         })
@@ -960,20 +1351,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn recovering_rule() {
+        let rule = Rule {
+            name: "a".to_owned(),
+            ty: RuleType::Silent,
+            doc: vec![],
+            expr: Expr::Str("b".to_owned())
+        };
+        let recovery = Recovery {
+            rule: "a".to_owned(),
+            error: "error".to_owned(),
+            sync: ";".to_owned()
+        };
+
+        assert_eq!(
+            generate_rule(rule, Some(&recovery)),
+            quote! {
+                #[inline]
+                #[allow(unused_variables)]
+                pub fn a<'i>(
+                    pos: ::pest::Position<'i>,
+                    state: &mut ::pest::ParserState<'i, Rule>
+                ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
+                    state.recover(Rule::error, pos, #[inline(always)] |state, pos| {
+                        state.match_string(pos, "b")
+                    }, |pos| pos.match_string(";"))
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn parse_tracking_rule_stack() {
+        let patterns = quote! {
+            Rule::a => rules::a(pos, &mut state)
+        };
+        let options = Options::new()
+            .with_memoize(true)
+            .with_track_rule_stack(true);
+
+        assert_eq!(
+            generate_parse(patterns, &options),
+            quote! {
+                state.memoize(true, move |mut state| {
+                    state.track_rule_stack(true, move |mut state| {
+                        match rule {
+                            Rule::a => rules::a(pos, &mut state)
+                        }
+                    })
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn memoized_parse() {
+        let patterns = quote! {
+            Rule::a => rules::a(pos, &mut state)
+        };
+
+        assert_eq!(
+            generate_parse(patterns, &Options::new().with_memoize(true)),
+            quote! {
+                state.memoize(true, move |mut state| {
+                    match rule {
+                        Rule::a => rules::a(pos, &mut state)
+                    }
+                })
+            }
+        );
+    }
+
     #[test]
     fn generate_complete() {
         let name = Ident::new("MyParser");
         let rules = vec![
             Rule {
-                name: Ident::new("a"),
+                name: "a".to_owned(),
                 ty: RuleType::Silent,
+                doc: vec![],
                 expr: Expr::Str("b".to_owned())
             },
         ];
-        let defaults = vec![Ident::new("any")];
+        let defaults = vec!["any"];
 
         assert_eq!(
-            generate(name, rules, defaults),
+            generate(name, rules, defaults, vec![], &Options::new()),
             quote! {
                 #[allow(dead_code, non_camel_case_types)]
                 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -998,7 +1462,7 @@ mod tests {
                                 pos: ::pest::Position<'i>,
                                 state: &mut ::pest::ParserState<'i, Rule>
                             ) -> ::std::result::Result<::pest::Position<'i>, ::pest::Position<'i>> {
-                                pos.match_string("b")
+                                state.match_string(pos, "b")
                             }
 
                             #[inline]
@@ -0,0 +1,328 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! # pest generator
+//!
+//! This crate generates the `Rule` `enum` and `Parser` implementation of a pest grammar. It is
+//! used by [`pest_derive`](https://docs.rs/pest_derive), but can also be called from a
+//! `build.rs` in order to write the generated parser to a regular `.rs` file which can be
+//! reviewed, stepped through in a debugger, and versioned.
+//!
+//! ```ignore
+//! // build.rs
+//! extern crate pest_generator;
+//!
+//! use std::env;
+//! use std::path::Path;
+//!
+//! fn main() {
+//!     let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("my_parser.rs");
+//!     let paths =
+//!         pest_generator::generate_to_file("src/my_grammar.pest", out_path, "MyParser").unwrap();
+//!
+//!     // Regenerates the parser whenever the grammar or any file it imports changes.
+//!     for path in paths {
+//!         println!("cargo:rerun-if-changed={}", path.display());
+//!     }
+//! }
+//! ```
+//!
+//! The generated code only contains the `Rule` `enum` and the `impl` of `Parser`, so the
+//! `struct` needs to be declared next to where it is included:
+//!
+//! ```ignore
+//! extern crate pest;
+//!
+//! pub struct MyParser;
+//!
+//! include!(concat!(env!("OUT_DIR"), "/my_parser.rs"));
+//! ```
+
+#![doc(html_root_url = "https://docs.rs/pest_generator")]
+#![recursion_limit = "256"]
+
+extern crate pest;
+extern crate pest_meta;
+#[macro_use]
+extern crate quote;
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use quote::Ident;
+
+mod files;
+mod generator;
+
+pub use files::{format_error, GrammarFiles};
+pub use generator::{generate, Options, Recovery};
+
+/// Reads the grammar at `grammar_path` along with the files it imports and writes the `Rule`
+/// `enum` and the `Parser` implementation for `parser_name` that they generate to `out_path`.
+/// Returns the canonical paths of every grammar file that was read, so that a `build.rs` can
+/// print a `cargo:rerun-if-changed` line for each of them.
+///
+/// The code is formatted with `rustfmt` when it can be found in the `PATH` and with a simple
+/// indenting formatter otherwise.
+///
+/// # Errors
+///
+/// Returns the `io::Error` of reading or writing the files, or an `io::Error` of kind
+/// `InvalidData` listing every error found in the grammars formatted with
+/// [`GrammarFiles::format_error`](struct.GrammarFiles.html#method.format_error).
+///
+/// # Examples
+///
+/// ```
+/// # extern crate pest_generator;
+/// # use std::env;
+/// # use std::fs::{self, File};
+/// # use std::io::Write;
+/// # fn main() {
+/// let dir = env::temp_dir();
+/// let grammar_path = dir.join("pest_generator_doc.pest");
+/// let out_path = dir.join("pest_generator_doc.rs");
+/// File::create(&grammar_path).unwrap().write_all(b"a = { \"a\" }").unwrap();
+///
+/// let paths = pest_generator::generate_to_file(&grammar_path, &out_path, "MyParser").unwrap();
+///
+/// assert_eq!(paths, vec![fs::canonicalize(&grammar_path).unwrap()]);
+/// assert!(fs::read_to_string(&out_path).unwrap().contains("MyParser"));
+/// # }
+/// ```
+pub fn generate_to_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    grammar_path: P,
+    out_path: Q,
+    parser_name: &str
+) -> io::Result<Vec<PathBuf>> {
+    let grammar_path = grammar_path.as_ref();
+    let file_name = match grammar_path.file_name() {
+        Some(file_name) => file_name.to_string_lossy().into_owned(),
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "grammar path should point to a file"
+            ))
+        }
+    };
+
+    let mut files = GrammarFiles::new();
+    files.add_file(grammar_path)?;
+
+    let (defaults, rules) = match pest_meta::parse_and_optimize(files.source()) {
+        Ok(result) => result,
+        Err(errors) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "error parsing {:?}\n\n{}",
+                    file_name,
+                    errors
+                        .iter()
+                        .map(|error| files.format_error(error))
+                        .collect::<Vec<_>>()
+                        .join("\n\n")
+                )
+            ))
+        }
+    };
+    let generated = generate(
+        Ident::new(parser_name),
+        rules,
+        defaults,
+        files.doc(),
+        &Options::new()
+    );
+    let generated = generated.as_str();
+    let source = rustfmt(generated).unwrap_or_else(|| format_source(generated));
+
+    let mut file = File::create(out_path)?;
+    write!(
+        file,
+        "// This file is generated by pest_generator from {}. Do not edit it by hand.\n\n{}",
+        file_name, source
+    )?;
+
+    Ok(files.paths().to_vec())
+}
+
+fn rustfmt(source: &str) -> Option<String> {
+    let mut child = Command::new("rustfmt")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    child.stdin.take()?.write_all(source.as_bytes()).ok()?;
+
+    let output = child.wait_with_output().ok()?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
+
+// Breaks lines after braces and semicolons and indents blocks. Strings and chars are copied
+// verbatim so that braces inside of them are left alone.
+fn format_source(source: &str) -> String {
+    let chars: Vec<_> = source.chars().collect();
+    let mut result = String::new();
+    let mut indent = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        i += 1;
+
+        let is_char =
+            c == '\'' && (chars.get(i) == Some(&'\\') || chars.get(i + 1) == Some(&'\''));
+
+        if c == '"' || is_char {
+            while i < chars.len() && chars[i] != c {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i += 1;
+
+            push_indented(&mut result, indent, &chars[start..i.min(chars.len())]);
+            continue;
+        }
+
+        match c {
+            '{' => {
+                push_indented(&mut result, indent, &['{']);
+                indent += 1;
+                new_line(&mut result);
+            }
+            '}' => {
+                indent = indent.saturating_sub(1);
+                new_line(&mut result);
+                push_indented(&mut result, indent, &['}']);
+                new_line(&mut result);
+            }
+            ';' => {
+                push_indented(&mut result, indent, &[';']);
+                new_line(&mut result);
+            }
+            ' ' if result.is_empty() || result.ends_with('\n') => (),
+            c => push_indented(&mut result, indent, &[c])
+        }
+    }
+
+    new_line(&mut result);
+
+    result
+}
+
+fn push_indented(result: &mut String, indent: usize, chars: &[char]) {
+    if result.ends_with('\n') {
+        result.extend((0..indent * 4).map(|_| ' '));
+    }
+
+    result.extend(chars);
+}
+
+fn new_line(result: &mut String) {
+    let len = result.trim_end_matches(' ').len();
+    result.truncate(len);
+
+    if !result.is_empty() && !result.ends_with('\n') {
+        result.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn format_blocks() {
+        assert_eq!(
+            format_source("mod a { fn b ( ) { c ( ) ; d ( ) } }"),
+            "mod a {\n    fn b ( ) {\n        c ( ) ;\n        d ( )\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn format_literals() {
+        assert_eq!(
+            format_source("fn a < 'i > ( ) { b ( \"{;\\\"}\" , '{' , '\\'' ) }"),
+            "fn a < 'i > ( ) {\n    b ( \"{;\\\"}\" , '{' , '\\'' )\n}\n"
+        );
+    }
+
+    #[test]
+    fn generate_file() {
+        let dir = env::temp_dir();
+        let grammar_path = dir.join("pest_generator_generate_file.pest");
+        let out_path = dir.join("pest_generator_generate_file.rs");
+        fs::write(&grammar_path, "a = { \"{\" ~ b } b = @{ '0'..'9' }").unwrap();
+
+        generate_to_file(&grammar_path, &out_path, "MyParser").unwrap();
+
+        let source = fs::read_to_string(&out_path).unwrap();
+
+        assert!(source.starts_with(
+            "// This file is generated by pest_generator from \
+             pest_generator_generate_file.pest. Do not edit it by hand.\n\n"
+        ));
+        assert!(source.contains("pub enum Rule {"));
+        assert!(source.contains("for MyParser {"));
+        assert!(source.lines().count() > 50);
+    }
+
+    #[test]
+    fn generate_file_paths() {
+        let dir = env::temp_dir();
+        let grammar_path = dir.join("pest_generator_generate_file_paths.pest");
+        let import_path = dir.join("pest_generator_generate_file_import.pest");
+        let out_path = dir.join("pest_generator_generate_file_paths.rs");
+        fs::write(&import_path, "b = { \"b\" }").unwrap();
+        fs::write(
+            &grammar_path,
+            "import \"pest_generator_generate_file_import.pest\"\na = { b }"
+        ).unwrap();
+
+        assert_eq!(
+            generate_to_file(&grammar_path, &out_path, "MyParser").unwrap(),
+            vec![
+                fs::canonicalize(&grammar_path).unwrap(),
+                fs::canonicalize(&import_path).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_file_grammar_errors() {
+        let dir = env::temp_dir();
+        let grammar_path = dir.join("pest_generator_grammar_errors.pest");
+        let out_path = dir.join("pest_generator_grammar_errors.rs");
+        fs::write(&grammar_path, "a = { b }").unwrap();
+
+        let error = generate_to_file(&grammar_path, &out_path, "MyParser").unwrap_err();
+        let errors = pest_meta::parse_and_optimize("a = { b }").unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            format!("{}", error),
+            format!(
+                "error parsing \"pest_generator_grammar_errors.pest\"\n\n{}",
+                format_error(&grammar_path, &errors[0])
+            )
+        );
+    }
+}
@@ -7,15 +7,21 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use quote::Ident;
-
+/// A `struct` of a grammar rule without any `Span`s, as consumed by code generators and
+/// interpreters.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Rule {
-    pub name: Ident,
+    /// Name of the rule
+    pub name: String,
+    /// Type of the rule, given by its modifier
     pub ty: RuleType,
+    /// Lines of the rule's `///` doc comment, without the leading `///`
+    pub doc: Vec<String>,
+    /// Body of the rule
     pub expr: Expr
 }
 
+/// An `enum` specifying the type of a rule, i.e. whether it is silent or atomic.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum RuleType {
     Normal,
@@ -25,12 +31,15 @@ pub enum RuleType {
     NonAtomic
 }
 
+/// An `enum` of grammar expressions. Strings and characters are kept exactly as written in the
+/// grammar, i.e. escaped and, in the case of `Range` and `InsensRange`, quoted.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Expr {
     Str(String),
     Insens(String),
     Range(String, String),
-    Ident(Ident),
+    InsensRange(String, String),
+    Ident(String),
     PosPred(Box<Expr>),
     NegPred(Box<Expr>),
     Seq(Box<Expr>, Box<Expr>),
@@ -42,10 +51,13 @@ pub enum Expr {
     RepMin(Box<Expr>, u32),
     RepMax(Box<Expr>, u32),
     RepMinMax(Box<Expr>, u32, u32),
-    Push(Box<Expr>)
+    Push(Box<Expr>),
+    PeekSlice(i32, Option<i32>),
+    NodeTag(Box<Expr>, String)
 }
 
 impl Expr {
+    /// Maps `f` over the `Expr` and all its sub-expressions, starting from the outermost one.
     pub fn map_top_down<F>(self, mut f: F) -> Expr
     where
         F: FnMut(Expr) -> Expr
@@ -108,6 +120,10 @@ impl Expr {
                     let mapped = Box::new(map_internal(*expr, f));
                     Expr::Push(mapped)
                 }
+                Expr::NodeTag(expr, tag) => {
+                    let mapped = Box::new(map_internal(*expr, f));
+                    Expr::NodeTag(mapped, tag)
+                }
                 expr => expr
             }
         }
@@ -115,6 +131,7 @@ impl Expr {
         map_internal(self, &mut f)
     }
 
+    /// Maps `f` over the `Expr` and all its sub-expressions, starting from the innermost ones.
     pub fn map_bottom_up<F>(self, mut f: F) -> Expr
     where
         F: FnMut(Expr) -> Expr
@@ -175,6 +192,10 @@ impl Expr {
                     let mapped = Box::new(map_internal(*expr, f));
                     Expr::Push(mapped)
                 }
+                Expr::NodeTag(expr, tag) => {
+                    let mapped = Box::new(map_internal(*expr, f));
+                    Expr::NodeTag(mapped, tag)
+                }
                 expr => expr
             };
 
@@ -193,7 +214,7 @@ mod tests {
     fn identity() {
         let expr = Expr::Choice(
             Box::new(Expr::Seq(
-                Box::new(Expr::Ident(Ident::new("a"))),
+                Box::new(Expr::Ident("a".to_owned())),
                 Box::new(Expr::Str("b".to_owned()))
             )),
             Box::new(Expr::PosPred(Box::new(Expr::NegPred(Box::new(
@@ -0,0 +1,106 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! # pest meta
+//!
+//! This crate parses, validates, and optimizes pest grammars. It is shared by
+//! [`pest_derive`](https://docs.rs/pest_derive), which generates Rust code from the optimized
+//! rules, and [`pest_vm`](https://docs.rs/pest_vm), which interprets them at runtime.
+//!
+//! [`parse_and_optimize`](fn.parse_and_optimize.html) runs the whole pipeline at once, while
+//! its steps are available on their own for tools which only need some of them:
+//!
+//! 1. [`GrammarParser`](parser/struct.GrammarParser.html) parses the grammar into `Pairs`.
+//! 2. [`validate_pairs`](validator/fn.validate_pairs.html) checks rule names and calls.
+//! 3. [`consume_rules_with_spans`](parser/fn.consume_rules_with_spans.html) builds an AST of
+//!    [`ParserRule`](parser/struct.ParserRule.html)s which keeps the `Span` of every
+//!    expression, and [`validate_ast`](validator/fn.validate_ast.html) checks it.
+//!    [`expand_parameterized_rules`](parser/fn.expand_parameterized_rules.html) then inlines the
+//!    calls of parameterized rules. [`consume_rules`](parser/fn.consume_rules.html) does all
+//!    three and drops the `Span`s.
+//! 4. [`optimize`](optimizer/fn.optimize.html) rewrites the AST for faster parsing.
+//!
+//! ```
+//! # extern crate pest;
+//! # extern crate pest_meta;
+//! use pest::Parser;
+//! use pest_meta::{optimizer, parser, validator};
+//! use pest_meta::parser::{GrammarParser, GrammarRule};
+//!
+//! # fn main() {
+//! let pairs = GrammarParser::parse(GrammarRule::grammar_rules, "a = { \"a\" ~ eoi }").unwrap();
+//! let defaults = validator::validate_pairs(pairs.clone()).unwrap();
+//! let ast = parser::consume_rules(pairs).unwrap();
+//! let optimized = optimizer::optimize(ast);
+//!
+//! assert_eq!(defaults, vec!["eoi"]);
+//! assert_eq!(optimized.len(), 1);
+//! # }
+//! ```
+
+#![doc(html_root_url = "https://docs.rs/pest_meta")]
+
+#[cfg(test)]
+#[macro_use]
+extern crate pest;
+#[cfg(not(test))]
+extern crate pest;
+
+use pest::{Error, Parser};
+
+use parser::{GrammarParser, GrammarRule};
+
+pub mod ast;
+pub mod optimizer;
+pub mod parser;
+pub mod validator;
+
+/// Parses, validates, and optimizes `grammar`, returning the names of the predefined rules it
+/// calls together with its optimized rules, or every error that was found sorted by position.
+/// Errors from parsing `grammar` have their rules renamed with
+/// [`rename_meta_rule`](parser/fn.rename_meta_rule.html).
+///
+/// # Examples
+///
+/// ```
+/// # use pest_meta;
+/// let (defaults, rules) = pest_meta::parse_and_optimize("a = { any }").unwrap();
+///
+/// assert_eq!(defaults, vec!["any"]);
+/// assert_eq!(rules[0].name, "a");
+///
+/// let errors = pest_meta::parse_and_optimize("a = { b } a = { c } d = { d }").unwrap_err();
+///
+/// assert_eq!(errors.len(), 4);
+/// ```
+pub fn parse_and_optimize(
+    grammar: &str
+) -> Result<(Vec<&str>, Vec<ast::Rule>), Vec<Error<'_, GrammarRule>>> {
+    let pairs = match GrammarParser::parse(GrammarRule::grammar_rules, grammar) {
+        Ok(pairs) => pairs,
+        Err(error) => return Err(vec![error.renamed_rules(parser::rename_meta_rule)])
+    };
+
+    // Both steps run even if the first one fails so that every error is reported at once. Errors
+    // found by both, like calls of undefined rules, are only reported once.
+    match (validator::validate_pairs(pairs.clone()), parser::consume_rules(pairs)) {
+        (Ok(defaults), Ok(ast)) => Ok((defaults, optimizer::optimize(ast))),
+        (defaults, ast) => {
+            let mut errors = defaults.err().unwrap_or_default();
+            errors.extend(ast.err().unwrap_or_default());
+            errors.sort_by_key(|error| match *error {
+                Error::CustomErrorSpan { ref span, .. } => span.start(),
+                _ => unreachable!()
+            });
+            errors.dedup();
+
+            Err(errors)
+        }
+    }
+}
@@ -9,6 +9,11 @@
 
 use ast::*;
 
+/// Optimizes `rules` for parsing, e.g. by concatenating adjacent strings in atomic rules and by
+/// rewriting bounded repetitions in terms of sequences, options, and `Rep`s.
+///
+/// Optimized rules only contain `Str`, `Insens`, `Range`, `InsensRange`, `Ident`, `PeekSlice`,
+/// `PosPred`, `NegPred`, `Seq`, `Choice`, `Opt`, `Rep`, `Push`, and `NodeTag` expressions.
 pub fn optimize(rules: Vec<Rule>) -> Vec<Rule> {
     rules
         .into_iter()
@@ -86,9 +91,10 @@ pub fn optimize(rules: Vec<Rule>) -> Vec<Rule> {
             };
 
             match rule {
-                Rule { name, ty, expr } => Rule {
+                Rule { name, ty, doc, expr } => Rule {
                     name,
                     ty,
+                    doc,
                     expr: expr.map_bottom_up(rotate_right)
                         .map_bottom_up(unroll_loops)
                         .map_bottom_up(|expr| {
@@ -137,14 +143,13 @@ pub fn optimize(rules: Vec<Rule>) -> Vec<Rule> {
 mod tests {
     use super::*;
 
-    use quote::Ident;
-
     #[test]
     fn concat_strings() {
         let rules = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
+                doc: vec![],
                 expr: Expr::Seq(
                     Box::new(Expr::Seq(
                         Box::new(Expr::Str("a".to_owned())),
@@ -159,8 +164,9 @@ mod tests {
         ];
         let concatenated = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
+                doc: vec![],
                 expr: Expr::Str("abcd".to_owned())
             },
         ];
@@ -172,20 +178,22 @@ mod tests {
     fn unroll_loop_exact() {
         let rules = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
-                expr: Expr::RepExact(Box::new(Expr::Ident(Ident::new("a"))), 3)
+                doc: vec![],
+                expr: Expr::RepExact(Box::new(Expr::Ident("a".to_owned())), 3)
             },
         ];
         let unrolled = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
+                doc: vec![],
                 expr: Expr::Seq(
-                    Box::new(Expr::Ident(Ident::new("a"))),
+                    Box::new(Expr::Ident("a".to_owned())),
                     Box::new(Expr::Seq(
-                        Box::new(Expr::Ident(Ident::new("a"))),
-                        Box::new(Expr::Ident(Ident::new("a")))
+                        Box::new(Expr::Ident("a".to_owned())),
+                        Box::new(Expr::Ident("a".to_owned()))
                     ))
                 )
             },
@@ -198,15 +206,17 @@ mod tests {
     fn unroll_loop_max() {
         let rules = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
+                doc: vec![],
                 expr: Expr::RepMax(Box::new(Expr::Str("a".to_owned())), 3)
             },
         ];
         let unrolled = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
+                doc: vec![],
                 expr: Expr::Seq(
                     Box::new(Expr::Opt(Box::new(Expr::Str("a".to_owned())))),
                     Box::new(Expr::Seq(
@@ -224,15 +234,17 @@ mod tests {
     fn unroll_loop_min() {
         let rules = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
+                doc: vec![],
                 expr: Expr::RepMin(Box::new(Expr::Str("a".to_owned())), 2)
             },
         ];
         let unrolled = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
+                doc: vec![],
                 expr: Expr::Seq(
                     Box::new(Expr::Str("a".to_owned())),
                     Box::new(Expr::Seq(
@@ -250,15 +262,17 @@ mod tests {
     fn unroll_loop_min_max() {
         let rules = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
+                doc: vec![],
                 expr: Expr::RepMinMax(Box::new(Expr::Str("a".to_owned())), 2, 3)
             },
         ];
         let unrolled = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
+                doc: vec![],
                 expr: Expr::Seq(
                     /* TODO possible room for improvement here:
                      * if the sequences were rolled out in the opposite
@@ -285,8 +299,9 @@ mod tests {
     fn concat_insensitive_strings() {
         let rules = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
+                doc: vec![],
                 expr: Expr::Seq(
                     Box::new(Expr::Seq(
                         Box::new(Expr::Insens("a".to_owned())),
@@ -301,8 +316,9 @@ mod tests {
         ];
         let concatenated = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Atomic,
+                doc: vec![],
                 expr: Expr::Insens("abcd".to_owned())
             },
         ];
@@ -314,37 +330,39 @@ mod tests {
     fn long_common_sequence() {
         let rules = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Silent,
+                doc: vec![],
                 expr: Expr::Choice(
                     Box::new(Expr::Seq(
-                        Box::new(Expr::Ident(Ident::new("a"))),
+                        Box::new(Expr::Ident("a".to_owned())),
                         Box::new(Expr::Seq(
-                            Box::new(Expr::Ident(Ident::new("b"))),
-                            Box::new(Expr::Ident(Ident::new("c")))
+                            Box::new(Expr::Ident("b".to_owned())),
+                            Box::new(Expr::Ident("c".to_owned()))
                         ))
                     )),
                     Box::new(Expr::Seq(
                         Box::new(Expr::Seq(
-                            Box::new(Expr::Ident(Ident::new("a"))),
-                            Box::new(Expr::Ident(Ident::new("b")))
+                            Box::new(Expr::Ident("a".to_owned())),
+                            Box::new(Expr::Ident("b".to_owned()))
                         )),
-                        Box::new(Expr::Ident(Ident::new("d")))
+                        Box::new(Expr::Ident("d".to_owned()))
                     ))
                 )
             },
         ];
         let optimized = vec![
             Rule {
-                name: Ident::new("rule"),
+                name: "rule".to_owned(),
                 ty: RuleType::Silent,
+                doc: vec![],
                 expr: Expr::Seq(
-                    Box::new(Expr::Ident(Ident::new("a"))),
+                    Box::new(Expr::Ident("a".to_owned())),
                     Box::new(Expr::Seq(
-                        Box::new(Expr::Ident(Ident::new("b"))),
+                        Box::new(Expr::Ident("b".to_owned())),
                         Box::new(Expr::Choice(
-                            Box::new(Expr::Ident(Ident::new("c"))),
-                            Box::new(Expr::Ident(Ident::new("d")))
+                            Box::new(Expr::Ident("c".to_owned())),
+                            Box::new(Expr::Ident("d".to_owned()))
                         ))
                     ))
                 )
@@ -7,7 +7,9 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
+use std::collections::HashMap;
 use std::iter::Peekable;
+use std::str::FromStr;
 
 use pest::{self, Error, Parser, ParserState};
 use pest::Position;
@@ -15,11 +17,10 @@ use pest::Span;
 use pest::iterators::{Pair, Pairs};
 use pest::prec_climber::{Assoc, Operator, PrecClimber};
 
-use quote::Ident;
-
 use ast::{Expr, Rule, RuleType};
 use validator;
 
+/// An `enum` of the rules of pest's own grammar.
 #[allow(dead_code, non_camel_case_types)]
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum GrammarRule {
@@ -27,6 +28,11 @@ pub enum GrammarRule {
     soi,
     eoi,
     grammar_rule,
+    grammar_doc,
+    line_doc,
+    import,
+    override_keyword,
+    parameters,
     assignment_operator,
     silent_modifier,
     atomic_modifier,
@@ -51,10 +57,17 @@ pub enum GrammarRule {
     repeat_min_max,
     comma,
     push,
+    peek_slice,
+    call,
+    integer,
+    opening_brack,
+    closing_brack,
     identifier,
+    tag_id,
     string,
     quote,
     insensitive_string,
+    insensitive_range,
     range,
     range_operator,
     character,
@@ -62,6 +75,8 @@ pub enum GrammarRule {
     single_quote
 }
 
+/// A `struct` which parses pest grammars. Parsing from
+/// [`GrammarRule::grammar_rules`](enum.GrammarRule.html) parses a whole grammar.
 pub struct GrammarParser;
 
 impl Parser<GrammarRule> for GrammarParser {
@@ -76,12 +91,12 @@ impl Parser<GrammarRule> for GrammarParser {
             pos.sequence(|pos| {
                 soi(pos, state)
                     .and_then(|pos| skip(pos, state))
-                    .and_then(|pos| grammar_rule(pos, state))
+                    .and_then(|pos| item(pos, state))
                     .and_then(|pos| {
                         pos.repeat(|pos| {
                             state.sequence(move |state| {
                                 pos.sequence(|pos| {
-                                    skip(pos, state).and_then(|pos| grammar_rule(pos, state))
+                                    skip(pos, state).and_then(|pos| item(pos, state))
                                 })
                             })
                         })
@@ -105,6 +120,30 @@ impl Parser<GrammarRule> for GrammarParser {
             pos.at_end()
         }
 
+        fn item<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            grammar_doc(pos, state)
+                .or_else(|pos| import(pos, state))
+                .or_else(|pos| grammar_rule(pos, state))
+        }
+
+        fn import<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            state.rule(GrammarRule::import, pos, |state, pos| {
+                state.sequence(move |state| {
+                    pos.sequence(|pos| {
+                        pos.match_string("import")
+                            .and_then(|pos| skip(pos, state))
+                            .and_then(|pos| string(pos, state))
+                    })
+                })
+            })
+        }
+
         fn grammar_rule<'i>(
             pos: Position<'i>,
             state: &mut ParserState<'i, GrammarRule>
@@ -112,8 +151,24 @@ impl Parser<GrammarRule> for GrammarParser {
             state.rule(GrammarRule::grammar_rule, pos, |state, pos| {
                 state.sequence(move |state| {
                     pos.sequence(|pos| {
-                        identifier(pos, state)
+                        pos.repeat(|pos| {
+                            state.sequence(move |state| {
+                                pos.sequence(|pos| {
+                                    line_doc(pos, state).and_then(|pos| skip(pos, state))
+                                })
+                            })
+                        }).and_then(|pos| {
+                            pos.optional(|pos| {
+                                override_keyword(pos, state).and_then(|pos| skip(pos, state))
+                            })
+                        })
+                            .and_then(|pos| identifier(pos, state))
                             .and_then(|pos| skip(pos, state))
+                            .and_then(|pos| {
+                                pos.optional(|pos| {
+                                    parameters(pos, state).and_then(|pos| skip(pos, state))
+                                })
+                            })
                             .and_then(|pos| assignment_operator(pos, state))
                             .and_then(|pos| skip(pos, state))
                             .and_then(|pos| pos.optional(|pos| modifier(pos, state)))
@@ -128,6 +183,77 @@ impl Parser<GrammarRule> for GrammarParser {
             })
         }
 
+        fn grammar_doc<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            // Only tried in front of `//!` so that it does not clutter errors of other items.
+            pos.lookahead(true, |pos| pos.match_string("//!")).and_then(|pos| {
+                state.rule(GrammarRule::grammar_doc, pos, |_, pos| {
+                    pos.sequence(|pos| pos.match_string("//!").and_then(|pos| rest_of_line(pos)))
+                })
+            })
+        }
+
+        fn line_doc<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            pos.lookahead(true, |pos| pos.match_string("///")).and_then(|pos| {
+                state.rule(GrammarRule::line_doc, pos, |_, pos| {
+                    pos.sequence(|pos| {
+                        pos.match_string("///")
+                            .and_then(|pos| pos.lookahead(false, |pos| pos.match_string("/")))
+                            .and_then(|pos| rest_of_line(pos))
+                    })
+                })
+            })
+        }
+
+        fn override_keyword<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            state.rule(GrammarRule::override_keyword, pos, |state, pos| {
+                pos.sequence(|pos| {
+                    pos.match_string("override").and_then(|pos| {
+                        pos.lookahead(false, |pos| {
+                            pos.match_string("_").or_else(|pos| alpha_num(pos, state))
+                        })
+                    })
+                })
+            })
+        }
+
+        fn parameters<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            state.rule(GrammarRule::parameters, pos, |state, pos| {
+                state.sequence(move |state| {
+                    pos.sequence(|pos| {
+                        opening_paren(pos, state)
+                            .and_then(|pos| skip(pos, state))
+                            .and_then(|pos| identifier(pos, state))
+                            .and_then(|pos| {
+                                pos.repeat(|pos| {
+                                    state.sequence(move |state| {
+                                        pos.sequence(|pos| {
+                                            skip(pos, state)
+                                                .and_then(|pos| comma(pos, state))
+                                                .and_then(|pos| skip(pos, state))
+                                                .and_then(|pos| identifier(pos, state))
+                                        })
+                                    })
+                                })
+                            })
+                            .and_then(|pos| skip(pos, state))
+                            .and_then(|pos| closing_paren(pos, state))
+                    })
+                })
+            })
+        }
+
         fn assignment_operator<'i>(
             pos: Position<'i>,
             state: &mut ParserState<'i, GrammarRule>
@@ -250,9 +376,20 @@ impl Parser<GrammarRule> for GrammarParser {
             state.rule(GrammarRule::term, pos, |state, pos| {
                 state.sequence(move |state| {
                     pos.sequence(|pos| {
-                        pos.repeat(|pos| {
-                            pos.sequence(|pos| {
-                                prefix_operator(pos, state).and_then(|pos| skip(pos, state))
+                        pos.optional(|pos| {
+                            state.sequence(move |state| {
+                                pos.sequence(|pos| {
+                                    tag_id(pos, state)
+                                        .and_then(|pos| skip(pos, state))
+                                        .and_then(|pos| assignment_operator(pos, state))
+                                        .and_then(|pos| skip(pos, state))
+                                })
+                            })
+                        }).and_then(|pos| {
+                            pos.repeat(|pos| {
+                                pos.sequence(|pos| {
+                                    prefix_operator(pos, state).and_then(|pos| skip(pos, state))
+                                })
                             })
                         }).and_then(|pos| {
                                 state
@@ -285,9 +422,12 @@ impl Parser<GrammarRule> for GrammarParser {
             state: &mut ParserState<'i, GrammarRule>
         ) -> Result<Position<'i>, Position<'i>> {
             push(pos, state)
+                .or_else(|pos| peek_slice(pos, state))
+                .or_else(|pos| call(pos, state))
                 .or_else(|pos| identifier(pos, state))
                 .or_else(|pos| string(pos, state))
                 .or_else(|pos| insensitive_string(pos, state))
+                .or_else(|pos| insensitive_range(pos, state))
                 .or_else(|pos| range(pos, state))
         }
 
@@ -482,6 +622,119 @@ impl Parser<GrammarRule> for GrammarParser {
             })
         }
 
+        fn call<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            // Only tried in front of `identifier (` so that it does not clutter errors of other
+            // terms.
+            pos.lookahead(true, |pos| {
+                pos.sequence(|pos| {
+                    pos.match_string("_")
+                        .or_else(|pos| alpha(pos, state))
+                        .and_then(|pos| {
+                            pos.repeat(|pos| {
+                                pos.match_string("_").or_else(|pos| alpha_num(pos, state))
+                            })
+                        })
+                        .and_then(|pos| skip(pos, state))
+                        .and_then(|pos| pos.match_string("("))
+                })
+            }).and_then(|pos| {
+                state.rule(GrammarRule::call, pos, |state, pos| {
+                    state.sequence(move |state| {
+                        pos.sequence(|pos| {
+                            identifier(pos, state)
+                                .and_then(|pos| skip(pos, state))
+                                .and_then(|pos| opening_paren(pos, state))
+                                .and_then(|pos| skip(pos, state))
+                                .and_then(|pos| expression(pos, state))
+                                .and_then(|pos| {
+                                    pos.repeat(|pos| {
+                                        state.sequence(move |state| {
+                                            pos.sequence(|pos| {
+                                                skip(pos, state)
+                                                    .and_then(|pos| comma(pos, state))
+                                                    .and_then(|pos| skip(pos, state))
+                                                    .and_then(|pos| expression(pos, state))
+                                            })
+                                        })
+                                    })
+                                })
+                                .and_then(|pos| skip(pos, state))
+                                .and_then(|pos| closing_paren(pos, state))
+                        })
+                    })
+                })
+            })
+        }
+
+        fn peek_slice<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            // Only tried in front of `peek` so that it does not clutter errors of other terms.
+            pos.lookahead(true, |pos| pos.match_string("peek")).and_then(|pos| {
+                state.rule(GrammarRule::peek_slice, pos, |state, pos| {
+                    state.sequence(move |state| {
+                        pos.sequence(|pos| {
+                            pos.match_string("peek")
+                                .and_then(|pos| skip(pos, state))
+                                .and_then(|pos| opening_brack(pos, state))
+                                .and_then(|pos| skip(pos, state))
+                                .and_then(|pos| {
+                                    pos.optional(|pos| {
+                                        pos.sequence(|pos| {
+                                            integer(pos, state).and_then(|pos| skip(pos, state))
+                                        })
+                                    })
+                                })
+                                .and_then(|pos| range_operator(pos, state))
+                                .and_then(|pos| skip(pos, state))
+                                .and_then(|pos| {
+                                    pos.optional(|pos| {
+                                        pos.sequence(|pos| {
+                                            integer(pos, state).and_then(|pos| skip(pos, state))
+                                        })
+                                    })
+                                })
+                                .and_then(|pos| closing_brack(pos, state))
+                        })
+                    })
+                })
+            })
+        }
+
+        fn integer<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            state.rule(GrammarRule::integer, pos, |state, pos| {
+                pos.sequence(|pos| {
+                    pos.optional(|pos| pos.match_string("-"))
+                        .and_then(|pos| number(pos, state))
+                })
+            })
+        }
+
+        fn opening_brack<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            state.rule(GrammarRule::opening_brack, pos, |_, pos| {
+                pos.match_string("[")
+            })
+        }
+
+        fn closing_brack<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            state.rule(GrammarRule::closing_brack, pos, |_, pos| {
+                pos.match_string("]")
+            })
+        }
+
         fn identifier<'i>(
             pos: Position<'i>,
             state: &mut ParserState<'i, GrammarRule>
@@ -499,6 +752,26 @@ impl Parser<GrammarRule> for GrammarParser {
             })
         }
 
+        fn tag_id<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            // Only tried in front of `#` so that it does not clutter errors of other terms.
+            pos.lookahead(true, |pos| pos.match_string("#")).and_then(|pos| {
+                state.rule(GrammarRule::tag_id, pos, |state, pos| {
+                    pos.sequence(|pos| {
+                        pos.match_string("#")
+                            .and_then(|pos| pos.match_string("_").or_else(|pos| alpha(pos, state)))
+                            .and_then(|pos| {
+                                pos.repeat(|pos| {
+                                    pos.match_string("_").or_else(|pos| alpha_num(pos, state))
+                                })
+                            })
+                    })
+                })
+            })
+        }
+
         fn alpha<'i>(
             pos: Position<'i>,
             _: &mut ParserState<'i, GrammarRule>
@@ -555,6 +828,26 @@ impl Parser<GrammarRule> for GrammarParser {
             })
         }
 
+        fn insensitive_range<'i>(
+            pos: Position<'i>,
+            state: &mut ParserState<'i, GrammarRule>
+        ) -> Result<Position<'i>, Position<'i>> {
+            // Only tried in front of `^'` so that it does not clutter errors of `^"`.
+            pos.lookahead(true, |pos| {
+                pos.match_string("^")
+                    .and_then(|pos| skip(pos, state))
+                    .and_then(|pos| pos.match_string("'"))
+            }).and_then(|pos| {
+                state.rule(GrammarRule::insensitive_range, pos, |state, pos| {
+                    pos.sequence(|pos| {
+                        pos.match_string("^")
+                            .and_then(|pos| skip(pos, state))
+                            .and_then(|pos| range(pos, state))
+                    })
+                })
+            })
+        }
+
         fn range<'i>(
             pos: Position<'i>,
             state: &mut ParserState<'i, GrammarRule>
@@ -711,14 +1004,28 @@ impl Parser<GrammarRule> for GrammarParser {
             pos: Position<'i>,
             _: &mut ParserState<'i, GrammarRule>
         ) -> Result<Position<'i>, Position<'i>> {
+            // `//!` and `///` start doc comments, but `////` and longer do not.
             pos.sequence(|pos| {
-                pos.match_string("//").and_then(|pos| {
-                    pos.repeat(|pos| {
-                        pos.sequence(|pos| {
-                            pos.lookahead(false, |pos| pos.match_string("\n"))
-                                .and_then(|pos| pos.skip(1))
+                pos.match_string("//")
+                    .and_then(|pos| {
+                        pos.lookahead(false, |pos| {
+                            pos.match_string("!").or_else(|pos| {
+                                pos.sequence(|pos| {
+                                    pos.match_string("/").and_then(|pos| {
+                                        pos.lookahead(false, |pos| pos.match_string("/"))
+                                    })
+                                })
+                            })
                         })
                     })
+                    .and_then(|pos| rest_of_line(pos))
+            })
+        }
+
+        fn rest_of_line<'i>(pos: Position<'i>) -> Result<Position<'i>, Position<'i>> {
+            pos.repeat(|pos| {
+                pos.sequence(|pos| {
+                    pos.lookahead(false, |pos| pos.match_string("\n")).and_then(|pos| pos.skip(1))
                 })
             })
         }
@@ -728,6 +1035,11 @@ impl Parser<GrammarRule> for GrammarParser {
             GrammarRule::soi => soi(pos, &mut state),
             GrammarRule::eoi => eoi(pos, &mut state),
             GrammarRule::grammar_rule => grammar_rule(pos, &mut state),
+            GrammarRule::grammar_doc => grammar_doc(pos, &mut state),
+            GrammarRule::line_doc => line_doc(pos, &mut state),
+            GrammarRule::import => import(pos, &mut state),
+            GrammarRule::override_keyword => override_keyword(pos, &mut state),
+            GrammarRule::parameters => parameters(pos, &mut state),
             GrammarRule::assignment_operator => assignment_operator(pos, &mut state),
             GrammarRule::silent_modifier => silent_modifier(pos, &mut state),
             GrammarRule::atomic_modifier => atomic_modifier(pos, &mut state),
@@ -756,10 +1068,17 @@ impl Parser<GrammarRule> for GrammarParser {
             GrammarRule::repeat_min_max => repeat_min_max(pos, &mut state),
             GrammarRule::comma => comma(pos, &mut state),
             GrammarRule::push => push(pos, &mut state),
+            GrammarRule::peek_slice => peek_slice(pos, &mut state),
+            GrammarRule::call => call(pos, &mut state),
+            GrammarRule::integer => integer(pos, &mut state),
+            GrammarRule::opening_brack => opening_brack(pos, &mut state),
+            GrammarRule::closing_brack => closing_brack(pos, &mut state),
             GrammarRule::identifier => identifier(pos, &mut state),
+            GrammarRule::tag_id => tag_id(pos, &mut state),
             GrammarRule::string => string(pos, &mut state),
             GrammarRule::quote => quote(pos, &mut state),
             GrammarRule::insensitive_string => insensitive_string(pos, &mut state),
+            GrammarRule::insensitive_range => insensitive_range(pos, &mut state),
             GrammarRule::range => range(pos, &mut state),
             GrammarRule::range_operator => range_operator(pos, &mut state),
             GrammarRule::character => character(pos, &mut state),
@@ -769,26 +1088,40 @@ impl Parser<GrammarRule> for GrammarParser {
     }
 }
 
+/// A `struct` of a grammar rule which keeps the `Span`s of its name and of all its expressions.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ParserRule<'i> {
-    pub name: Ident,
+    /// Name of the rule
+    pub name: String,
+    /// `Span` of the rule's name
     pub span: Span<'i>,
+    /// Type of the rule, given by its modifier
     pub ty: RuleType,
+    /// Names of the rule's parameters, empty if it takes no arguments
+    pub params: Vec<String>,
+    /// Lines of the rule's `///` doc comment, without the leading `///`
+    pub doc: Vec<String>,
+    /// Body of the rule
     pub node: ParserNode<'i>
 }
 
+/// A `struct` of a grammar expression together with its `Span`.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ParserNode<'i> {
+    /// The expression
     pub expr: ParserExpr<'i>,
+    /// `Span` of the expression in the grammar
     pub span: Span<'i>
 }
 
+/// An `enum` of spanned grammar expressions, mirroring [`Expr`](../ast/enum.Expr.html).
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ParserExpr<'i> {
     Str(String),
     Insens(String),
     Range(String, String),
-    Ident(Ident),
+    InsensRange(String, String),
+    Ident(String),
     PosPred(Box<ParserNode<'i>>),
     NegPred(Box<ParserNode<'i>>),
     Seq(Box<ParserNode<'i>>, Box<ParserNode<'i>>),
@@ -800,15 +1133,18 @@ pub enum ParserExpr<'i> {
     RepMin(Box<ParserNode<'i>>, u32),
     RepMax(Box<ParserNode<'i>>, u32),
     RepMinMax(Box<ParserNode<'i>>, u32, u32),
-    Push(Box<ParserNode<'i>>)
+    Push(Box<ParserNode<'i>>),
+    PeekSlice(i32, Option<i32>),
+    NodeTag(Box<ParserNode<'i>>, String),
+    Call(String, Vec<ParserNode<'i>>)
 }
 
 fn convert_rule<'i>(rule: ParserRule<'i>) -> Rule {
     match rule {
-        ParserRule { name, ty, node, .. } => {
+        ParserRule { name, ty, doc, node, .. } => {
             let expr = convert_node(node);
 
-            Rule { name, ty, expr }
+            Rule { name, ty, doc, expr }
         }
     }
 }
@@ -818,6 +1154,7 @@ fn convert_node<'i>(node: ParserNode<'i>) -> Expr {
         ParserExpr::Str(string) => Expr::Str(string),
         ParserExpr::Insens(string) => Expr::Insens(string),
         ParserExpr::Range(start, end) => Expr::Range(start, end),
+        ParserExpr::InsensRange(start, end) => Expr::InsensRange(start, end),
         ParserExpr::Ident(ident) => Expr::Ident(ident),
         ParserExpr::PosPred(node) => Expr::PosPred(Box::new(convert_node(*node))),
         ParserExpr::NegPred(node) => Expr::NegPred(Box::new(convert_node(*node))),
@@ -838,35 +1175,184 @@ fn convert_node<'i>(node: ParserNode<'i>) -> Expr {
         ParserExpr::RepMinMax(node, min, max) => {
             Expr::RepMinMax(Box::new(convert_node(*node)), min, max)
         }
-        ParserExpr::Push(node) => Expr::Push(Box::new(convert_node(*node)))
+        ParserExpr::Push(node) => Expr::Push(Box::new(convert_node(*node))),
+        ParserExpr::PeekSlice(start, end) => Expr::PeekSlice(start, end),
+        ParserExpr::NodeTag(node, tag) => Expr::NodeTag(Box::new(convert_node(*node)), tag),
+        ParserExpr::Call(..) => unreachable!("calls are expanded before conversion")
     }
 }
 
-pub fn consume_rules<'i>(pairs: Pairs<'i, GrammarRule>) -> (Vec<Rule>, Vec<Ident>) {
-    let defaults = validator::validate_pairs(pairs.clone());
-    let rules = consume_rules_with_spans(pairs);
+/// Expands every call of a parameterized rule in `rules` by substituting its arguments for its
+/// parameters, dropping the parameterized rules themselves. Since the expanded expressions are
+/// inlined, parameterized rules are always silent.
+///
+/// The `rules` should be validated with [`validate_ast`](../validator/fn.validate_ast.html)
+/// first.
+///
+/// # Panics
+///
+/// Panics if a called rule is not a parameterized rule of `rules`.
+pub fn expand_parameterized_rules<'i>(rules: Vec<ParserRule<'i>>) -> Vec<ParserRule<'i>> {
+    let parameterized: HashMap<_, _> = rules
+        .iter()
+        .filter(|rule| !rule.params.is_empty())
+        .map(|rule| (rule.name.clone(), rule.clone()))
+        .collect();
+
+    rules
+        .into_iter()
+        .filter(|rule| rule.params.is_empty())
+        .map(|rule| ParserRule {
+            node: expand_node(rule.node, &parameterized, &HashMap::new()),
+            ..rule
+        })
+        .collect()
+}
 
-    validator::validate_ast(&rules);
+// Expands the calls in `node`, replacing the parameters in `args` with their expanded arguments.
+fn expand_node<'i>(
+    node: ParserNode<'i>,
+    rules: &HashMap<String, ParserRule<'i>>,
+    args: &HashMap<String, ParserNode<'i>>
+) -> ParserNode<'i> {
+    let expand = |node: Box<ParserNode<'i>>| Box::new(expand_node(*node, rules, args));
+
+    let expr = match node.expr {
+        ParserExpr::Ident(ident) => match args.get(&ident) {
+            Some(arg) => return arg.clone(),
+            None => ParserExpr::Ident(ident)
+        },
+        ParserExpr::Call(name, call_args) => {
+            let rule = &rules[&name];
+            let call_args = rule.params
+                .iter()
+                .cloned()
+                .zip(call_args.into_iter().map(|arg| expand_node(arg, rules, args)))
+                .collect();
+
+            expand_node(rule.node.clone(), rules, &call_args).expr
+        }
+        ParserExpr::PosPred(node) => ParserExpr::PosPred(expand(node)),
+        ParserExpr::NegPred(node) => ParserExpr::NegPred(expand(node)),
+        ParserExpr::Seq(lhs, rhs) => ParserExpr::Seq(expand(lhs), expand(rhs)),
+        ParserExpr::Choice(lhs, rhs) => ParserExpr::Choice(expand(lhs), expand(rhs)),
+        ParserExpr::Opt(node) => ParserExpr::Opt(expand(node)),
+        ParserExpr::Rep(node) => ParserExpr::Rep(expand(node)),
+        ParserExpr::RepOnce(node) => ParserExpr::RepOnce(expand(node)),
+        ParserExpr::RepExact(node, num) => ParserExpr::RepExact(expand(node), num),
+        ParserExpr::RepMin(node, min) => ParserExpr::RepMin(expand(node), min),
+        ParserExpr::RepMax(node, max) => ParserExpr::RepMax(expand(node), max),
+        ParserExpr::RepMinMax(node, min, max) => ParserExpr::RepMinMax(expand(node), min, max),
+        ParserExpr::Push(node) => ParserExpr::Push(expand(node)),
+        ParserExpr::NodeTag(node, tag) => ParserExpr::NodeTag(expand(node), tag),
+        expr => expr
+    };
+
+    ParserNode {
+        expr,
+        span: node.span
+    }
+}
 
-    (
-        rules.into_iter().map(|rule| convert_rule(rule)).collect(),
-        defaults
-    )
+/// Returns a human-readable description of `rule`, to be used with
+/// [`Error::renamed_rules`](../../pest/enum.Error.html#method.renamed_rules).
+pub fn rename_meta_rule(rule: &GrammarRule) -> String {
+    match *rule {
+        GrammarRule::grammar_rule => "rule".to_owned(),
+        GrammarRule::import => "import".to_owned(),
+        GrammarRule::grammar_doc => "grammar doc comment".to_owned(),
+        GrammarRule::line_doc => "doc comment".to_owned(),
+        GrammarRule::override_keyword => "`override`".to_owned(),
+        GrammarRule::eoi => "end-of-input".to_owned(),
+        GrammarRule::assignment_operator => "`=`".to_owned(),
+        GrammarRule::silent_modifier => "`_`".to_owned(),
+        GrammarRule::atomic_modifier => "`@`".to_owned(),
+        GrammarRule::compound_atomic_modifier => "`$`".to_owned(),
+        GrammarRule::non_atomic_modifier => "`!`".to_owned(),
+        GrammarRule::opening_brace => "`{`".to_owned(),
+        GrammarRule::closing_brace => "`}`".to_owned(),
+        GrammarRule::opening_paren => "`(`".to_owned(),
+        GrammarRule::positive_predicate_operator => "`&`".to_owned(),
+        GrammarRule::negative_predicate_operator => "`!`".to_owned(),
+        GrammarRule::sequence_operator => "`&`".to_owned(),
+        GrammarRule::choice_operator => "`|`".to_owned(),
+        GrammarRule::optional_operator => "`?`".to_owned(),
+        GrammarRule::repeat_operator => "`*`".to_owned(),
+        GrammarRule::repeat_once_operator => "`+`".to_owned(),
+        GrammarRule::comma => "`,`".to_owned(),
+        GrammarRule::closing_paren => "`)`".to_owned(),
+        GrammarRule::quote => "`\"`".to_owned(),
+        GrammarRule::insensitive_string => "`^`".to_owned(),
+        GrammarRule::insensitive_range => "`^`".to_owned(),
+        GrammarRule::range_operator => "`..`".to_owned(),
+        GrammarRule::tag_id => "node tag".to_owned(),
+        GrammarRule::single_quote => "`'`".to_owned(),
+        other_rule => format!("{:?}", other_rule)
+    }
 }
 
-fn consume_rules_with_spans<'i>(pairs: Pairs<'i, GrammarRule>) -> Vec<ParserRule<'i>> {
+/// Consumes the grammar `pairs` returned by [`GrammarParser`](struct.GrammarParser.html) into
+/// `Rule`s, validating them with [`validate_ast`](../validator/fn.validate_ast.html) and expanding
+/// them with [`expand_parameterized_rules`](fn.expand_parameterized_rules.html).
+///
+/// The `pairs` should be validated with
+/// [`validate_pairs`](../validator/fn.validate_pairs.html) first.
+pub fn consume_rules<'i>(
+    pairs: Pairs<'i, GrammarRule>
+) -> Result<Vec<Rule>, Vec<Error<'i, GrammarRule>>> {
+    let rules = consume_rules_with_spans(pairs)?;
+    let errors = validator::validate_ast(&rules);
+
+    if errors.is_empty() {
+        Ok(expand_parameterized_rules(rules)
+            .into_iter()
+            .map(|rule| convert_rule(rule))
+            .collect())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Consumes the grammar `pairs` returned by [`GrammarParser`](struct.GrammarParser.html) into
+/// `ParserRule`s which keep the `Span` of every expression. Fails if a repetition or `peek`
+/// bound does not fit its integer type.
+pub fn consume_rules_with_spans<'i>(
+    pairs: Pairs<'i, GrammarRule>
+) -> Result<Vec<ParserRule<'i>>, Vec<Error<'i, GrammarRule>>> {
     let climber = PrecClimber::new(vec![
         Operator::new(GrammarRule::choice_operator, Assoc::Left),
         Operator::new(GrammarRule::sequence_operator, Assoc::Left),
     ]);
 
-    pairs
+    let rules: Vec<(bool, ParserRule<'i>)> = pairs
         .filter(|pair| pair.as_rule() == GrammarRule::grammar_rule)
         .map(|pair| {
             let mut pairs = pair.into_inner().peekable();
 
+            let mut doc = vec![];
+            while pairs.peek().unwrap().as_rule() == GrammarRule::line_doc {
+                doc.push(doc_line(pairs.next().unwrap()));
+            }
+
+            let is_override = pairs.peek().unwrap().as_rule() == GrammarRule::override_keyword;
+            if is_override {
+                pairs.next().unwrap(); // override_keyword
+            }
+
             let span = pairs.next().unwrap().into_span();
-            let name = Ident::new(span.as_str());
+            let name = span.as_str().to_owned();
+
+            let params = if pairs.peek().unwrap().as_rule() == GrammarRule::parameters {
+                pairs
+                    .next()
+                    .unwrap()
+                    .into_inner()
+                    .filter(|pair| pair.as_rule() == GrammarRule::identifier)
+                    .map(|pair| pair.as_str().to_owned())
+                    .collect()
+            } else {
+                vec![]
+            };
 
             pairs.next().unwrap(); // assignment_operator
 
@@ -884,14 +1370,79 @@ fn consume_rules_with_spans<'i>(pairs: Pairs<'i, GrammarRule>) -> Vec<ParserRule
 
             pairs.next().unwrap(); // opening_brace
 
-            let node = consume_expr(pairs.next().unwrap().into_inner().peekable(), &climber);
+            let node = consume_expr(pairs.next().unwrap().into_inner().peekable(), &climber)?;
+
+            Ok((
+                is_override,
+                ParserRule {
+                    name,
+                    span,
+                    ty,
+                    params,
+                    doc,
+                    node
+                }
+            ))
+        })
+        .collect::<Result<_, Vec<Error<'i, GrammarRule>>>>()?;
+
+    Ok(apply_overrides(rules))
+}
 
-            ParserRule {
-                name,
-                span,
-                ty,
-                node
+/// Returns the lines of the grammar's `//!` doc comments, without the leading `//!`, from the
+/// grammar `pairs` returned by [`GrammarParser`](struct.GrammarParser.html).
+///
+/// # Examples
+///
+/// ```
+/// # extern crate pest;
+/// # extern crate pest_meta;
+/// # use pest::Parser;
+/// # use pest_meta::parser::{self, GrammarParser, GrammarRule};
+/// # fn main() {
+/// let input = "//! A grammar of `a`s.\n\n/// An `a`.\na = { \"a\" }";
+/// let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+///
+/// assert_eq!(parser::consume_grammar_doc(pairs.clone()), vec![" A grammar of `a`s."]);
+/// assert_eq!(parser::consume_rules(pairs).unwrap()[0].doc, vec![" An `a`."]);
+/// # }
+/// ```
+pub fn consume_grammar_doc<'i>(pairs: Pairs<'i, GrammarRule>) -> Vec<String> {
+    pairs
+        .filter(|pair| pair.as_rule() == GrammarRule::grammar_doc)
+        .map(|pair| doc_line(pair))
+        .collect()
+}
+
+fn doc_line<'i>(pair: Pair<'i, GrammarRule>) -> String {
+    pair.as_str()[3..].trim_end_matches('\r').to_owned()
+}
+
+// Replaces every overridden rule with the last rule overriding it, keeping its place in the
+// grammar. Overriding rules without a doc comment keep the one of the rule they override.
+fn apply_overrides<'i>(rules: Vec<(bool, ParserRule<'i>)>) -> Vec<ParserRule<'i>> {
+    let mut overrides = HashMap::new();
+    let mut result = vec![];
+
+    for (is_override, rule) in rules {
+        if is_override {
+            overrides.insert(rule.name.clone(), rule);
+        } else {
+            result.push(rule);
+        }
+    }
+
+    result
+        .into_iter()
+        .map(|rule| match overrides.remove(&rule.name) {
+            Some(over) => {
+                if over.doc.is_empty() {
+                    ParserRule { doc: rule.doc, ..over }
+                } else {
+                    over
+                }
             }
+            None => rule
         })
         .collect()
 }
@@ -899,16 +1450,16 @@ fn consume_rules_with_spans<'i>(pairs: Pairs<'i, GrammarRule>) -> Vec<ParserRule
 fn consume_expr<'i>(
     pairs: Peekable<Pairs<'i, GrammarRule>>,
     climber: &PrecClimber<GrammarRule>
-) -> ParserNode<'i> {
+) -> Result<ParserNode<'i>, Vec<Error<'i, GrammarRule>>> {
     fn unaries<'i>(
         mut pairs: Peekable<Pairs<'i, GrammarRule>>,
         climber: &PrecClimber<GrammarRule>
-    ) -> ParserNode<'i> {
+    ) -> Result<ParserNode<'i>, Vec<Error<'i, GrammarRule>>> {
         let pair = pairs.next().unwrap();
 
-        match pair.as_rule() {
+        let node = match pair.as_rule() {
             GrammarRule::opening_paren => {
-                let node = unaries(pairs, climber);
+                let node = unaries(pairs, climber)?;
                 let end = node.span.end_pos();
 
                 ParserNode {
@@ -917,7 +1468,7 @@ fn consume_expr<'i>(
                 }
             }
             GrammarRule::positive_predicate_operator => {
-                let node = unaries(pairs, climber);
+                let node = unaries(pairs, climber)?;
                 let end = node.span.end_pos();
 
                 ParserNode {
@@ -926,7 +1477,7 @@ fn consume_expr<'i>(
                 }
             }
             GrammarRule::negative_predicate_operator => {
-                let node = unaries(pairs, climber);
+                let node = unaries(pairs, climber)?;
                 let end = node.span.end_pos();
 
                 ParserNode {
@@ -936,14 +1487,14 @@ fn consume_expr<'i>(
             }
             other_rule => {
                 let node = match other_rule {
-                    GrammarRule::expression => consume_expr(pair.into_inner().peekable(), climber),
+                    GrammarRule::expression => consume_expr(pair.into_inner().peekable(), climber)?,
                     GrammarRule::push => {
                         let start = pair.clone().into_span().start_pos();
                         let mut pairs = pair.into_inner();
                         pairs.next().unwrap(); // opening_paren
                         let pair = pairs.next().unwrap();
 
-                        let node = consume_expr(pair.into_inner().peekable(), climber);
+                        let node = consume_expr(pair.into_inner().peekable(), climber)?;
                         let end = node.span.end_pos();
 
                         ParserNode {
@@ -951,8 +1502,52 @@ fn consume_expr<'i>(
                             span: start.span(&end)
                         }
                     }
+                    GrammarRule::peek_slice => {
+                        let span = pair.clone().into_span();
+                        let mut pairs = pair.into_inner();
+
+                        pairs.next().unwrap(); // opening_brack
+
+                        let pair = pairs.next().unwrap();
+                        let start = if pair.as_rule() == GrammarRule::integer {
+                            let start = parse_number(pair, "number cannot overflow i32")?;
+
+                            pairs.next().unwrap(); // range_operator
+
+                            start
+                        } else {
+                            0
+                        };
+
+                        let pair = pairs.next().unwrap();
+                        let end = if pair.as_rule() == GrammarRule::integer {
+                            Some(parse_number(pair, "number cannot overflow i32")?)
+                        } else {
+                            None
+                        };
+
+                        ParserNode {
+                            expr: ParserExpr::PeekSlice(start, end),
+                            span
+                        }
+                    }
+                    GrammarRule::call => {
+                        let span = pair.clone().into_span();
+                        let mut pairs = pair.into_inner();
+                        let name = pairs.next().unwrap().as_str().to_owned();
+
+                        let args = pairs
+                            .filter(|pair| pair.as_rule() == GrammarRule::expression)
+                            .map(|pair| consume_expr(pair.into_inner().peekable(), climber))
+                            .collect::<Result<_, _>>()?;
+
+                        ParserNode {
+                            expr: ParserExpr::Call(name, args),
+                            span
+                        }
+                    }
                     GrammarRule::identifier => ParserNode {
-                        expr: ParserExpr::Ident(Ident::new(pair.as_str())),
+                        expr: ParserExpr::Ident(pair.as_str().to_owned()),
                         span: pair.clone().into_span()
                     },
                     GrammarRule::string => {
@@ -984,11 +1579,23 @@ fn consume_expr<'i>(
                             span: start_pos.span(&end_pos)
                         }
                     }
+                    GrammarRule::insensitive_range => {
+                        let span = pair.clone().into_span();
+                        let mut pairs = pair.into_inner().next().unwrap().into_inner();
+                        let start = pairs.next().unwrap().as_str();
+                        pairs.next();
+                        let end = pairs.next().unwrap().as_str();
+
+                        ParserNode {
+                            expr: ParserExpr::InsensRange(start.to_owned(), end.to_owned()),
+                            span
+                        }
+                    }
                     _ => unreachable!()
                 };
 
-                pairs.fold(node, |node, pair| {
-                    match pair.as_rule() {
+                pairs.try_fold(node, |node, pair| -> Result<_, Vec<Error<'i, GrammarRule>>> {
+                    let node = match pair.as_rule() {
                         GrammarRule::optional_operator => {
                             let start = node.span.start_pos();
                             ParserNode {
@@ -1011,24 +1618,12 @@ fn consume_expr<'i>(
                             }
                         }
                         GrammarRule::repeat_exact => {
-                            let overflow = |span| {
-                                let error: Error<()> = Error::CustomErrorSpan {
-                                    message: "number cannot overflow u32".to_owned(),
-                                    span
-                                };
-
-                                format!("parsing error\n\n{}", error)
-                            };
-
                             let mut inner = pair.clone().into_inner();
 
                             inner.next().unwrap(); // opening_brace
 
                             let number = inner.next().unwrap();
-                            let num: u32 = number
-                                .as_str()
-                                .parse()
-                                .expect(&overflow(number.into_span()));
+                            let num = parse_number(number, "number cannot overflow u32")?;
 
                             let start = node.span.start_pos();
                             ParserNode {
@@ -1037,24 +1632,12 @@ fn consume_expr<'i>(
                             }
                         }
                         GrammarRule::repeat_min => {
-                            let overflow = |span| {
-                                let error: Error<()> = Error::CustomErrorSpan {
-                                    message: "number cannot overflow u32".to_owned(),
-                                    span
-                                };
-
-                                format!("parsing error\n\n{}", error)
-                            };
-
                             let mut inner = pair.clone().into_inner();
 
                             inner.next().unwrap(); // opening_brace
 
                             let min_number = inner.next().unwrap();
-                            let min: u32 = min_number
-                                .as_str()
-                                .parse()
-                                .expect(&overflow(min_number.into_span()));
+                            let min = parse_number(min_number, "number cannot overflow u32")?;
 
                             let start = node.span.start_pos();
                             ParserNode {
@@ -1063,25 +1646,13 @@ fn consume_expr<'i>(
                             }
                         }
                         GrammarRule::repeat_max => {
-                            let overflow = |span| {
-                                let error: Error<()> = Error::CustomErrorSpan {
-                                    message: "number cannot overflow u32".to_owned(),
-                                    span
-                                };
-
-                                format!("parsing error\n\n{}", error)
-                            };
-
                             let mut inner = pair.clone().into_inner();
 
                             inner.next().unwrap(); // opening_brace
                             inner.next().unwrap(); // comma
 
                             let max_number = inner.next().unwrap();
-                            let max: u32 = max_number
-                                .as_str()
-                                .parse()
-                                .expect(&overflow(max_number.into_span()));
+                            let max = parse_number(max_number, "number cannot overflow u32")?;
 
                             let start = node.span.start_pos();
                             ParserNode {
@@ -1090,32 +1661,17 @@ fn consume_expr<'i>(
                             }
                         }
                         GrammarRule::repeat_min_max => {
-                            let overflow = |span| {
-                                let error: Error<()> = Error::CustomErrorSpan {
-                                    message: "number cannot overflow u32".to_owned(),
-                                    span
-                                };
-
-                                format!("parsing error\n\n{}", error)
-                            };
-
                             let mut inner = pair.clone().into_inner();
 
                             inner.next().unwrap(); // opening_brace
 
                             let min_number = inner.next().unwrap();
-                            let min: u32 = min_number
-                                .as_str()
-                                .parse()
-                                .expect(&overflow(min_number.into_span()));
+                            let min = parse_number(min_number, "number cannot overflow u32")?;
 
                             inner.next().unwrap(); // comma
 
                             let max_number = inner.next().unwrap();
-                            let max: u32 = max_number
-                                .as_str()
-                                .parse()
-                                .expect(&overflow(max_number.into_span()));
+                            let max = parse_number(max_number, "number cannot overflow u32")?;
 
                             let start = node.span.start_pos();
                             ParserNode {
@@ -1132,39 +1688,69 @@ fn consume_expr<'i>(
                             }
                         }
                         _ => unreachable!()
-                    }
-                })
+                    };
+
+                    Ok(node)
+                })?
             }
-        }
+        };
+
+        Ok(node)
     }
 
-    let term = |pair: Pair<'i, GrammarRule>| unaries(pair.into_inner().peekable(), climber);
-    let infix =
-        |lhs: ParserNode<'i>, op: Pair<'i, GrammarRule>, rhs: ParserNode<'i>| match op.as_rule() {
-            GrammarRule::sequence_operator => {
-                let start = lhs.span.start_pos();
-                let end = rhs.span.end_pos();
+    let term = |pair: Pair<'i, GrammarRule>| {
+        let mut pairs = pair.into_inner().peekable();
 
-                ParserNode {
-                    expr: ParserExpr::Seq(Box::new(lhs), Box::new(rhs)),
-                    span: start.span(&end)
-                }
-            }
-            GrammarRule::choice_operator => {
-                let start = lhs.span.start_pos();
-                let end = rhs.span.end_pos();
+        if pairs.peek().unwrap().as_rule() != GrammarRule::tag_id {
+            return unaries(pairs, climber);
+        }
 
-                ParserNode {
-                    expr: ParserExpr::Choice(Box::new(lhs), Box::new(rhs)),
-                    span: start.span(&end)
-                }
-            }
+        let tag = pairs.next().unwrap();
+        pairs.next().unwrap(); // assignment_operator
+
+        let node = unaries(pairs, climber)?;
+        let end = node.span.end_pos();
+
+        Ok(ParserNode {
+            expr: ParserExpr::NodeTag(Box::new(node), tag.as_str()[1..].to_owned()),
+            span: tag.into_span().start_pos().span(&end)
+        })
+    };
+    let infix = |lhs: Result<ParserNode<'i>, Vec<Error<'i, GrammarRule>>>,
+                 op: Pair<'i, GrammarRule>,
+                 rhs: Result<ParserNode<'i>, Vec<Error<'i, GrammarRule>>>| {
+        let lhs = lhs?;
+        let rhs = rhs?;
+        let start = lhs.span.start_pos();
+        let end = rhs.span.end_pos();
+
+        let expr = match op.as_rule() {
+            GrammarRule::sequence_operator => ParserExpr::Seq(Box::new(lhs), Box::new(rhs)),
+            GrammarRule::choice_operator => ParserExpr::Choice(Box::new(lhs), Box::new(rhs)),
             _ => unreachable!()
         };
 
+        Ok(ParserNode {
+            expr,
+            span: start.span(&end)
+        })
+    };
+
     climber.climb(pairs, term, infix)
 }
 
+fn parse_number<'i, T: FromStr>(
+    pair: Pair<'i, GrammarRule>,
+    message: &str
+) -> Result<T, Vec<Error<'i, GrammarRule>>> {
+    pair.as_str().parse().map_err(|_| {
+        vec![Error::CustomErrorSpan {
+            message: message.to_owned(),
+            span: pair.into_span()
+        }]
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1202,6 +1788,115 @@ mod tests {
         };
     }
 
+    #[test]
+    fn imports() {
+        parses_to! {
+            parser: GrammarParser,
+            input: "import \"a.pest\" import = { b }",
+            rule: GrammarRule::grammar_rules,
+            tokens: [
+                import(0, 15, [
+                    string(7, 15, [
+                        quote(7, 8),
+                        quote(14, 15)
+                    ])
+                ]),
+                grammar_rule(16, 30, [
+                    identifier(16, 22),
+                    assignment_operator(23, 24),
+                    opening_brace(25, 26),
+                    expression(27, 28, [
+                        term(27, 28, [
+                            identifier(27, 28)
+                        ])
+                    ]),
+                    closing_brace(29, 30)
+                ])
+            ]
+        };
+    }
+
+    #[test]
+    fn doc_comments() {
+        parses_to! {
+            parser: GrammarParser,
+            input: "//! g\n/// a\n//// c\na = { b }",
+            rule: GrammarRule::grammar_rules,
+            tokens: [
+                grammar_doc(0, 5),
+                grammar_rule(6, 28, [
+                    line_doc(6, 11),
+                    identifier(19, 20),
+                    assignment_operator(21, 22),
+                    opening_brace(23, 24),
+                    expression(25, 26, [
+                        term(25, 26, [
+                            identifier(25, 26)
+                        ])
+                    ]),
+                    closing_brace(27, 28)
+                ])
+            ]
+        };
+    }
+
+    #[test]
+    fn override_rule() {
+        parses_to! {
+            parser: GrammarParser,
+            input: "override a = { b }",
+            rule: GrammarRule::grammar_rule,
+            tokens: [
+                grammar_rule(0, 18, [
+                    override_keyword(0, 8),
+                    identifier(9, 10),
+                    assignment_operator(11, 12),
+                    opening_brace(13, 14),
+                    expression(15, 16, [
+                        term(15, 16, [
+                            identifier(15, 16)
+                        ])
+                    ]),
+                    closing_brace(17, 18)
+                ])
+            ]
+        };
+    }
+
+    #[test]
+    fn parameterized_rule() {
+        parses_to! {
+            parser: GrammarParser,
+            input: "a(x, y) = _{ x ~ y }",
+            rule: GrammarRule::grammar_rule,
+            tokens: [
+                grammar_rule(0, 20, [
+                    identifier(0, 1),
+                    parameters(1, 7, [
+                        opening_paren(1, 2),
+                        identifier(2, 3),
+                        comma(3, 4),
+                        identifier(5, 6),
+                        closing_paren(6, 7)
+                    ]),
+                    assignment_operator(8, 9),
+                    silent_modifier(10, 11),
+                    opening_brace(11, 12),
+                    expression(13, 18, [
+                        term(13, 14, [
+                            identifier(13, 14)
+                        ]),
+                        sequence_operator(15, 16),
+                        term(17, 18, [
+                            identifier(17, 18)
+                        ])
+                    ]),
+                    closing_brace(19, 20)
+                ])
+            ]
+        };
+    }
+
     #[test]
     fn rule() {
         parses_to! {
@@ -1373,6 +2068,91 @@ mod tests {
         };
     }
 
+    #[test]
+    fn peek_slice_all() {
+        parses_to! {
+            parser: GrammarParser,
+            input: "peek[..]",
+            rule: GrammarRule::peek_slice,
+            tokens: [
+                peek_slice(0, 8, [
+                    opening_brack(4, 5),
+                    range_operator(5, 7),
+                    closing_brack(7, 8)
+                ])
+            ]
+        };
+    }
+
+    #[test]
+    fn peek_slice_start_end() {
+        parses_to! {
+            parser: GrammarParser,
+            input: "peek [ 1 .. -2 ]",
+            rule: GrammarRule::peek_slice,
+            tokens: [
+                peek_slice(0, 16, [
+                    opening_brack(5, 6),
+                    integer(7, 8, [
+                        number(7, 8)
+                    ]),
+                    range_operator(9, 11),
+                    integer(12, 14, [
+                        number(13, 14)
+                    ]),
+                    closing_brack(15, 16)
+                ])
+            ]
+        };
+    }
+
+    #[test]
+    fn call() {
+        parses_to! {
+            parser: GrammarParser,
+            input: "a (b, \"c\")",
+            rule: GrammarRule::call,
+            tokens: [
+                call(0, 10, [
+                    identifier(0, 1),
+                    opening_paren(2, 3),
+                    expression(3, 4, [
+                        term(3, 4, [
+                            identifier(3, 4)
+                        ])
+                    ]),
+                    comma(4, 5),
+                    expression(6, 9, [
+                        term(6, 9, [
+                            string(6, 9, [
+                                quote(6, 7),
+                                quote(8, 9)
+                            ])
+                        ])
+                    ]),
+                    closing_paren(9, 10)
+                ])
+            ]
+        };
+    }
+
+    #[test]
+    fn tagged_term() {
+        parses_to! {
+            parser: GrammarParser,
+            input: "#lhs = !a",
+            rule: GrammarRule::term,
+            tokens: [
+                term(0, 9, [
+                    tag_id(0, 4),
+                    assignment_operator(5, 6),
+                    negative_predicate_operator(7, 8),
+                    identifier(8, 9)
+                ])
+            ]
+        };
+    }
+
     #[test]
     fn identifier() {
         parses_to! {
@@ -1439,6 +2219,30 @@ mod tests {
         };
     }
 
+    #[test]
+    fn insensitive_range() {
+        parses_to! {
+            parser: GrammarParser,
+            input: "^ 'a'..'z'",
+            rule: GrammarRule::insensitive_range,
+            tokens: [
+                insensitive_range(0, 10, [
+                    range(2, 10, [
+                        character(2, 5, [
+                            single_quote(2, 3),
+                            single_quote(4, 5)
+                        ]),
+                        range_operator(5, 7),
+                        character(7, 10, [
+                            single_quote(7, 8),
+                            single_quote(9, 10)
+                        ])
+                    ])
+                ])
+            ]
+        };
+    }
+
     #[test]
     fn character() {
         parses_to! {
@@ -1492,7 +2296,7 @@ mod tests {
             parser: GrammarParser,
             input: "0",
             rule: GrammarRule::grammar_rules,
-            positives: vec![GrammarRule::identifier],
+            positives: vec![GrammarRule::grammar_rule, GrammarRule::import],
             negatives: vec![],
             pos: 0
         };
@@ -1504,7 +2308,7 @@ mod tests {
             parser: GrammarParser,
             input: "a {}",
             rule: GrammarRule::grammar_rules,
-            positives: vec![GrammarRule::assignment_operator],
+            positives: vec![GrammarRule::parameters, GrammarRule::assignment_operator],
             negatives: vec![],
             pos: 2
         };
@@ -1708,26 +2512,27 @@ mod tests {
             "rule = _{ a{1} ~ \"a\"{3,} ~ b{, 2} ~ \"b\"{1, 2} | !(^\"c\" | push('d'..'e'))?* }";
 
         let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
-        let ast = consume_rules_with_spans(pairs);
+        let ast = consume_rules_with_spans(pairs).unwrap();
         let ast: Vec<_> = ast.into_iter().map(|rule| convert_rule(rule)).collect();
 
         assert_eq!(
             ast,
             vec![
                 Rule {
-                    name: Ident::new("rule"),
+                    name: "rule".to_owned(),
                     ty: RuleType::Silent,
+                    doc: vec![],
                     expr: Expr::Choice(
                         Box::new(Expr::Seq(
                             Box::new(Expr::Seq(
                                 Box::new(Expr::Seq(
                                     Box::new(Expr::RepExact(
-                                        Box::new(Expr::Ident(Ident::new("a"))),
+                                        Box::new(Expr::Ident("a".to_owned())),
                                         1
                                     )),
                                     Box::new(Expr::RepMin(Box::new(Expr::Str("a".to_owned())), 3))
                                 )),
-                                Box::new(Expr::RepMax(Box::new(Expr::Ident(Ident::new("b"))), 2))
+                                Box::new(Expr::RepMax(Box::new(Expr::Ident("b".to_owned())), 2))
                             )),
                             Box::new(Expr::RepMinMax(Box::new(Expr::Str("b".to_owned())), 1, 2))
                         )),
@@ -1745,4 +2550,229 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn ast_peek_slice() {
+        let input = "rule = { peek[..] ~ peek[1..] ~ peek[..-1] ~ peek[-2..3] }";
+
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+        let ast = consume_rules_with_spans(pairs).unwrap();
+        let ast: Vec<_> = ast.into_iter().map(|rule| convert_rule(rule)).collect();
+
+        assert_eq!(
+            ast,
+            vec![
+                Rule {
+                    name: "rule".to_owned(),
+                    ty: RuleType::Normal,
+                    doc: vec![],
+                    expr: Expr::Seq(
+                        Box::new(Expr::Seq(
+                            Box::new(Expr::Seq(
+                                Box::new(Expr::PeekSlice(0, None)),
+                                Box::new(Expr::PeekSlice(1, None))
+                            )),
+                            Box::new(Expr::PeekSlice(0, Some(-1)))
+                        )),
+                        Box::new(Expr::PeekSlice(-2, Some(3)))
+                    )
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ast_override() {
+        let input = "a = { b } b = { \"b\" } c = { \"c\" } override b = @{ c } override_b = { a }";
+
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+        let ast = consume_rules_with_spans(pairs).unwrap();
+        let ast: Vec<_> = ast.into_iter().map(|rule| convert_rule(rule)).collect();
+
+        assert_eq!(
+            ast,
+            vec![
+                Rule {
+                    name: "a".to_owned(),
+                    ty: RuleType::Normal,
+                    doc: vec![],
+                    expr: Expr::Ident("b".to_owned())
+                },
+                Rule {
+                    name: "b".to_owned(),
+                    ty: RuleType::Atomic,
+                    doc: vec![],
+                    expr: Expr::Ident("c".to_owned())
+                },
+                Rule {
+                    name: "c".to_owned(),
+                    ty: RuleType::Normal,
+                    doc: vec![],
+                    expr: Expr::Str("c".to_owned())
+                },
+                Rule {
+                    name: "override_b".to_owned(),
+                    ty: RuleType::Normal,
+                    doc: vec![],
+                    expr: Expr::Ident("a".to_owned())
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ast_parameterized() {
+        let input = "list(x, s) = _{ x ~ (s ~ x)* } a = { list(b, list(\"+\", \"-\")) } \
+                     b = { \"b\" }";
+
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+        let ast = consume_rules(pairs).unwrap();
+
+        let plus_minus = Expr::Seq(
+            Box::new(Expr::Str("+".to_owned())),
+            Box::new(Expr::Rep(Box::new(Expr::Seq(
+                Box::new(Expr::Str("-".to_owned())),
+                Box::new(Expr::Str("+".to_owned()))
+            ))))
+        );
+
+        assert_eq!(
+            ast,
+            vec![
+                Rule {
+                    name: "a".to_owned(),
+                    ty: RuleType::Normal,
+                    doc: vec![],
+                    expr: Expr::Seq(
+                        Box::new(Expr::Ident("b".to_owned())),
+                        Box::new(Expr::Rep(Box::new(Expr::Seq(
+                            Box::new(plus_minus),
+                            Box::new(Expr::Ident("b".to_owned()))
+                        ))))
+                    )
+                },
+                Rule {
+                    name: "b".to_owned(),
+                    ty: RuleType::Normal,
+                    doc: vec![],
+                    expr: Expr::Str("b".to_owned())
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ast_node_tag() {
+        let input = "rule = { #lhs = a ~ #rhs = (b | c)* }";
+
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+        let ast = consume_rules_with_spans(pairs).unwrap();
+        let ast: Vec<_> = ast.into_iter().map(|rule| convert_rule(rule)).collect();
+
+        assert_eq!(
+            ast,
+            vec![Rule {
+                name: "rule".to_owned(),
+                ty: RuleType::Normal,
+                doc: vec![],
+                expr: Expr::Seq(
+                    Box::new(Expr::NodeTag(
+                        Box::new(Expr::Ident("a".to_owned())),
+                        "lhs".to_owned()
+                    )),
+                    Box::new(Expr::NodeTag(
+                        Box::new(Expr::Rep(Box::new(Expr::Choice(
+                            Box::new(Expr::Ident("b".to_owned())),
+                            Box::new(Expr::Ident("c".to_owned()))
+                        )))),
+                        "rhs".to_owned()
+                    ))
+                )
+            }]
+        );
+    }
+
+    #[test]
+    fn ast_insensitive_range() {
+        let input = "rule = { ^'a'..'z' }";
+
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+        let ast = consume_rules_with_spans(pairs).unwrap();
+        let ast: Vec<_> = ast.into_iter().map(|rule| convert_rule(rule)).collect();
+
+        assert_eq!(
+            ast,
+            vec![Rule {
+                name: "rule".to_owned(),
+                ty: RuleType::Normal,
+                doc: vec![],
+                expr: Expr::InsensRange("'a'".to_owned(), "'z'".to_owned())
+            }]
+        );
+    }
+
+    #[test]
+    fn ast_last_override_wins() {
+        let input = "a = { \"a\" } override a = { \"b\" } override a = { \"c\" }";
+
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+        let ast = consume_rules(pairs).unwrap();
+
+        assert_eq!(
+            ast,
+            vec![Rule {
+                name: "a".to_owned(),
+                ty: RuleType::Normal,
+                doc: vec![],
+                expr: Expr::Str("c".to_owned())
+            }]
+        );
+    }
+
+    #[test]
+    fn ast_doc_comments() {
+        let input = "/// A.\n///\na = { \"a\" }\noverride a = { \"b\" }\n\
+                     /// B.\nb = { a }\n/// C.\noverride b = { \"c\" }";
+
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+        let ast = consume_rules(pairs).unwrap();
+
+        assert_eq!(
+            ast,
+            vec![
+                Rule {
+                    name: "a".to_owned(),
+                    ty: RuleType::Normal,
+                    doc: vec![" A.".to_owned(), "".to_owned()],
+                    expr: Expr::Str("b".to_owned())
+                },
+                Rule {
+                    name: "b".to_owned(),
+                    ty: RuleType::Normal,
+                    doc: vec![" C.".to_owned()],
+                    expr: Expr::Str("c".to_owned())
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn repeat_overflow() {
+        let input = "rule = { \"a\"{4294967296} }";
+
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+        let errors = consume_rules_with_spans(pairs).unwrap_err();
+
+        assert_eq!(
+            format!("{}", errors[0]),
+            [
+                " --> 1:14",
+                "  |",
+                "1 | rule = { \"a\"{4294967296} }",
+                "  |              ^--------^",
+                "  |",
+                "  = number cannot overflow u32"
+            ].join("\n")
+        );
+    }
 }
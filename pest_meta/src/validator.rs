@@ -0,0 +1,1338 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::collections::{HashMap, HashSet};
+
+use pest::Error;
+use pest::Span;
+use pest::iterators::{Pair, Pairs};
+use pest::unicode;
+
+use ast::RuleType;
+use parser::{self, GrammarRule, ParserExpr, ParserNode, ParserRule};
+
+const ASCII_CLASSES: [&str; 11] = [
+    "ASCII_DIGIT",
+    "ASCII_NONZERO_DIGIT",
+    "ASCII_BIN_DIGIT",
+    "ASCII_OCT_DIGIT",
+    "ASCII_HEX_DIGIT",
+    "ASCII_ALPHA_LOWER",
+    "ASCII_ALPHA_UPPER",
+    "ASCII_ALPHA",
+    "ASCII_ALPHANUMERIC",
+    "ASCII",
+    "NEWLINE"
+];
+
+/// Validates the grammar `pairs` returned by
+/// [`GrammarParser`](../parser/struct.GrammarParser.html), checking that no rule is defined twice,
+/// that no rule is named after a Rust or pest keyword, and that every called rule is defined.
+///
+/// Returns the names of the predefined rules that the grammar calls, like `any` or `eoi`, or all
+/// the errors that were found.
+pub fn validate_pairs<'i>(
+    pairs: Pairs<'i, GrammarRule>
+) -> Result<Vec<&'i str>, Vec<Error<'i, GrammarRule>>> {
+    let mut rust_keywords = HashSet::new();
+    rust_keywords.insert("abstract");
+    rust_keywords.insert("alignof");
+    rust_keywords.insert("as");
+    rust_keywords.insert("become");
+    rust_keywords.insert("box");
+    rust_keywords.insert("break");
+    rust_keywords.insert("const");
+    rust_keywords.insert("continue");
+    rust_keywords.insert("crate");
+    rust_keywords.insert("do");
+    rust_keywords.insert("else");
+    rust_keywords.insert("enum");
+    rust_keywords.insert("extern");
+    rust_keywords.insert("false");
+    rust_keywords.insert("final");
+    rust_keywords.insert("fn");
+    rust_keywords.insert("for");
+    rust_keywords.insert("if");
+    rust_keywords.insert("impl");
+    rust_keywords.insert("in");
+    rust_keywords.insert("let");
+    rust_keywords.insert("loop");
+    rust_keywords.insert("macro");
+    rust_keywords.insert("match");
+    rust_keywords.insert("mod");
+    rust_keywords.insert("move");
+    rust_keywords.insert("mut");
+    rust_keywords.insert("offsetof");
+    rust_keywords.insert("override");
+    rust_keywords.insert("priv");
+    rust_keywords.insert("proc");
+    rust_keywords.insert("pure");
+    rust_keywords.insert("pub");
+    rust_keywords.insert("ref");
+    rust_keywords.insert("return");
+    rust_keywords.insert("Self");
+    rust_keywords.insert("self");
+    rust_keywords.insert("sizeof");
+    rust_keywords.insert("static");
+    rust_keywords.insert("struct");
+    rust_keywords.insert("super");
+    rust_keywords.insert("trait");
+    rust_keywords.insert("true");
+    rust_keywords.insert("type");
+    rust_keywords.insert("typeof");
+    rust_keywords.insert("unsafe");
+    rust_keywords.insert("unsized");
+    rust_keywords.insert("use");
+    rust_keywords.insert("virtual");
+    rust_keywords.insert("where");
+    rust_keywords.insert("while");
+    rust_keywords.insert("yield");
+
+    let mut pest_keywords = HashSet::new();
+    pest_keywords.insert("any");
+    pest_keywords.insert("drop");
+    pest_keywords.insert("eoi");
+    pest_keywords.insert("peek");
+    pest_keywords.insert("peek_all");
+    pest_keywords.insert("pop");
+    pest_keywords.insert("pop_all");
+    pest_keywords.insert("push");
+    pest_keywords.insert("soi");
+
+    let mut predefined = HashSet::new();
+    predefined.insert("any");
+    predefined.insert("drop");
+    predefined.insert("eoi");
+    predefined.insert("peek");
+    predefined.insert("peek_all");
+    predefined.insert("pop");
+    predefined.insert("pop_all");
+    predefined.insert("soi");
+
+    let definitions = definitions(pairs.clone());
+    let overrides: Vec<_> = pairs
+        .clone()
+        .filter(|pair| pair.as_rule() == GrammarRule::grammar_rule)
+        .filter(|pair| is_override(pair))
+        .map(|pair| name(pair).into_span())
+        .collect();
+    let parameters: Vec<_> = pairs
+        .clone()
+        .filter(|pair| pair.as_rule() == GrammarRule::grammar_rule)
+        .map(|pair| parameter_spans(pair))
+        .collect();
+    let called_rules: Vec<_> = pairs
+        .clone()
+        .filter(|pair| pair.as_rule() == GrammarRule::grammar_rule)
+        .flat_map(|pair| {
+            let params: Vec<_> = parameter_spans(pair.clone())
+                .iter()
+                .map(|span| span.as_str())
+                .collect();
+
+            pair.into_inner()
+                .filter(|pair| pair.as_rule() == GrammarRule::expression)
+                .flat_map(|pair| pair.into_inner().flatten())
+                .filter(|pair| pair.as_rule() == GrammarRule::identifier)
+                .filter(move |pair| !params.contains(&pair.as_str()))
+                .map(|pair| pair.into_span())
+        })
+        .collect();
+
+    let literals: Vec<_> = pairs
+        .clone()
+        .filter(|pair| pair.as_rule() == GrammarRule::grammar_rule)
+        .flat_map(|pair| pair.into_inner().flatten())
+        .filter(|pair| {
+            pair.as_rule() == GrammarRule::string || pair.as_rule() == GrammarRule::character
+        })
+        .map(|pair| pair.into_span())
+        .collect();
+
+    let mut errors = vec![];
+
+    errors.extend(validate_rust_keywords(&definitions, &rust_keywords));
+    errors.extend(validate_pest_keywords(&definitions, &pest_keywords));
+    errors.extend(validate_already_defined(&definitions));
+    errors.extend(validate_overrides(&definitions, &overrides));
+    errors.extend(validate_parameters(&parameters));
+    errors.extend(validate_undefined(&definitions, &called_rules, &predefined));
+    errors.extend(validate_escapes(&literals));
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let definitions: HashSet<_> = definitions.iter().map(|span| span.as_str()).collect();
+    let called_rules: HashSet<_> = called_rules.iter().map(|span| span.as_str()).collect();
+
+    let defaults = called_rules.difference(&definitions);
+
+    Ok(defaults.cloned().collect())
+}
+
+/// Returns the `Span` of the first definition of the rule which `error` reports as already
+/// defined, or `None` if `error` does not report a rule defined twice. `error` has to be one of
+/// the errors returned by [`validate_pairs`](fn.validate_pairs.html) for the same `pairs`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate pest;
+/// # extern crate pest_meta;
+/// # use pest::Parser;
+/// # use pest_meta::parser::{GrammarParser, GrammarRule};
+/// # use pest_meta::validator;
+/// # fn main() {
+/// let input = "a = { \"a\" } a = { \"b\" }";
+/// let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+/// let errors = validator::validate_pairs(pairs.clone()).unwrap_err();
+/// let first = validator::first_definition(pairs, &errors[0]).unwrap();
+///
+/// assert_eq!((first.start(), first.end()), (0, 1));
+/// # }
+/// ```
+pub fn first_definition<'i>(
+    pairs: Pairs<'i, GrammarRule>,
+    error: &Error<'i, GrammarRule>
+) -> Option<Span<'i>> {
+    let span = match *error {
+        Error::CustomErrorSpan { ref span, .. } => span,
+        _ => return None
+    };
+    let definitions = definitions(pairs);
+    let index = definitions.iter().position(|definition| definition == span)?;
+
+    definitions[..index]
+        .iter()
+        .find(|definition| definition.as_str() == span.as_str())
+        .cloned()
+}
+
+fn definitions<'i>(pairs: Pairs<'i, GrammarRule>) -> Vec<Span<'i>> {
+    pairs
+        .filter(|pair| pair.as_rule() == GrammarRule::grammar_rule)
+        .filter(|pair| !is_override(pair))
+        .map(|pair| name(pair).into_span())
+        .collect()
+}
+
+fn is_override(pair: &Pair<GrammarRule>) -> bool {
+    pair.clone()
+        .into_inner()
+        .any(|pair| pair.as_rule() == GrammarRule::override_keyword)
+}
+
+fn name<'i>(pair: Pair<'i, GrammarRule>) -> Pair<'i, GrammarRule> {
+    pair.into_inner()
+        .find(|pair| pair.as_rule() == GrammarRule::identifier)
+        .unwrap()
+}
+
+fn parameter_spans<'i>(pair: Pair<'i, GrammarRule>) -> Vec<Span<'i>> {
+    pair.into_inner()
+        .filter(|pair| pair.as_rule() == GrammarRule::parameters)
+        .flat_map(|pair| pair.into_inner())
+        .filter(|pair| pair.as_rule() == GrammarRule::identifier)
+        .map(|pair| pair.into_span())
+        .collect()
+}
+
+fn validate_rust_keywords<'i>(
+    definitions: &Vec<Span<'i>>,
+    rust_keywords: &HashSet<&str>
+) -> Vec<Error<'i, GrammarRule>> {
+    let mut errors = vec![];
+
+    for definition in definitions {
+        let name = definition.as_str();
+
+        if rust_keywords.contains(name) {
+            errors.push(Error::CustomErrorSpan {
+                message: format!("{} is a rust keyword", name),
+                span: definition.clone()
+            })
+        }
+    }
+
+    errors
+}
+
+fn validate_pest_keywords<'i>(
+    definitions: &Vec<Span<'i>>,
+    pest_keywords: &HashSet<&str>
+) -> Vec<Error<'i, GrammarRule>> {
+    let mut errors = vec![];
+
+    for definition in definitions {
+        let name = definition.as_str();
+
+        if pest_keywords.contains(name) || is_character_class(name) {
+            errors.push(Error::CustomErrorSpan {
+                message: format!("{} is a pest keyword", name),
+                span: definition.clone()
+            })
+        }
+    }
+
+    errors
+}
+
+fn validate_already_defined<'i>(definitions: &Vec<Span<'i>>) -> Vec<Error<'i, GrammarRule>> {
+    let mut errors = vec![];
+    let mut defined = HashSet::new();
+
+    for definition in definitions {
+        let name = definition.as_str();
+
+        if defined.contains(&name) {
+            errors.push(Error::CustomErrorSpan {
+                message: format!("rule {} already defined", name),
+                span: definition.clone()
+            })
+        } else {
+            defined.insert(name);
+        }
+    }
+
+    errors
+}
+
+fn validate_overrides<'i>(
+    definitions: &Vec<Span<'i>>,
+    overrides: &Vec<Span<'i>>
+) -> Vec<Error<'i, GrammarRule>> {
+    let mut errors = vec![];
+    let definitions: HashSet<_> = definitions.iter().map(|span| span.as_str()).collect();
+
+    for rule in overrides {
+        let name = rule.as_str();
+
+        if !definitions.contains(name) {
+            errors.push(Error::CustomErrorSpan {
+                message: format!("rule {} overrides a rule which is not defined", name),
+                span: rule.clone()
+            })
+        }
+    }
+
+    errors
+}
+
+fn validate_parameters<'i>(parameters: &Vec<Vec<Span<'i>>>) -> Vec<Error<'i, GrammarRule>> {
+    let mut errors = vec![];
+
+    for params in parameters {
+        let mut defined = HashSet::new();
+
+        for param in params {
+            let name = param.as_str();
+
+            if !defined.insert(name) {
+                errors.push(Error::CustomErrorSpan {
+                    message: format!("parameter {} already defined", name),
+                    span: param.clone()
+                })
+            }
+        }
+    }
+
+    errors
+}
+
+fn validate_escapes<'i>(literals: &Vec<Span<'i>>) -> Vec<Error<'i, GrammarRule>> {
+    literals
+        .iter()
+        .filter_map(|literal| {
+            invalid_escape(literal.as_str()).map(|escape| Error::CustomErrorSpan {
+                message: format!("{} is not a valid character escape", escape),
+                span: literal.clone()
+            })
+        })
+        .collect()
+}
+
+// Returns the first escape of a `literal` which does not stand for a `char`. Just like in Rust
+// literals, `\x` escapes only stand for ASCII `char`s.
+fn invalid_escape(literal: &str) -> Option<&str> {
+    let mut chars = literal.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        if c != '\\' {
+            continue;
+        }
+
+        match chars.next() {
+            Some((_, 'x')) => {
+                let escape = &literal[i..i + 4];
+
+                match u8::from_str_radix(&escape[2..], 16) {
+                    Ok(code) if code <= 0x7f => (),
+                    _ => return Some(escape)
+                }
+            }
+            Some((_, 'u')) => {
+                let end = i + literal[i..].find('}').unwrap();
+                let escape = &literal[i..end + 1];
+
+                let code = u32::from_str_radix(&escape[3..escape.len() - 1], 16).ok();
+                if code.and_then(::std::char::from_u32).is_none() {
+                    return Some(escape);
+                }
+            }
+            _ => ()
+        }
+    }
+
+    None
+}
+
+fn validate_undefined<'i>(
+    definitions: &Vec<Span<'i>>,
+    called_rules: &Vec<Span<'i>>,
+    predefined: &HashSet<&str>
+) -> Vec<Error<'i, GrammarRule>> {
+    let mut errors = vec![];
+    let definitions: HashSet<_> = definitions.iter().map(|span| span.as_str()).collect();
+
+    for rule in called_rules {
+        let name = rule.as_str();
+
+        if !definitions.contains(name) && !predefined.contains(name) && !is_character_class(name) {
+            errors.push(Error::CustomErrorSpan {
+                message: format!("rule {} is undefined", name),
+                span: rule.clone()
+            })
+        }
+    }
+
+    errors
+}
+
+/// Validates the spanned `rules` returned by
+/// [`consume_rules_with_spans`](../parser/fn.consume_rules_with_spans.html), checking that
+/// parameterized rules are silent, called with the right number of arguments, and not recursive,
+/// and then checking their expansion for repetitions, `whitespace`, and `comment` rules which
+/// would loop forever and for left-recursive rules.
+///
+/// Returns all the errors that were found, sorted by their position in the grammar.
+pub fn validate_ast<'i>(rules: &Vec<ParserRule<'i>>) -> Vec<Error<'i, GrammarRule>> {
+    let mut errors = validate_calls(rules);
+
+    // Errors found in a parameterized rule are reported once for every call expanding it.
+    if errors.is_empty() {
+        let rules = &parser::expand_parameterized_rules(rules.clone());
+
+        errors.extend(validate_repetition(rules));
+        errors.extend(validate_whitespace_comment(rules));
+        errors.extend(validate_left_recursion(rules));
+    }
+
+    errors.sort_by_key(|error| match *error {
+        Error::CustomErrorSpan { ref span, .. } => span.clone(),
+        _ => unreachable!()
+    });
+    errors.dedup();
+
+    errors
+}
+
+fn validate_calls<'i>(rules: &Vec<ParserRule<'i>>) -> Vec<Error<'i, GrammarRule>> {
+    fn check_node<'i>(
+        node: &ParserNode<'i>,
+        rules: &HashMap<&str, &ParserRule<'i>>,
+        params: &[String],
+        errors: &mut Vec<Error<'i, GrammarRule>>
+    ) {
+        match node.expr {
+            ParserExpr::Ident(ref ident) => {
+                if let Some(rule) = rules.get(ident.as_str()) {
+                    if !rule.params.is_empty() && !params.contains(ident) {
+                        errors.push(Error::CustomErrorSpan {
+                            message: format!(
+                                "rule {} expects {}, found 0",
+                                ident,
+                                arguments(rule.params.len())
+                            ),
+                            span: node.span.clone()
+                        });
+                    }
+                }
+            }
+            ParserExpr::Call(ref name, ref args) => {
+                match rules.get(name.as_str()) {
+                    Some(rule) if !rule.params.is_empty() && !params.contains(name) => {
+                        if rule.params.len() != args.len() {
+                            errors.push(Error::CustomErrorSpan {
+                                message: format!(
+                                    "rule {} expects {}, found {}",
+                                    name,
+                                    arguments(rule.params.len()),
+                                    args.len()
+                                ),
+                                span: node.span.clone()
+                            });
+                        }
+                    }
+                    None if !params.contains(name) && !is_predefined(name) => {
+                        // Reported at the name, like validate_pairs does, so that the two errors
+                        // are merged by parse_and_optimize.
+                        let start = node.span.start_pos();
+                        let end = start.clone().skip(name.chars().count()).unwrap();
+
+                        errors.push(Error::CustomErrorSpan {
+                            message: format!("rule {} is undefined", name),
+                            span: start.span(&end)
+                        });
+                    }
+                    _ => errors.push(Error::CustomErrorSpan {
+                        message: format!("rule {} does not take arguments", name),
+                        span: node.span.clone()
+                    })
+                }
+
+                for arg in args {
+                    check_node(arg, rules, params, errors);
+                }
+            }
+            ParserExpr::PosPred(ref node)
+            | ParserExpr::NegPred(ref node)
+            | ParserExpr::Opt(ref node)
+            | ParserExpr::Rep(ref node)
+            | ParserExpr::RepOnce(ref node)
+            | ParserExpr::RepExact(ref node, _)
+            | ParserExpr::RepMin(ref node, _)
+            | ParserExpr::RepMax(ref node, _)
+            | ParserExpr::RepMinMax(ref node, _, _)
+            | ParserExpr::Push(ref node)
+            | ParserExpr::NodeTag(ref node, _) => check_node(node, rules, params, errors),
+            ParserExpr::Seq(ref lhs, ref rhs) | ParserExpr::Choice(ref lhs, ref rhs) => {
+                check_node(lhs, rules, params, errors);
+                check_node(rhs, rules, params, errors);
+            }
+            _ => ()
+        }
+    }
+
+    let mut errors = vec![];
+    let by_name: HashMap<_, _> = rules.iter().map(|rule| (rule.name.as_str(), rule)).collect();
+
+    for rule in rules {
+        if !rule.params.is_empty() && rule.ty != RuleType::Silent {
+            errors.push(Error::CustomErrorSpan {
+                message: format!("parameterized rule {} must be silent", rule.name),
+                span: rule.span.clone()
+            });
+        }
+
+        check_node(&rule.node, &by_name, &rule.params, &mut errors);
+    }
+
+    if errors.is_empty() {
+        errors.extend(parameterized_recursion(&by_name));
+    }
+
+    errors
+}
+
+fn arguments(count: usize) -> String {
+    if count == 1 {
+        "1 argument".to_owned()
+    } else {
+        format!("{} arguments", count)
+    }
+}
+
+fn is_predefined(name: &str) -> bool {
+    ["any", "drop", "eoi", "peek", "peek_all", "pop", "pop_all", "soi"].contains(&name)
+        || is_character_class(name)
+}
+
+// Predefined rules matching a single character, or a line break in the case of `NEWLINE`. The
+// Unicode ones are the predicates of `pest::unicode`.
+fn is_character_class(name: &str) -> bool {
+    ASCII_CLASSES.contains(&name) || unicode::by_name(name).is_some()
+}
+
+// Parameterized rules are expanded in place, so a parameterized rule which ends up calling itself
+// would expand forever.
+fn parameterized_recursion<'i>(
+    rules: &HashMap<&str, &ParserRule<'i>>
+) -> Vec<Error<'i, GrammarRule>> {
+    fn check_node<'i>(
+        node: &ParserNode<'i>,
+        rules: &HashMap<&str, &ParserRule<'i>>,
+        params: &[String],
+        trace: &mut Vec<String>
+    ) -> Option<Error<'i, GrammarRule>> {
+        match node.expr {
+            ParserExpr::Call(ref name, ref args) => {
+                if let Some(error) = args.iter()
+                    .filter_map(|arg| check_node(arg, rules, params, trace))
+                    .next()
+                {
+                    return Some(error);
+                }
+
+                if params.contains(name) {
+                    return None;
+                }
+
+                if trace[0] == *name {
+                    trace.push(name.clone());
+                    let chain = trace.join(" -> ");
+
+                    return Some(Error::CustomErrorSpan {
+                        message: format!(
+                            "parameterized rule {} is recursive ({}) and cannot be expanded",
+                            name, chain
+                        ),
+                        span: node.span.clone()
+                    });
+                }
+
+                if !trace.contains(name) {
+                    let rule = rules[name.as_str()];
+
+                    trace.push(name.clone());
+                    let result = check_node(&rule.node, rules, &rule.params, trace);
+                    trace.pop().unwrap();
+
+                    return result;
+                }
+
+                None
+            }
+            ParserExpr::PosPred(ref node)
+            | ParserExpr::NegPred(ref node)
+            | ParserExpr::Opt(ref node)
+            | ParserExpr::Rep(ref node)
+            | ParserExpr::RepOnce(ref node)
+            | ParserExpr::RepExact(ref node, _)
+            | ParserExpr::RepMin(ref node, _)
+            | ParserExpr::RepMax(ref node, _)
+            | ParserExpr::RepMinMax(ref node, _, _)
+            | ParserExpr::Push(ref node)
+            | ParserExpr::NodeTag(ref node, _) => check_node(node, rules, params, trace),
+            ParserExpr::Seq(ref lhs, ref rhs) | ParserExpr::Choice(ref lhs, ref rhs) => {
+                check_node(lhs, rules, params, trace)
+                    .or_else(|| check_node(rhs, rules, params, trace))
+            }
+            _ => None
+        }
+    }
+
+    let mut errors = vec![];
+
+    for rule in rules.values().filter(|rule| !rule.params.is_empty()) {
+        let mut trace = vec![rule.name.clone()];
+
+        if let Some(error) = check_node(&rule.node, rules, &rule.params, &mut trace) {
+            errors.push(error);
+        }
+    }
+
+    errors
+}
+
+fn is_non_progressing<'i>(expr: &ParserExpr<'i>) -> bool {
+    match *expr {
+        ParserExpr::Str(ref string) => string == "",
+        ParserExpr::Ident(ref ident) => {
+            ["soi", "eoi", "peek_all", "pop_all", "drop"].contains(&ident.as_str())
+        }
+        // Slices of the stack match nothing when they are empty.
+        ParserExpr::PeekSlice(..) => true,
+        ParserExpr::PosPred(_) => true,
+        ParserExpr::NegPred(_) => true,
+        ParserExpr::Seq(ref lhs, ref rhs) => {
+            is_non_progressing(&lhs.expr) && is_non_progressing(&rhs.expr)
+        }
+        ParserExpr::Choice(ref lhs, ref rhs) => {
+            is_non_progressing(&lhs.expr) || is_non_progressing(&rhs.expr)
+        }
+        ParserExpr::NodeTag(ref node, _) => is_non_progressing(&node.expr),
+        _ => false
+    }
+}
+
+fn is_non_failing<'i>(expr: &ParserExpr<'i>) -> bool {
+    match *expr {
+        ParserExpr::Str(ref string) => string == "",
+        // The whole stack matches without failing when it is empty.
+        ParserExpr::Ident(ref ident) => ident == "peek_all" || ident == "pop_all",
+        ParserExpr::PeekSlice(..) => true,
+        ParserExpr::Opt(_) => true,
+        ParserExpr::Rep(_) => true,
+        ParserExpr::Seq(ref lhs, ref rhs) => is_non_failing(&lhs.expr) && is_non_failing(&rhs.expr),
+        ParserExpr::Choice(ref lhs, ref rhs) => {
+            is_non_failing(&lhs.expr) || is_non_failing(&rhs.expr)
+        }
+        ParserExpr::NodeTag(ref node, _) => is_non_failing(&node.expr),
+        _ => false
+    }
+}
+
+fn validate_repetition<'i>(rules: &Vec<ParserRule<'i>>) -> Vec<Error<'i, GrammarRule>> {
+    fn check_node<'i>(node: &ParserNode<'i>, errors: &mut Vec<Error<'i, GrammarRule>>) {
+        match node.expr {
+            ParserExpr::Rep(ref other)
+            | ParserExpr::RepOnce(ref other)
+            | ParserExpr::RepMin(ref other, _) => {
+                if is_non_failing(&other.expr) {
+                    errors.push(Error::CustomErrorSpan {
+                        message: "expression inside repetition is non-failing and will repeat \
+                                  infinitely"
+                            .to_owned(),
+                        span: node.span.clone()
+                    });
+                } else if is_non_progressing(&other.expr) {
+                    errors.push(Error::CustomErrorSpan {
+                        message: "expression inside repetition is non-progressing and will repeat \
+                                  infinitely"
+                            .to_owned(),
+                        span: node.span.clone()
+                    });
+                } else {
+                    check_node(other, errors);
+                }
+            }
+            ParserExpr::PosPred(ref node)
+            | ParserExpr::NegPred(ref node)
+            | ParserExpr::Opt(ref node)
+            | ParserExpr::RepExact(ref node, _)
+            | ParserExpr::RepMax(ref node, _)
+            | ParserExpr::RepMinMax(ref node, _, _)
+            | ParserExpr::Push(ref node)
+            | ParserExpr::NodeTag(ref node, _) => check_node(node, errors),
+            ParserExpr::Seq(ref lhs, ref rhs) | ParserExpr::Choice(ref lhs, ref rhs) => {
+                check_node(lhs, errors);
+                check_node(rhs, errors);
+            }
+            ParserExpr::Call(_, ref args) => {
+                for arg in args {
+                    check_node(arg, errors);
+                }
+            }
+            _ => ()
+        }
+    }
+
+    let mut errors = vec![];
+
+    for rule in rules {
+        check_node(&rule.node, &mut errors);
+    }
+
+    errors
+}
+
+fn validate_whitespace_comment<'i>(rules: &Vec<ParserRule<'i>>) -> Vec<Error<'i, GrammarRule>> {
+    rules
+        .into_iter()
+        .filter_map(|rule| {
+            if rule.name == "whitespace" || rule.name == "comment" {
+                if is_non_failing(&rule.node.expr) {
+                    Some(Error::CustomErrorSpan {
+                        message: format!(
+                            "{} is non-failing and will repeat infinitely",
+                            &rule.name
+                        ),
+                        span: rule.node.span.clone()
+                    })
+                } else if is_non_progressing(&rule.node.expr) {
+                    Some(Error::CustomErrorSpan {
+                        message: format!(
+                            "{} is non-progressing and will repeat infinitely",
+                            &rule.name
+                        ),
+                        span: rule.node.span.clone()
+                    })
+                } else {
+                    None
+                }
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn validate_left_recursion<'i>(rules: &Vec<ParserRule<'i>>) -> Vec<Error<'i, GrammarRule>> {
+    left_recursion(to_hash_map(rules))
+}
+
+fn to_hash_map<'a, 'i>(rules: &'a Vec<ParserRule<'i>>) -> HashMap<String, &'a ParserNode<'i>> {
+    let mut hash_map = HashMap::new();
+
+    for rule in rules {
+        hash_map.insert(rule.name.clone(), &rule.node);
+    }
+
+    hash_map
+}
+
+fn left_recursion<'i>(rules: HashMap<String, &ParserNode<'i>>) -> Vec<Error<'i, GrammarRule>> {
+    fn check_expr<'i>(
+        node: &ParserNode<'i>,
+        rules: &HashMap<String, &ParserNode<'i>>,
+        trace: &mut Vec<String>
+    ) -> Option<Error<'i, GrammarRule>> {
+        match node.expr {
+            ParserExpr::Ident(ref other) => {
+                if trace[0] == *other {
+                    trace.push(other.clone());
+                    let chain = trace
+                        .iter()
+                        .map(|ident| ident.as_ref())
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+
+                    return Some(Error::CustomErrorSpan {
+                        message: format!(
+                            "rule {} is left-recursive ({}); pest::prec_climber might \
+                             be useful in this case",
+                            node.span.as_str(),
+                            chain
+                        ),
+                        span: node.span.clone()
+                    });
+                }
+
+                if !trace.contains(other) {
+                    if let Some(node) = rules.get(other) {
+                        trace.push(other.clone());
+                        let result = check_expr(node, rules, trace);
+                        trace.pop().unwrap();
+
+                        return result;
+                    }
+                }
+
+                None
+            }
+            ParserExpr::Seq(ref lhs, ref rhs) => {
+                if is_non_failing(&lhs.expr) {
+                    check_expr(rhs, rules, trace)
+                } else {
+                    check_expr(lhs, rules, trace)
+                }
+            }
+            ParserExpr::Choice(ref lhs, ref rhs) => {
+                check_expr(&lhs, rules, trace).or(check_expr(&rhs, rules, trace))
+            }
+            ParserExpr::Rep(ref node) => check_expr(&node, rules, trace),
+            ParserExpr::RepOnce(ref node) => check_expr(&node, rules, trace),
+            ParserExpr::Opt(ref node) => check_expr(&node, rules, trace),
+            ParserExpr::PosPred(ref node) => check_expr(&node, rules, trace),
+            ParserExpr::NegPred(ref node) => check_expr(&node, rules, trace),
+            ParserExpr::Push(ref node) => check_expr(&node, rules, trace),
+            ParserExpr::NodeTag(ref node, _) => check_expr(&node, rules, trace),
+            _ => None
+        }
+    }
+
+    let mut errors = vec![];
+
+    for (ref name, ref node) in &rules {
+        let name = (*name).clone();
+
+        if let Some(error) = check_expr(node, &rules, &mut vec![name]) {
+            errors.push(error);
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use pest::Parser;
+
+    use super::*;
+    use super::super::parser::{consume_rules, GrammarParser};
+
+    fn format_errors(errors: Vec<Error<GrammarRule>>) -> String {
+        errors
+            .into_iter()
+            .map(|error| format!("{}", error))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    #[test]
+    fn rust_keyword() {
+        let input = "let = { \"a\" }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(validate_pairs(pairs).unwrap_err()),
+            " --> 1:1
+  |
+1 | let = { \"a\" }
+  | ^-^
+  |
+  = let is a rust keyword"
+        );
+    }
+
+    #[test]
+    fn pest_keyword() {
+        let input = "any = { \"a\" }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(validate_pairs(pairs).unwrap_err()),
+            " --> 1:1
+  |
+1 | any = { \"a\" }
+  | ^-^
+  |
+  = any is a pest keyword"
+        );
+    }
+
+    #[test]
+    fn character_class_keyword() {
+        let input = "LETTER = { \"a\" }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(validate_pairs(pairs).unwrap_err()),
+            " --> 1:1
+  |
+1 | LETTER = { \"a\" }
+  | ^----^
+  |
+  = LETTER is a pest keyword"
+        );
+    }
+
+    #[test]
+    fn character_classes() {
+        let input = "a = { ASCII_DIGIT ~ NEWLINE ~ XID_START }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        let mut defaults = validate_pairs(pairs).unwrap();
+        defaults.sort();
+
+        assert_eq!(defaults, vec!["ASCII_DIGIT", "NEWLINE", "XID_START"]);
+    }
+
+    #[test]
+    fn already_defined() {
+        let input = "a = { \"a\" } a = { \"a\" }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(validate_pairs(pairs).unwrap_err()),
+            " --> 1:13
+  |
+1 | a = { \"a\" } a = { \"a\" }
+  |             ^
+  |
+  = rule a already defined"
+        );
+    }
+
+    #[test]
+    fn undefined() {
+        let input = "a = { b }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(validate_pairs(pairs).unwrap_err()),
+            " --> 1:7
+  |
+1 | a = { b }
+  |       ^
+  |
+  = rule b is undefined"
+        );
+    }
+
+    #[test]
+    fn undefined_override() {
+        let input = "a = { \"a\" } override b = { a }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(validate_pairs(pairs).unwrap_err()),
+            " --> 1:22
+  |
+1 | a = { \"a\" } override b = { a }
+  |                      ^
+  |
+  = rule b overrides a rule which is not defined"
+        );
+    }
+
+    #[test]
+    fn override_left_recursion() {
+        let input = "a = { b } b = { \"b\" } override b = { \"b\"? ~ a }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert!(validate_pairs(pairs.clone()).is_ok());
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:7
+  |
+1 | a = { b } b = { \"b\" } override b = { \"b\"? ~ a }
+  |       ^
+  |
+  = rule b is left-recursive (b -> a -> b); pest::prec_climber might be useful in this case
+
+ --> 1:45
+  |
+1 | a = { b } b = { \"b\" } override b = { \"b\"? ~ a }
+  |                                             ^
+  |
+  = rule a is left-recursive (a -> b -> a); pest::prec_climber might be useful in this case"
+        );
+    }
+
+    #[test]
+    fn parameters() {
+        let input = "list(x, s) = _{ x ~ (s ~ x)* } a = { list(\"a\", any) }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(validate_pairs(pairs.clone()).unwrap(), vec!["any"]);
+        assert!(consume_rules(pairs).is_ok());
+    }
+
+    #[test]
+    fn parameter_already_defined() {
+        let input = "a(x, x) = _{ x }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(validate_pairs(pairs).unwrap_err()),
+            " --> 1:6
+  |
+1 | a(x, x) = _{ x }
+  |      ^
+  |
+  = parameter x already defined"
+        );
+    }
+
+    #[test]
+    fn wrong_arguments() {
+        let input = "a(x) = _{ x } b = { a(\"b\", \"c\") ~ a } c = { b(\"c\") }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:21
+  |
+1 | a(x) = _{ x } b = { a(\"b\", \"c\") ~ a } c = { b(\"c\") }
+  |                     ^---------^
+  |
+  = rule a expects 1 argument, found 2
+
+ --> 1:35
+  |
+1 | a(x) = _{ x } b = { a(\"b\", \"c\") ~ a } c = { b(\"c\") }
+  |                                   ^
+  |
+  = rule a expects 1 argument, found 0
+
+ --> 1:45
+  |
+1 | a(x) = _{ x } b = { a(\"b\", \"c\") ~ a } c = { b(\"c\") }
+  |                                             ^----^
+  |
+  = rule b does not take arguments"
+        );
+    }
+
+    #[test]
+    fn non_silent_parameterized() {
+        let input = "a(x) = { x }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:1
+  |
+1 | a(x) = { x }
+  | ^
+  |
+  = parameterized rule a must be silent"
+        );
+    }
+
+    #[test]
+    fn recursive_parameterized() {
+        let input = "a(x) = _{ x ~ b(x)? } b(x) = _{ a(x) }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:15
+  |
+1 | a(x) = _{ x ~ b(x)? } b(x) = _{ a(x) }
+  |               ^--^
+  |
+  = parameterized rule b is recursive (b -> a -> b) and cannot be expanded
+
+ --> 1:33
+  |
+1 | a(x) = _{ x ~ b(x)? } b(x) = _{ a(x) }
+  |                                 ^--^
+  |
+  = parameterized rule a is recursive (a -> b -> a) and cannot be expanded"
+        );
+    }
+
+    #[test]
+    fn parameterized_left_recursion() {
+        let input = "a(x) = _{ x ~ \"a\" } b = { a(b) }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:29
+  |
+1 | a(x) = _{ x ~ \"a\" } b = { a(b) }
+  |                             ^
+  |
+  = rule b is left-recursive (b -> b); pest::prec_climber might be useful in this case"
+        );
+    }
+
+    #[test]
+    fn valid_recursion() {
+        let input = "a = { \"\" ~ \"a\"? ~ \"a\"* ~ (\"a\" | \"b\") ~ a }";
+        consume_rules(GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        let input = "a = { \"\\u{D800}\" ~ '\\x80'..'a' ~ \"\\x7F\\u{10FFFF}\" }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(validate_pairs(pairs).unwrap_err()),
+            vec![
+                r#" --> 1:7
+  |
+1 | a = { "\u{D800}" ~ '\x80'..'a' ~ "\x7F\u{10FFFF}" }
+  |       ^--------^
+  |
+  = \u{D800} is not a valid character escape"#,
+                r#" --> 1:20
+  |
+1 | a = { "\u{D800}" ~ '\x80'..'a' ~ "\x7F\u{10FFFF}" }
+  |                    ^----^
+  |
+  = \x80 is not a valid character escape"#,
+            ].join("\n\n")
+        );
+    }
+
+    #[test]
+    fn non_failing_whitespace() {
+        let input = "whitespace = { \"\" }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:16
+  |
+1 | whitespace = { \"\" }
+  |                ^^
+  |
+  = whitespace is non-failing and will repeat infinitely"
+        );
+    }
+
+    #[test]
+    fn non_progressing_comment() {
+        let input = "comment = { soi }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:13
+  |
+1 | comment = { soi }
+  |             ^-^
+  |
+  = comment is non-progressing and will repeat infinitely"
+        );
+    }
+
+    #[test]
+    fn non_failing_repetition() {
+        let input = "a = { (\"\")* }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:7
+  |
+1 | a = { (\"\")* }
+  |       ^---^
+  |
+  = expression inside repetition is non-failing and will repeat infinitely"
+        );
+    }
+
+    #[test]
+    fn non_progressing_repetition() {
+        let input = "a = { (\"\" ~ &\"a\" ~ !\"a\" ~ (soi | eoi))* }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:7
+  |
+1 | a = { (\"\" ~ &\"a\" ~ !\"a\" ~ (soi | eoi))* }
+  |       ^-------------------------------^
+  |
+  = expression inside repetition is non-progressing and will repeat infinitely"
+        );
+    }
+
+    #[test]
+    fn non_failing_peek_all_repetition() {
+        let input = "a = { peek_all* }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:7
+  |
+1 | a = { peek_all* }
+  |       ^-------^
+  |
+  = expression inside repetition is non-failing and will repeat infinitely"
+        );
+    }
+
+    #[test]
+    fn non_failing_peek_slice_repetition() {
+        let input = "a = { peek[1..]* }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:7
+  |
+1 | a = { peek[1..]* }
+  |       ^--------^
+  |
+  = expression inside repetition is non-failing and will repeat infinitely"
+        );
+    }
+
+    #[test]
+    fn non_failing_nested_repetition() {
+        let input = "a = { peek_all* ~ \"x\" }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:7
+  |
+1 | a = { peek_all* ~ \"x\" }
+  |       ^-------^
+  |
+  = expression inside repetition is non-failing and will repeat infinitely"
+        );
+    }
+
+    #[test]
+    fn non_progressing_drop_repetition() {
+        let input = "a = { (\"\" ~ drop)* }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:7
+  |
+1 | a = { (\"\" ~ drop)* }
+  |       ^----------^
+  |
+  = expression inside repetition is non-progressing and will repeat infinitely"
+        );
+    }
+
+    #[test]
+    fn simple_left_recursion() {
+        let input = "a = { a }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:7
+  |
+1 | a = { a }
+  |       ^
+  |
+  = rule a is left-recursive (a -> a); pest::prec_climber might be useful in this case"
+        );
+    }
+
+    #[test]
+    fn indirect_left_recursion() {
+        let input = "a = { b } b = { a }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:7
+  |
+1 | a = { b } b = { a }
+  |       ^
+  |
+  = rule b is left-recursive (b -> a -> b); pest::prec_climber might be useful in this case
+
+ --> 1:17
+  |
+1 | a = { b } b = { a }
+  |                 ^
+  |
+  = rule a is left-recursive (a -> b -> a); pest::prec_climber might be useful in this case"
+        );
+    }
+
+    #[test]
+    fn non_failing_left_recursion() {
+        let input = "a = { \"\" ~ \"a\"? ~ \"a\"* ~ (\"a\" | \"\") ~ a }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:39
+  |
+1 | a = { \"\" ~ \"a\"? ~ \"a\"* ~ (\"a\" | \"\") ~ a }
+  |                                       ^
+  |
+  = rule a is left-recursive (a -> a); pest::prec_climber might be useful in this case"
+        );
+    }
+
+    #[test]
+    fn non_primary_choice_left_recursion() {
+        let input = "a = { \"a\" | a }";
+        let pairs = GrammarParser::parse(GrammarRule::grammar_rules, input).unwrap();
+
+        assert_eq!(
+            format_errors(consume_rules(pairs).unwrap_err()),
+            " --> 1:13
+  |
+1 | a = { \"a\" | a }
+  |             ^
+  |
+  = rule a is left-recursive (a -> a); pest::prec_climber might be useful in this case"
+        );
+    }
+}
@@ -0,0 +1,546 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! # pest vm
+//!
+//! This crate interprets pest grammars which are only known at runtime. Instead of generating
+//! code like [`pest_derive`](https://docs.rs/pest_derive), a [`Vm`](struct.Vm.html) walks the
+//! optimized rules produced by [`pest_meta`](https://docs.rs/pest_meta) over a
+//! [`ParserState`](../pest/struct.ParserState.html) and returns the very same `Pairs`.
+//!
+//! Since there is no generated `Rule` `enum`, rules are identified by their names.
+//!
+//! ```
+//! # extern crate pest_vm;
+//! use pest_vm::Vm;
+//!
+//! # fn main() {
+//! let vm = Vm::from_grammar("number = @{ '0'..'9'+ } list = { number ~ (\",\" ~ number)* }")
+//!     .unwrap();
+//! let pairs = vm.parse("list", "1,23").unwrap();
+//!
+//! assert_eq!(format!("{}", pairs), r#"["list"(0, 4, ["number"(0, 1), "number"(2, 4)])]"#);
+//! # }
+//! ```
+
+#![doc(html_root_url = "https://docs.rs/pest_vm")]
+
+extern crate pest;
+extern crate pest_meta;
+
+use std::collections::{HashMap, HashSet};
+
+use pest::{Atomicity, Error, ParserState, Position};
+use pest::iterators::Pairs;
+use pest::unicode;
+use pest_meta::ast::{Expr, Rule, RuleType};
+use pest_meta::parser::GrammarRule;
+
+const BUILTINS: [&str; 19] = [
+    "any",
+    "eoi",
+    "soi",
+    "peek",
+    "peek_all",
+    "pop",
+    "pop_all",
+    "drop",
+    "ASCII_DIGIT",
+    "ASCII_NONZERO_DIGIT",
+    "ASCII_BIN_DIGIT",
+    "ASCII_OCT_DIGIT",
+    "ASCII_HEX_DIGIT",
+    "ASCII_ALPHA_LOWER",
+    "ASCII_ALPHA_UPPER",
+    "ASCII_ALPHA",
+    "ASCII_ALPHANUMERIC",
+    "ASCII",
+    "NEWLINE"
+];
+
+/// A `struct` which interprets the rules of a grammar. Rules are looked up by name and parse to
+/// `Pairs` whose `Rule` type is `&str`.
+#[derive(Debug)]
+pub struct Vm {
+    rules: HashMap<String, Rule>,
+    undefined: HashMap<String, String>
+}
+
+impl Vm {
+    /// Creates a new `Vm` from rules, which are usually optimized with
+    /// [`optimize`](../pest_meta/optimizer/fn.optimize.html). The rules do not need to be
+    /// validated: starting to parse from a rule which calls an undefined rule, directly or
+    /// through other rules, returns an error instead of parsing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate pest_meta;
+    /// # extern crate pest_vm;
+    /// # use pest_vm::Vm;
+    /// # fn main() {
+    /// let (_, rules) = pest_meta::parse_and_optimize("a = { \"a\" }").unwrap();
+    /// let vm = Vm::new(rules);
+    ///
+    /// assert!(vm.parse("a", "a").is_ok());
+    /// # }
+    /// ```
+    pub fn new(rules: Vec<Rule>) -> Vm {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let name = rule.name.clone();
+                let rule = Rule {
+                    expr: unescape_expr(rule.expr),
+                    ..rule
+                };
+
+                (name, rule)
+            })
+            .collect();
+        let undefined = undefined_calls(&rules);
+
+        Vm { rules, undefined }
+    }
+
+    /// Parses, validates, and optimizes `grammar` with
+    /// [`parse_and_optimize`](../pest_meta/fn.parse_and_optimize.html), returning a `Vm` which
+    /// interprets it or every error that was found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate pest_vm;
+    /// # use pest_vm::Vm;
+    /// # fn main() {
+    /// assert!(Vm::from_grammar("a = { \"a\" }").is_ok());
+    /// assert_eq!(Vm::from_grammar("a = { \"a\"").unwrap_err().len(), 1);
+    /// assert_eq!(Vm::from_grammar("a = { a }").unwrap_err().len(), 1);
+    /// # }
+    /// ```
+    pub fn from_grammar(grammar: &str) -> Result<Vm, Vec<Error<'_, GrammarRule>>> {
+        let (_, rules) = pest_meta::parse_and_optimize(grammar)?;
+
+        Ok(Vm::new(rules))
+    }
+
+    /// Parses `input` starting from the rule named `rule`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `CustomErrorPos` at the start of `input` if the grammar defines no rule named
+    /// `rule` or `rule` calls a rule which is not defined, or the `ParsingError` of `input`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate pest_vm;
+    /// # use pest_vm::Vm;
+    /// # fn main() {
+    /// let vm = Vm::from_grammar("ab = { \"a\" ~ \"b\" }").unwrap();
+    ///
+    /// assert_eq!(format!("{}", vm.parse("ab", "ab").unwrap()), r#"["ab"(0, 2)]"#);
+    /// assert!(vm.parse("ab", "ba").is_err());
+    /// assert!(vm.parse("ba", "ba").is_err());
+    /// # }
+    /// ```
+    pub fn parse<'a>(
+        &'a self,
+        rule: &'a str,
+        input: &'a str
+    ) -> Result<Pairs<'a, &'a str>, Error<'a, &'a str>> {
+        let undefined = if self.rules.contains_key(rule) {
+            self.undefined.get(rule).map(|name| name.as_str())
+        } else {
+            Some(rule)
+        };
+
+        if let Some(undefined) = undefined {
+            return Err(Error::CustomErrorPos {
+                message: format!("undefined rule {}", undefined),
+                pos: Position::from_start(input)
+            });
+        }
+
+        pest::state(input, |state, pos| self.parse_rule(rule, pos, state))
+    }
+
+    fn parse_rule<'a>(
+        &'a self,
+        rule: &'a str,
+        pos: Position<'a>,
+        state: &mut ParserState<'a, &'a str>
+    ) -> Result<Position<'a>, Position<'a>> {
+        match rule {
+            "any" => return pos.skip(1),
+            "eoi" => return pos.at_end(),
+            "soi" => return pos.at_start(),
+            "peek" => return state.stack_peek(pos),
+            "peek_all" => return state.stack_peek_all(pos),
+            "pop" => return state.stack_pop(pos),
+            "pop_all" => return state.stack_pop_all(pos),
+            "drop" => return state.stack_drop(pos),
+            "ASCII_DIGIT" => return pos.match_range('0'..'9'),
+            "ASCII_NONZERO_DIGIT" => return pos.match_range('1'..'9'),
+            "ASCII_BIN_DIGIT" => return pos.match_range('0'..'1'),
+            "ASCII_OCT_DIGIT" => return pos.match_range('0'..'7'),
+            "ASCII_HEX_DIGIT" => {
+                return pos.match_range('0'..'9')
+                    .or_else(|pos| pos.match_range('a'..'f'))
+                    .or_else(|pos| pos.match_range('A'..'F'))
+            }
+            "ASCII_ALPHA_LOWER" => return pos.match_range('a'..'z'),
+            "ASCII_ALPHA_UPPER" => return pos.match_range('A'..'Z'),
+            "ASCII_ALPHA" => {
+                return pos.match_range('a'..'z').or_else(|pos| pos.match_range('A'..'Z'))
+            }
+            "ASCII_ALPHANUMERIC" => {
+                return pos.match_range('a'..'z')
+                    .or_else(|pos| pos.match_range('A'..'Z'))
+                    .or_else(|pos| pos.match_range('0'..'9'))
+            }
+            "ASCII" => return pos.match_range('\x00'..'\x7f'),
+            "NEWLINE" => {
+                return pos.match_string("\n")
+                    .or_else(|pos| pos.match_string("\r\n"))
+                    .or_else(|pos| pos.match_string("\r"))
+            }
+            _ => ()
+        };
+
+        if let Some(predicate) = unicode::by_name(rule) {
+            return pos.match_char_by(predicate);
+        }
+
+        let rule = &self.rules[rule];
+        let name = rule.name.as_str();
+
+        match rule.ty {
+            RuleType::Normal => {
+                state.rule(name, pos, |state, pos| self.parse_body(rule, pos, state))
+            }
+            RuleType::Silent => self.parse_body(rule, pos, state),
+            RuleType::Atomic => state.rule(name, pos, |state, pos| {
+                state.atomic(Atomicity::Atomic, move |state| {
+                    self.parse_expr(&rule.expr, pos, state)
+                })
+            }),
+            RuleType::CompoundAtomic => state.atomic(Atomicity::CompoundAtomic, move |state| {
+                state.rule(name, pos, |state, pos| self.parse_expr(&rule.expr, pos, state))
+            }),
+            RuleType::NonAtomic => state.atomic(Atomicity::NonAtomic, move |state| {
+                state.rule(name, pos, |state, pos| self.parse_body(rule, pos, state))
+            })
+        }
+    }
+
+    fn parse_body<'a>(
+        &'a self,
+        rule: &'a Rule,
+        pos: Position<'a>,
+        state: &mut ParserState<'a, &'a str>
+    ) -> Result<Position<'a>, Position<'a>> {
+        if rule.name == "whitespace" || rule.name == "comment" {
+            state.atomic(Atomicity::Atomic, move |state| {
+                self.parse_expr(&rule.expr, pos, state)
+            })
+        } else {
+            self.parse_expr(&rule.expr, pos, state)
+        }
+    }
+
+    fn parse_expr<'a>(
+        &'a self,
+        expr: &'a Expr,
+        pos: Position<'a>,
+        state: &mut ParserState<'a, &'a str>
+    ) -> Result<Position<'a>, Position<'a>> {
+        match *expr {
+            Expr::Str(ref string) => state.match_string(pos, string),
+            Expr::Insens(ref string) => state.match_insensitive(pos, string),
+            Expr::Range(ref start, ref end) => {
+                let start = start.chars().next().unwrap();
+                let end = end.chars().next().unwrap();
+
+                state.match_range(pos, start..end)
+            }
+            Expr::InsensRange(ref start, ref end) => {
+                let start = start.chars().next().unwrap();
+                let end = end.chars().next().unwrap();
+
+                state.match_range_insensitive(pos, start..end)
+            }
+            Expr::Ident(ref ident) => self.parse_rule(ident, pos, state),
+            Expr::PeekSlice(start, end) => state.stack_peek_slice(pos, start, end),
+            Expr::PosPred(ref expr) => state.lookahead(true, move |state| {
+                pos.lookahead(true, |pos| self.parse_expr(expr, pos, state))
+            }),
+            Expr::NegPred(ref expr) => state.lookahead(false, move |state| {
+                pos.lookahead(false, |pos| self.parse_expr(expr, pos, state))
+            }),
+            Expr::Seq(ref lhs, ref rhs) => state.sequence(move |state| {
+                pos.sequence(|pos| {
+                    self.parse_expr(lhs, pos, state)
+                        .and_then(|pos| self.skip(pos, state))
+                        .and_then(|pos| self.parse_expr(rhs, pos, state))
+                })
+            }),
+            Expr::Choice(ref lhs, ref rhs) => self.parse_expr(lhs, pos, state)
+                .or_else(|pos| self.parse_expr(rhs, pos, state)),
+            Expr::Opt(ref expr) => pos.optional(|pos| self.parse_expr(expr, pos, state)),
+            Expr::Rep(ref expr) => state.sequence(move |state| {
+                pos.sequence(|pos| {
+                    pos.optional(|pos| self.parse_expr(expr, pos, state))
+                        .and_then(|pos| {
+                            pos.repeat(|pos| {
+                                state.sequence(move |state| {
+                                    pos.sequence(|pos| {
+                                        self.skip(pos, state)
+                                            .and_then(|pos| self.parse_expr(expr, pos, state))
+                                    })
+                                })
+                            })
+                        })
+                })
+            }),
+            Expr::RepOnce(ref expr) => self.parse_repetition(expr, 1, None, pos, state),
+            Expr::RepExact(ref expr, num) => {
+                self.parse_repetition(expr, num, Some(num), pos, state)
+            }
+            Expr::RepMin(ref expr, min) => self.parse_repetition(expr, min, None, pos, state),
+            Expr::RepMax(ref expr, max) => self.parse_repetition(expr, 0, Some(max), pos, state),
+            Expr::RepMinMax(ref expr, min, max) => {
+                self.parse_repetition(expr, min, Some(max), pos, state)
+            }
+            Expr::Push(ref expr) => {
+                let start = pos.clone();
+
+                match self.parse_expr(expr, pos, state) {
+                    Ok(end) => {
+                        state.stack.push(start.span(&end));
+                        Ok(end)
+                    }
+                    Err(pos) => Err(pos)
+                }
+            }
+            Expr::NodeTag(ref expr, ref tag) => {
+                state.tag_node(tag, move |state| self.parse_expr(expr, pos, state))
+            }
+        }
+    }
+
+    // Bounded repetitions are unrolled by the optimizer, but are also interpreted for rules
+    // which were not optimized.
+    fn parse_repetition<'a>(
+        &'a self,
+        expr: &'a Expr,
+        min: u32,
+        max: Option<u32>,
+        pos: Position<'a>,
+        state: &mut ParserState<'a, &'a str>
+    ) -> Result<Position<'a>, Position<'a>> {
+        state.sequence(move |state| {
+            pos.sequence(|mut pos| {
+                let mut count = 0;
+
+                loop {
+                    match max {
+                        Some(max) if count >= max => break,
+                        _ => ()
+                    }
+
+                    let result = if count == 0 {
+                        self.parse_expr(expr, pos.clone(), state)
+                    } else {
+                        let start = pos.clone();
+
+                        state.sequence(move |state| {
+                            start.sequence(|pos| {
+                                self.skip(pos, state)
+                                    .and_then(|pos| self.parse_expr(expr, pos, state))
+                            })
+                        })
+                    };
+
+                    match result {
+                        Ok(next) => {
+                            pos = next;
+                            count += 1;
+                        }
+                        Err(pos) if count < min => return Err(pos),
+                        Err(_) => break
+                    }
+                }
+
+                Ok(pos)
+            })
+        })
+    }
+
+    fn skip<'a>(
+        &'a self,
+        pos: Position<'a>,
+        state: &mut ParserState<'a, &'a str>
+    ) -> Result<Position<'a>, Position<'a>> {
+        if state.atomicity != Atomicity::NonAtomic {
+            return Ok(pos);
+        }
+
+        let whitespace = self.rules.contains_key("whitespace");
+        let comment = self.rules.contains_key("comment");
+
+        match (whitespace, comment) {
+            (false, false) => Ok(pos),
+            (true, false) => pos.repeat(|pos| self.parse_rule("whitespace", pos, state)),
+            (false, true) => pos.repeat(|pos| self.parse_rule("comment", pos, state)),
+            (true, true) => state.sequence(move |state| {
+                pos.sequence(|pos| {
+                    pos.repeat(|pos| self.parse_rule("whitespace", pos, state))
+                        .and_then(|pos| {
+                            pos.repeat(|pos| {
+                                state.sequence(move |state| {
+                                    pos.sequence(|pos| {
+                                        self.parse_rule("comment", pos, state).and_then(|pos| {
+                                            pos.repeat(|pos| {
+                                                self.parse_rule("whitespace", pos, state)
+                                            })
+                                        })
+                                    })
+                                })
+                            })
+                        })
+                })
+            })
+        }
+    }
+}
+
+// Strings and characters are kept escaped in the AST since pest_derive pastes them into the
+// generated code as they are.
+fn unescape_expr(expr: Expr) -> Expr {
+    expr.map_bottom_up(|expr| match expr {
+        Expr::Str(string) => Expr::Str(unescape(&string)),
+        Expr::Insens(string) => Expr::Insens(unescape(&string)),
+        Expr::Range(start, end) => {
+            Expr::Range(unescape(&start[1..start.len() - 1]), unescape(&end[1..end.len() - 1]))
+        }
+        Expr::InsensRange(start, end) => Expr::InsensRange(
+            unescape(&start[1..start.len() - 1]),
+            unescape(&end[1..end.len() - 1])
+        ),
+        expr => expr
+    })
+}
+
+fn unescape(string: &str) -> String {
+    let mut result = String::new();
+    let mut chars = string.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        let c = match chars.next().unwrap() {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            'x' => {
+                let code: String = chars.by_ref().take(2).collect();
+
+                char_of(&code)
+            }
+            'u' => {
+                let code: String = chars
+                    .by_ref()
+                    .skip(1)
+                    .take_while(|&c| c != '}')
+                    .collect();
+
+                char_of(&code)
+            }
+            c => c
+        };
+
+        result.push(c);
+    }
+
+    result
+}
+
+// Escapes which do not stand for a `char` are rejected by pest_meta's validator. They are only
+// replaced here for rules which were not validated.
+fn char_of(code: &str) -> char {
+    u32::from_str_radix(code, 16)
+        .ok()
+        .and_then(std::char::from_u32)
+        .unwrap_or(std::char::REPLACEMENT_CHARACTER)
+}
+
+// Maps every rule which calls an undefined rule, directly or through other rules, to the first
+// such undefined rule that is found.
+fn undefined_calls(rules: &HashMap<String, Rule>) -> HashMap<String, String> {
+    let mut undefined = HashMap::new();
+
+    for name in rules.keys() {
+        let mut visited = HashSet::new();
+        let mut stack = vec![name.as_str()];
+
+        while let Some(called) = stack.pop() {
+            if !visited.insert(called) {
+                continue;
+            }
+
+            match rules.get(called) {
+                Some(rule) => calls(&rule.expr, &mut stack),
+                None if BUILTINS.contains(&called) || unicode::by_name(called).is_some() => (),
+                None => {
+                    undefined.insert(name.clone(), called.to_owned());
+                    break;
+                }
+            }
+        }
+    }
+
+    undefined
+}
+
+fn calls<'a>(expr: &'a Expr, names: &mut Vec<&'a str>) {
+    match *expr {
+        Expr::Ident(ref name) => names.push(name),
+        Expr::PosPred(ref expr)
+        | Expr::NegPred(ref expr)
+        | Expr::Opt(ref expr)
+        | Expr::Rep(ref expr)
+        | Expr::RepOnce(ref expr)
+        | Expr::RepExact(ref expr, _)
+        | Expr::RepMin(ref expr, _)
+        | Expr::RepMax(ref expr, _)
+        | Expr::RepMinMax(ref expr, _, _)
+        | Expr::Push(ref expr)
+        | Expr::NodeTag(ref expr, _) => calls(expr, names),
+        Expr::Seq(ref lhs, ref rhs) | Expr::Choice(ref lhs, ref rhs) => {
+            calls(lhs, names);
+            calls(rhs, names);
+        }
+        _ => ()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescape_all() {
+        assert_eq!(unescape(r#"a\n\r\t\\\0\'\"\x41\u{1F600}"#), "a\n\r\t\\\0'\"A\u{1F600}");
+    }
+}
@@ -0,0 +1,353 @@
+// pest. The Elegant Parser
+// Copyright (c) 2018 Dragoș Tiselice
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+extern crate pest_meta;
+extern crate pest_vm;
+
+use pest_meta::ast::{Expr, Rule, RuleType};
+use pest_vm::Vm;
+
+fn vm() -> Vm {
+    Vm::from_grammar(include_str!("grammar.pest")).unwrap()
+}
+
+fn parse(rule: &str, input: &str) -> Option<String> {
+    let vm = vm();
+    let result = vm.parse(rule, input).ok().map(|pairs| format!("{}", pairs));
+
+    result
+}
+
+#[test]
+fn string() {
+    assert_eq!(parse("string", "abc").unwrap(), r#"["string"(0, 3)]"#);
+}
+
+#[test]
+fn insensitive() {
+    assert_eq!(parse("insensitive", "aBC").unwrap(), r#"["insensitive"(0, 3)]"#);
+}
+
+#[test]
+fn insensitive_unicode() {
+    assert_eq!(
+        parse("insensitive_unicode", "STRASSE").unwrap(),
+        r#"["insensitive_unicode"(0, 7)]"#
+    );
+    assert_eq!(parse("insensitive_unicode", "STRAS"), None);
+}
+
+#[test]
+fn range() {
+    assert_eq!(parse("range", "6").unwrap(), r#"["range"(0, 1)]"#);
+    assert_eq!(parse("range", "9").unwrap(), r#"["range"(0, 1)]"#);
+    assert_eq!(parse("range", "a"), None);
+}
+
+#[test]
+fn insensitive_range() {
+    assert_eq!(parse("insensitive_range", "B").unwrap(), r#"["insensitive_range"(0, 1)]"#);
+    assert_eq!(parse("insensitive_range", "d"), None);
+}
+
+#[test]
+fn ident() {
+    assert_eq!(parse("ident", "abc").unwrap(), r#"["ident"(0, 3, ["string"(0, 3)])]"#);
+}
+
+#[test]
+fn pos_pred() {
+    assert_eq!(parse("pos_pred", "abc").unwrap(), r#"["pos_pred"(0, 0)]"#);
+}
+
+#[test]
+fn neg_pred() {
+    assert_eq!(parse("neg_pred", "").unwrap(), r#"["neg_pred"(0, 0)]"#);
+    assert_eq!(parse("neg_pred", "abc"), None);
+}
+
+#[test]
+fn sequence() {
+    assert_eq!(
+        parse("sequence", "abc   abc").unwrap(),
+        r#"["sequence"(0, 9, ["string"(0, 3), "string"(6, 9)])]"#
+    );
+}
+
+#[test]
+fn sequence_compound() {
+    assert_eq!(
+        parse("sequence_compound", "abcabc").unwrap(),
+        r#"["sequence_compound"(0, 6, ["string"(0, 3), "string"(3, 6)])]"#
+    );
+    assert_eq!(parse("sequence_compound", "abc abc"), None);
+}
+
+#[test]
+fn sequence_atomic() {
+    assert_eq!(parse("sequence_atomic", "abcabc").unwrap(), r#"["sequence_atomic"(0, 6)]"#);
+    assert_eq!(parse("sequence_atomic", "abc abc"), None);
+}
+
+#[test]
+fn sequence_non_atomic() {
+    assert_eq!(
+        parse("sequence_non_atomic", "abc   abc").unwrap(),
+        r#"["sequence_non_atomic"(0, 9, ["sequence"(0, 9, ["string"(0, 3), "string"(6, 9)])])]"#
+    );
+}
+
+#[test]
+fn choice() {
+    assert_eq!(parse("choice", "abc").unwrap(), r#"["choice"(0, 3, ["string"(0, 3)])]"#);
+    assert_eq!(parse("choice", "0").unwrap(), r#"["choice"(0, 1, ["range"(0, 1)])]"#);
+}
+
+#[test]
+fn optional() {
+    assert_eq!(parse("optional", "abc").unwrap(), r#"["optional"(0, 3, ["string"(0, 3)])]"#);
+    assert_eq!(parse("optional", "").unwrap(), r#"["optional"(0, 0)]"#);
+}
+
+#[test]
+fn repeat() {
+    assert_eq!(parse("repeat", "").unwrap(), r#"["repeat"(0, 0)]"#);
+    assert_eq!(
+        parse("repeat", "abc   abc").unwrap(),
+        r#"["repeat"(0, 9, ["string"(0, 3), "string"(6, 9)])]"#
+    );
+}
+
+#[test]
+fn repeat_atomic() {
+    assert_eq!(parse("repeat_atomic", "abcabc").unwrap(), r#"["repeat_atomic"(0, 6)]"#);
+    assert_eq!(parse("repeat_atomic", "abc abc").unwrap(), r#"["repeat_atomic"(0, 3)]"#);
+}
+
+#[test]
+fn repeat_once() {
+    assert_eq!(parse("repeat_once", ""), None);
+    assert_eq!(
+        parse("repeat_once", "abc   abc").unwrap(),
+        r#"["repeat_once"(0, 9, ["string"(0, 3), "string"(6, 9)])]"#
+    );
+}
+
+#[test]
+fn repeat_min_max() {
+    assert_eq!(
+        parse("repeat_min_max", "abc abc abc").unwrap(),
+        r#"["repeat_min_max"(0, 11, ["string"(0, 3), "string"(4, 7), "string"(8, 11)])]"#
+    );
+    assert_eq!(parse("repeat_min_max", "abc"), None);
+}
+
+#[test]
+fn repeat_exact() {
+    assert_eq!(
+        parse("repeat_exact", "abc abc").unwrap(),
+        r#"["repeat_exact"(0, 7, ["string"(0, 3), "string"(4, 7)])]"#
+    );
+}
+
+#[test]
+fn repeat_max_atomic() {
+    assert_eq!(parse("repeat_max_atomic", "abcabc").unwrap(), r#"["repeat_max_atomic"(0, 6)]"#);
+    assert_eq!(
+        parse("repeat_max_atomic", "abcabcabc").unwrap(),
+        r#"["repeat_max_atomic"(0, 6)]"#
+    );
+}
+
+#[test]
+fn repeat_comment() {
+    assert_eq!(
+        parse("repeat_once", "abc$$$ $$$abc").unwrap(),
+        r#"["repeat_once"(0, 13, ["string"(0, 3), "string"(10, 13)])]"#
+    );
+}
+
+#[test]
+fn peek() {
+    assert_eq!(
+        parse("peek_", "0111").unwrap(),
+        r#"["peek_"(0, 4, ["range"(0, 1), "range"(1, 2)])]"#
+    );
+}
+
+#[test]
+fn pop() {
+    assert_eq!(
+        parse("pop_", "0110").unwrap(),
+        r#"["pop_"(0, 4, ["range"(0, 1), "range"(1, 2)])]"#
+    );
+}
+
+#[test]
+fn pop_fail() {
+    assert_eq!(
+        parse("pop_fail", "010").unwrap(),
+        r#"["pop_fail"(0, 3, ["range"(0, 1), "range"(1, 2)])]"#
+    );
+}
+
+#[test]
+fn pop_all() {
+    assert_eq!(
+        parse("pop_all_", "0110").unwrap(),
+        r#"["pop_all_"(0, 4, ["range"(0, 1), "range"(1, 2)])]"#
+    );
+}
+
+#[test]
+fn drop() {
+    assert_eq!(
+        parse("drop_", "010").unwrap(),
+        r#"["drop_"(0, 3, ["range"(0, 1), "range"(1, 2)])]"#
+    );
+}
+
+#[test]
+fn peek_slice_23() {
+    assert_eq!(
+        parse("peek_slice_23", "0123412").unwrap(),
+        concat!(
+            r#"["peek_slice_23"(0, 7, ["range"(0, 1), "range"(1, 2), "range"(2, 3), "#,
+            r#""range"(3, 4), "range"(4, 5)])]"#
+        )
+    );
+}
+
+#[test]
+fn push_backtrack() {
+    assert_eq!(
+        parse("push_backtrack", "010").unwrap(),
+        r#"["push_backtrack"(0, 3, ["range"(0, 1), "range"(1, 2)])]"#
+    );
+}
+
+#[test]
+fn node_tag() {
+    let vm = vm();
+    let pairs = vm.parse("node_tag", "abc 1 2").unwrap();
+
+    assert_eq!(
+        format!("{}", pairs),
+        r#"["node_tag"(0, 7, [#lhs = "string"(0, 3), #rhs = "range"(4, 5), #rhs = "range"(6, 7)])]"#
+    );
+    assert_eq!(pairs.find_tagged("rhs").count(), 2);
+}
+
+#[test]
+fn ascii_digits() {
+    assert_eq!(parse("ascii_digits", "6 90").unwrap(), r#"["ascii_digits"(0, 4)]"#);
+    assert_eq!(parse("ascii_digits", "a"), None);
+}
+
+#[test]
+fn newline() {
+    assert_eq!(parse("newline", "\n\r\n\r").unwrap(), r#"["newline"(0, 4)]"#);
+}
+
+#[test]
+fn unicode() {
+    assert_eq!(parse("unicode", "নামে").unwrap(), r#"["unicode"(0, 12)]"#);
+    assert_eq!(parse("unicode", "1a"), None);
+}
+
+#[test]
+fn escapes() {
+    let vm = Vm::from_grammar("a = { \"\\t\" ~ '\\x41'..'\\u{5A}' }").unwrap();
+
+    assert!(vm.parse("a", "\tQ").is_ok());
+    assert!(vm.parse("a", "\tq").is_err());
+}
+
+#[test]
+fn error() {
+    let vm = vm();
+    let error = vm.parse("sequence", "abc x").unwrap_err();
+
+    assert_eq!(
+        format!("{}", error),
+        [
+            " --> 1:5",
+            "  |",
+            "1 | abc x",
+            "  |     ^---",
+            "  |",
+            "  = expected \"string\""
+        ].join("\n")
+    );
+}
+
+#[test]
+fn undefined_rule() {
+    let vm = vm();
+    let error = vm.parse("b", "").unwrap_err();
+
+    assert_eq!(
+        format!("{}", error),
+        [" --> 1:1", "  |", "1 | ", "  | ^---", "  |", "  = undefined rule b"].join("\n")
+    );
+}
+
+#[test]
+fn undefined_rule_called() {
+    let vm = Vm::new(vec![
+        Rule {
+            name: "a".to_owned(),
+            ty: RuleType::Normal,
+            doc: vec![],
+            expr: Expr::Seq(
+                Box::new(Expr::Ident("any".to_owned())),
+                Box::new(Expr::Ident("b".to_owned()))
+            )
+        },
+        Rule {
+            name: "b".to_owned(),
+            ty: RuleType::Normal,
+            doc: vec![],
+            expr: Expr::Opt(Box::new(Expr::Ident("c".to_owned())))
+        },
+    ]);
+    let error = vm.parse("a", "a").unwrap_err();
+
+    assert_eq!(
+        format!("{}", error),
+        [" --> 1:1", "  |", "1 | a", "  | ^---", "  |", "  = undefined rule c"].join("\n")
+    );
+}
+
+#[test]
+fn invalid_escape() {
+    assert_eq!(Vm::from_grammar("a = { \"\\u{D800}\" }").unwrap_err().len(), 1);
+    assert_eq!(Vm::from_grammar("a = { \"\\xFF\" }").unwrap_err().len(), 1);
+}
+
+#[test]
+fn unoptimized_repetitions() {
+    let rule = |expr| Rule {
+        name: "a".to_owned(),
+        ty: RuleType::Normal,
+        doc: vec![],
+        expr
+    };
+    let a = || Box::new(Expr::Str("a".to_owned()));
+
+    let vm = Vm::new(vec![rule(Expr::RepMinMax(a(), 1, 2))]);
+    assert_eq!(format!("{}", vm.parse("a", "aaa").unwrap()), r#"["a"(0, 2)]"#);
+    assert!(vm.parse("a", "").is_err());
+
+    let vm = Vm::new(vec![rule(Expr::RepOnce(a()))]);
+    assert_eq!(format!("{}", vm.parse("a", "aaa").unwrap()), r#"["a"(0, 3)]"#);
+
+    let vm = Vm::new(vec![rule(Expr::RepExact(a(), 2))]);
+    assert!(vm.parse("a", "a").is_err());
+}